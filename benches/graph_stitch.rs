@@ -0,0 +1,92 @@
+//! Times `graph_stitch` on growing amounts of segments: scattered squares
+//! and a single ring, which are sparse, and a lattice where every corner
+//! branches three ways, which is dense.
+//!
+//! Run with `cargo bench --bench graph_stitch`.  The time per segment should
+//! stay roughly flat as the input grows.
+
+#[macro_use]
+extern crate criterion;
+extern crate euclid;
+extern crate line_stitch;
+
+use criterion::Criterion;
+use euclid::{UnknownUnit, point2};
+use line_stitch::{graph_stitch, PathSegment};
+
+const EPSILON: f32 = 0.001;
+
+/// A grid of unit squares, each made out of four two-point segments, listed
+/// in an order that scatters the sides of each square.
+fn squares(count: usize) -> Vec<PathSegment<UnknownUnit>> {
+    let side = (count as f32).sqrt().ceil() as usize;
+    let mut out = Vec::with_capacity(count * 4);
+    for edge in 0..4 {
+        for i in 0..count {
+            let x = (i % side) as f32 * 2.0;
+            let y = (i / side) as f32 * 2.0;
+            let corners = [
+                point2(x, y),
+                point2(x + 1.0, y),
+                point2(x + 1.0, y + 1.0),
+                point2(x, y + 1.0),
+            ];
+            out.push(PathSegment::new(
+                vec![corners[edge], corners[(edge + 1) % 4]],
+                EPSILON,
+            ));
+        }
+    }
+    out
+}
+
+/// One closed polygon with `count` sides.
+fn ring(count: usize) -> Vec<PathSegment<UnknownUnit>> {
+    let radius = count as f32 / 4.0;
+    let at = |i: usize| {
+        let angle = (i % count) as f32 / count as f32 * 2.0 * ::std::f32::consts::PI;
+        point2(radius * angle.cos(), radius * angle.sin())
+    };
+    (0..count)
+        .map(|i| PathSegment::new(vec![at(i), at(i + 1)], EPSILON))
+        .collect()
+}
+
+/// About `count` segments on a square lattice, running right, up and
+/// diagonally up from every corner, with one segment from the far corner
+/// back to the start.  Every corner is reached by many paths of different
+/// lengths.
+fn lattice(count: usize) -> Vec<PathSegment<UnknownUnit>> {
+    let side = ((count / 3) as f32).sqrt().ceil() as usize;
+    let at = |x: usize, y: usize| point2(x as f32, y as f32);
+    let mut out = Vec::with_capacity(side * side * 3 + 1);
+    for x in 0..side {
+        for y in 0..side {
+            out.push(PathSegment::new(vec![at(x, y), at(x + 1, y)], EPSILON));
+            out.push(PathSegment::new(vec![at(x, y), at(x, y + 1)], EPSILON));
+            out.push(PathSegment::new(vec![at(x, y), at(x + 1, y + 1)], EPSILON));
+        }
+    }
+    out.push(PathSegment::new(vec![at(side, side), point2(-1.0, side as f32 + 1.0), at(0, 0)], EPSILON));
+    out
+}
+
+fn bench(c: &mut Criterion, name: &str, make: fn(usize) -> Vec<PathSegment<UnknownUnit>>, sizes: Vec<usize>) {
+    c.bench_function_over_inputs(
+        name,
+        move |b, &len| b.iter_with_setup(|| make(len), graph_stitch),
+        sizes,
+    );
+}
+
+fn sparse(c: &mut Criterion) {
+    bench(c, "squares", |len| squares(len / 4), vec![1_000, 10_000, 100_000]);
+    bench(c, "ring", ring, vec![1_000, 10_000, 100_000]);
+}
+
+fn dense(c: &mut Criterion) {
+    bench(c, "lattice", lattice, vec![1_000, 10_000, 100_000]);
+}
+
+criterion_group!(benches, sparse, dense);
+criterion_main!(benches);
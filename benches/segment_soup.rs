@@ -0,0 +1,64 @@
+//! Compares `stitch_segment_soup` against `connect_obvious` on two-point
+//! segments shaped like marching squares output.
+//!
+//! Run with `cargo bench --bench segment_soup`.
+
+#[macro_use]
+extern crate criterion;
+extern crate euclid;
+extern crate line_stitch;
+
+use criterion::{Criterion, Fun};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+use line_stitch::{connect_obvious, stitch_segment_soup, PathSegment};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+/// Concentric rings around the origin, cut into unit-ish edges and listed
+/// ring by ring with every other edge flipped.
+fn rings(count: usize) -> Vec<(Point, Point)> {
+    let mut out = Vec::with_capacity(count);
+    let mut ring = 1;
+    while out.len() < count {
+        let sides = ring * 8;
+        let radius = ring as f32 * 2.0;
+        let at = |i: usize| {
+            let angle = (i % sides) as f32 / sides as f32 * 2.0 * ::std::f32::consts::PI;
+            point2(radius * angle.cos(), radius * angle.sin())
+        };
+        for i in 0..sides {
+            if i % 2 == 0 {
+                out.push((at(i), at(i + 1)));
+            } else {
+                out.push((at(i + 1), at(i)));
+            }
+        }
+        ring += 1;
+    }
+    out
+}
+
+fn compare(c: &mut Criterion, name: &str, pairs: Vec<(Point, Point)>) {
+    let soup = Fun::new("soup", |b, pairs: &Vec<(Point, Point)>| {
+        b.iter(|| -> Vec<PathSegment<UnknownUnit>> { stitch_segment_soup(pairs.clone(), EPSILON) })
+    });
+    let general = Fun::new("connect_obvious", |b, pairs: &Vec<(Point, Point)>| {
+        b.iter(|| -> Vec<PathSegment<UnknownUnit>> {
+            connect_obvious(pairs.iter().map(|&(a, b)| vec![a, b]), EPSILON, false, false)
+        })
+    });
+    c.bench_functions(name, vec![soup, general], pairs);
+}
+
+fn small(c: &mut Criterion) {
+    compare(c, "rings 10k", rings(10_000));
+}
+
+fn large(c: &mut Criterion) {
+    compare(c, "rings 100k", rings(100_000));
+}
+
+criterion_group!(benches, small, large);
+criterion_main!(benches);
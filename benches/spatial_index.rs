@@ -0,0 +1,82 @@
+//! Compares the quadtree and grid backends on a traced raster, where the
+//! endpoints are spread evenly, and on clustered strokes, where they are not.
+//!
+//! Run with `cargo bench --bench spatial_index`.
+
+#[macro_use]
+extern crate criterion;
+extern crate euclid;
+extern crate line_stitch;
+
+use criterion::{Criterion, Fun};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+use line_stitch::{run_pipeline, IndexBackend, PipelineConfig};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+/// Every pixel edge of a checkerboard, as marching squares would trace it.
+fn traced_raster(side: usize) -> Vec<Vec<Point>> {
+    let mut out = vec![];
+    for x in 0..side {
+        for y in 0..side {
+            if (x + y) % 2 != 0 {
+                continue;
+            }
+            let (x, y) = (x as f32, y as f32);
+            out.push(vec![point2(x, y), point2(x + 1.0, y)]);
+            out.push(vec![point2(x + 1.0, y), point2(x + 1.0, y + 1.0)]);
+            out.push(vec![point2(x + 1.0, y + 1.0), point2(x, y + 1.0)]);
+            out.push(vec![point2(x, y + 1.0), point2(x, y)]);
+        }
+    }
+    out
+}
+
+/// Short strokes bunched around a handful of far apart centers.
+fn clustered(count: usize) -> Vec<Vec<Point>> {
+    let centers = [(0.0, 0.0), (1000.0, 0.0), (0.0, 1000.0), (1000.0, 1000.0)];
+    (0..count)
+        .map(|i| {
+            let (cx, cy) = centers[i % centers.len()];
+            let angle = i as f32 * 0.618;
+            let radius = (i / centers.len()) as f32 * 0.01;
+            let a = point2(cx + radius * angle.cos(), cy + radius * angle.sin());
+            let b = point2(a.x + 0.005, a.y);
+            vec![a, b]
+        })
+        .collect()
+}
+
+fn compare(c: &mut Criterion, name: &str, input: Vec<Vec<Point>>) {
+    let backends = vec![
+        ("quadtree", IndexBackend::default()),
+        ("grid", IndexBackend::Grid { cell_size: 4.0 }),
+    ];
+    let functions = backends
+        .into_iter()
+        .map(|(label, backend)| {
+            Fun::new(label, move |b, input: &Vec<Vec<Point>>| {
+                let config = PipelineConfig {
+                    epsilon: EPSILON.into(),
+                    index: backend,
+                    ..PipelineConfig::default()
+                };
+                b.iter(|| run_pipeline(input.clone(), &config))
+            })
+        })
+        .collect();
+    c.bench_functions(name, functions, input);
+}
+
+fn traced(c: &mut Criterion) {
+    compare(c, "traced raster", traced_raster(100));
+}
+
+fn strokes(c: &mut Criterion) {
+    compare(c, "clustered strokes", clustered(20_000));
+}
+
+criterion_group!(benches, traced, strokes);
+criterion_main!(benches);
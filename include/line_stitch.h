@@ -0,0 +1,53 @@
+/*
+ * C declarations for the `ffi` feature of line-stitch.  See src/ffi.rs for
+ * the documentation of each function.
+ *
+ * Points cross the boundary as interleaved x, y pairs of floats.  Every
+ * object handed out must be released with its matching _free function.
+ * Functions never unwind: on failure they return NULL, zero or false.
+ *
+ * Build the library with
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * or with --crate-type staticlib for a static one.
+ */
+
+#ifndef LINE_STITCH_H
+#define LINE_STITCH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct LineStitchSegments LineStitchSegments;
+typedef struct LineStitchPaths LineStitchPaths;
+
+typedef struct LineStitchConfig {
+    float epsilon;
+    bool epsilon_is_relative;
+    bool only_starts;
+    bool allow_ambiguous;
+    bool prune;
+    bool graph_stitch;
+} LineStitchConfig;
+
+LineStitchSegments *line_stitch_segments_new(void);
+void line_stitch_segments_push(LineStitchSegments *segments, const float *coords, size_t point_count);
+void line_stitch_segments_free(LineStitchSegments *segments);
+
+LineStitchPaths *line_stitch_run(const LineStitchSegments *segments, const LineStitchConfig *config);
+
+size_t line_stitch_paths_len(const LineStitchPaths *paths);
+const float *line_stitch_paths_points(const LineStitchPaths *paths, size_t index, size_t *point_count);
+bool line_stitch_paths_closed(const LineStitchPaths *paths, size_t index);
+void line_stitch_paths_free(LineStitchPaths *paths);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,99 @@
+//! Python bindings for line-stitch.
+//!
+//! Build with `maturin develop` from this directory, then:
+//!
+//! ```python
+//! import line_stitch
+//! paths = line_stitch.stitch([[(0, 0), (1, 0)], [(1, 0), (1, 1)]], epsilon=0.001)
+//! ```
+
+use euclid::{point2, UnknownUnit};
+use line_stitch::{flat_from_points, points_from_tuples, run_pipeline, Epsilon, PathSegment, PipelineConfig, Points};
+use numpy::{PyArray1, PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Reads one polyline, given either as an `(n, 2)` numpy array or as a
+/// sequence of `(x, y)` pairs.
+fn polyline(item: &PyAny) -> PyResult<Points<UnknownUnit>> {
+    if let Ok(array) = item.extract::<PyReadonlyArray2<f64>>() {
+        let array = array.as_array();
+        check_columns(array.ncols())?;
+        return Ok(array.rows().into_iter().map(|r| point2(r[0] as f32, r[1] as f32)).collect());
+    }
+    if let Ok(array) = item.extract::<PyReadonlyArray2<f32>>() {
+        let array = array.as_array();
+        check_columns(array.ncols())?;
+        return Ok(array.rows().into_iter().map(|r| point2(r[0], r[1])).collect());
+    }
+    let pairs: Vec<(f32, f32)> = item.extract()?;
+    Ok(points_from_tuples(pairs))
+}
+
+fn check_columns(columns: usize) -> PyResult<()> {
+    if columns == 2 {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err("polyline arrays must have shape (n, 2)"))
+    }
+}
+
+/// Joins polylines whose ends touch.
+///
+/// Returns a list of `(n, 2)` float32 arrays.  Closed paths repeat their
+/// first point at the end.
+#[pyfunction]
+#[pyo3(signature = (
+    polylines,
+    epsilon = 0.001,
+    relative_epsilon = false,
+    only_starts = false,
+    allow_ambiguous = false,
+    prune = false,
+    graph_stitch = false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn stitch<'py>(
+    py: Python<'py>,
+    polylines: Vec<&'py PyAny>,
+    epsilon: f32,
+    relative_epsilon: bool,
+    only_starts: bool,
+    allow_ambiguous: bool,
+    prune: bool,
+    graph_stitch: bool,
+) -> PyResult<Vec<&'py PyArray2<f32>>> {
+    let input = polylines
+        .into_iter()
+        .map(polyline)
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let config = PipelineConfig {
+        epsilon: if relative_epsilon {
+            Epsilon::RelativeToBounds(epsilon)
+        } else {
+            Epsilon::Absolute(epsilon)
+        },
+        only_starts,
+        allow_ambiguous,
+        prune,
+        graph_stitch,
+        ..PipelineConfig::default()
+    };
+
+    let output: Vec<PathSegment<UnknownUnit>> = py.allow_threads(|| run_pipeline(input, &config));
+
+    output
+        .into_iter()
+        .map(|segment| {
+            let points: Points<UnknownUnit> = segment.into();
+            PyArray1::from_vec(py, flat_from_points(&points)).reshape([points.len(), 2])
+        })
+        .collect()
+}
+
+#[pymodule]
+fn line_stitch(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(stitch, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+# Run with `maturin develop && pytest` from the `python` directory.
+
+import numpy as np
+import line_stitch
+
+
+def test_joins_touching_polylines():
+    paths = line_stitch.stitch([[(0, 0), (1, 0)], [(1, 0), (1, 1)]])
+    assert len(paths) == 1
+    assert paths[0].shape == (3, 2)
+    assert paths[0].dtype == np.float32
+
+
+def test_closed_paths_repeat_their_first_point():
+    paths = line_stitch.stitch([[(0, 0), (1, 0), (1, 1)], [(1, 1), (0, 0)]])
+    assert len(paths) == 1
+    assert paths[0].shape == (4, 2)
+    assert (paths[0][0] == paths[0][-1]).all()
+
+
+def test_accepts_numpy_arrays():
+    a = np.array([[0, 0], [1, 0]], dtype=np.float64)
+    b = np.array([[1, 0], [2, 0]], dtype=np.float32)
+    paths = line_stitch.stitch([a, b])
+    assert len(paths) == 1
+
+
+def test_rejects_arrays_of_the_wrong_shape():
+    try:
+        line_stitch.stitch([np.zeros((2, 3))])
+    except ValueError:
+        return
+    assert False, "expected a ValueError"
+
+
+def test_relative_epsilon():
+    polylines = [[(0, 0), (100, 0)], [(100.5, 0), (200, 0)]]
+    assert len(line_stitch.stitch(polylines)) == 2
+    assert len(line_stitch.stitch(polylines, epsilon=0.01, relative_epsilon=True)) == 1
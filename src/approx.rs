@@ -0,0 +1,107 @@
+use ::*;
+
+impl<S> PathSegment<S> {
+    /// True if both segments visit the same points, each within `epsilon`
+    /// of its counterpart.
+    ///
+    /// Segments that run in opposite directions are considered equal, and
+    /// closed segments may also start at different points along the loop.
+    /// A relative `epsilon` is resolved against the bounds of both segments.
+    pub fn approx_eq<E: Into<Epsilon>>(&self, other: &PathSegment<S>, epsilon: E) -> bool {
+        if self.closed != other.closed || self.path.len() != other.path.len() {
+            return false;
+        }
+        let epsilon = epsilon.into().resolve_with(|| {
+            util::compute_bounding_box(self.path.iter().chain(other.path.iter()).cloned())
+        });
+        let close = |a: Point<S>, b: Point<S>| (a - b).square_length() <= epsilon * epsilon;
+
+        let n = self.path.len();
+        let (a, b) = (&self.path, &other.path);
+        if self.closed {
+            // Try every starting point, walking `other` both ways around.
+            (0..n).any(|shift| {
+                (0..n).all(|i| close(a[i], b[(shift + i) % n]))
+                    || (0..n).all(|i| close(a[i], b[(shift + n - i) % n]))
+            })
+        } else {
+            (0..n).all(|i| close(a[i], b[i])) || (0..n).all(|i| close(a[i], b[n - 1 - i]))
+        }
+    }
+
+    /// A key that is the same for segments that are the same once their
+    /// points are rounded to the nearest multiple of `grid`, for use in
+    /// hash maps and sets.
+    ///
+    /// Like `approx_eq`, it ignores direction and where closed segments
+    /// start.  Points that are close to each other but round to different
+    /// multiples of `grid` give different keys, so this finds exact
+    /// duplicates in data that went through some float noise, but doesn't
+    /// replace `approx_eq`.
+    pub fn key(&self, grid: f32) -> SegmentKey {
+        assert!(grid > 0.0);
+        let quantized: Vec<(i64, i64)> = self.path
+            .iter()
+            .map(|p| ((p.x / grid).round() as i64, (p.y / grid).round() as i64))
+            .collect();
+
+        let mut reversed = quantized.clone();
+        reversed.reverse();
+
+        let points = if self.closed {
+            let rotate = |mut points: Vec<(i64, i64)>| {
+                let shift = least_rotation(&points);
+                points.rotate_left(shift);
+                points
+            };
+            rotate(quantized).min(rotate(reversed))
+        } else {
+            quantized.min(reversed)
+        };
+
+        SegmentKey {
+            closed: self.closed,
+            points: points,
+        }
+    }
+}
+
+/// The shift that rotates `s` to its lexicographically smallest rotation,
+/// found in linear time with Booth's algorithm.
+fn least_rotation<T: Ord>(s: &[T]) -> usize {
+    let n = s.len();
+    if n == 0 {
+        return 0;
+    }
+    let at = |i: usize| &s[i % n];
+
+    // `failure[j]` is the failure function of the doubled sequence, read
+    // from the current candidate start `k`.
+    let mut failure = vec![-1isize; 2 * n];
+    let mut k = 0;
+    for j in 1..2 * n {
+        let mut i = failure[j - k - 1];
+        while i != -1 && at(j) != at(k + i as usize + 1) {
+            if at(j) < at(k + i as usize + 1) {
+                k = j - i as usize - 1;
+            }
+            i = failure[i as usize];
+        }
+        if i == -1 && at(j) != at(k) {
+            if at(j) < at(k) {
+                k = j;
+            }
+            failure[j - k] = -1;
+        } else {
+            failure[j - k] = i + 1;
+        }
+    }
+    k % n
+}
+
+/// A hashable stand-in for a segment, built by `PathSegment::key`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SegmentKey {
+    closed: bool,
+    points: Vec<(i64, i64)>,
+}
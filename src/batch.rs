@@ -0,0 +1,110 @@
+use ::*;
+use pipeline::{debug_validate, finish, ignore_passes, resolve_epsilon, run_passes};
+use progress::ignore_progress;
+
+/// Runs one `PipelineConfig` over many independent drawings.
+///
+/// Gives the same paths as calling `run_pipeline` on each drawing, running
+/// the very same passes, but the segment table and the input buffer are kept
+/// from one drawing to the next.  With `IndexBackend::Grid` the spatial index
+/// is kept as well; a quadtree can't be emptied in place, so with
+/// `IndexBackend::QuadTree` it is built again for every drawing.
+pub struct BatchProcessor<S> {
+    config: PipelineConfig,
+    travel: Option<(Point<S>, Option<Point<S>>)>,
+    dual_qt: Option<DualQuadTree<S>>,
+    input: Vec<Points<S>>,
+}
+
+impl<S: 'static> BatchProcessor<S> {
+    /// A processor that runs the passes picked out by `config`.
+    pub fn new(config: PipelineConfig) -> BatchProcessor<S> {
+        BatchProcessor {
+            config: config,
+            travel: None,
+            dual_qt: None,
+            input: vec![],
+        }
+    }
+
+    /// Also orders the output of every drawing with `order_travel_between`.
+    pub fn with_travel(mut self, start: Point<S>, end: Option<Point<S>>) -> BatchProcessor<S> {
+        self.travel = Some((start, end));
+        self
+    }
+
+    /// The configuration that every drawing is run with.
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// Runs the passes over one drawing.
+    pub fn process<P, I>(&mut self, segments: I) -> Vec<PathSegment<S>>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+    {
+        let out = self.stitch(segments);
+        match self.travel {
+            Some((start, end)) => order_travel_between(out, start, end),
+            None => out,
+        }
+    }
+
+    /// Runs the passes over one drawing and plans the pen's moves through
+    /// it, starting from the travel start given to `with_travel`, or from
+    /// the origin.
+    pub fn plan<P, I>(&mut self, segments: I) -> Plan<S>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+    {
+        let (start, end) = self.travel.unwrap_or((Point::origin(), None));
+        let out = self.stitch(segments);
+        plan_travel_between(out, start, end)
+    }
+
+    /// Runs the passes over every drawing, in order.
+    pub fn process_all<P, I, D>(&mut self, drawings: D) -> Vec<Vec<PathSegment<S>>>
+    where
+        D: IntoIterator<Item = I>,
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+    {
+        drawings.into_iter().map(|drawing| self.process(drawing)).collect()
+    }
+
+    fn stitch<P, I>(&mut self, segments: I) -> Vec<PathSegment<S>>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+    {
+        let config = self.config;
+        self.input.clear();
+        self.input.extend(segments.into_iter().map(Into::into));
+        let input = if config.debug_validate {
+            Some(self.input.clone())
+        } else {
+            None
+        };
+
+        let epsilon = resolve_epsilon(&self.input, &config);
+        let (segments, aabb, epsilon) = util::prepare(self.input.drain(..), Epsilon::Absolute(epsilon));
+        if let Some(ref mut dual_qt) = self.dual_qt {
+            dual_qt.refill(aabb, epsilon);
+        } else {
+            self.dual_qt = Some(DualQuadTree::new(aabb, epsilon, config.index));
+        }
+        let dual_qt = self.dual_qt.as_mut().unwrap();
+        for segment in segments {
+            dual_qt.insert(segment);
+        }
+
+        run_passes(dual_qt, &config, &mut ignore_progress, &mut ignore_passes);
+        let out = finish(dual_qt.drain(), epsilon, &config, &mut ignore_passes);
+        if let Some(input) = input {
+            debug_validate(&input, &out, epsilon, &config);
+        }
+        out
+    }
+}
@@ -0,0 +1,525 @@
+//! Joins the line work of a drawing and writes it out ready for a plotter.
+//!
+//! ```text
+//! line-stitch [OPTIONS] [INPUT]
+//!
+//!     -o, --output FILE    where to write the result (default: stdout)
+//!     --format FORMAT      svg, gcode or hpgl (default: from the output
+//!                          file's extension, or svg)
+//!     --epsilon E          join endpoints closer than E (default: 0.001)
+//!     --prune              drop segments that can't be part of a loop
+//!     --graph-stitch       join loops with graph_stitch
+//!     --prune-len L        drop output paths shorter than L
+//!     --sort-travel        order the paths to minimize pen-up travel
+//! ```
+//!
+//! The input is read from stdin if no file is given.  Files ending in
+//! `.svg` are read as SVG; `line`, `polyline`, `polygon` and `path` elements
+//! are picked up, with curves replaced by straight lines between their end
+//! points, and moved by the `transform` attributes on them and on the groups
+//! around them.  Anything else is read as text with one polyline per line, written
+//! as whitespace separated `x,y` pairs.
+
+extern crate euclid;
+extern crate line_stitch;
+
+use euclid::{UnknownUnit, point2};
+use line_stitch::{drawn_length, plan_travel, run_pipeline, Move, PathSegment, PipelineConfig, Plan};
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+type Point = euclid::TypedPoint2D<f32, UnknownUnit>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Svg,
+    Gcode,
+    Hpgl,
+}
+
+struct Options {
+    input: Option<String>,
+    output: Option<String>,
+    format: Option<Format>,
+    config: PipelineConfig,
+    prune_len: f32,
+    sort_travel: bool,
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: line-stitch [-o FILE] [--format svg|gcode|hpgl] [--epsilon E] \
+         [--prune] [--graph-stitch] [--prune-len L] [--sort-travel] [INPUT]"
+    );
+    process::exit(2);
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        input: None,
+        output: None,
+        format: None,
+        config: PipelineConfig::default(),
+        prune_len: 0.0,
+        sort_travel: false,
+    };
+
+    fn number(value: Option<String>, flag: &str) -> Result<f32, String> {
+        value
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("{} needs a number", flag))
+    }
+
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "-o" | "--output" => {
+                options.output = Some(args.next().ok_or("--output needs a file")?);
+            }
+            "--format" => {
+                options.format = Some(match args.next().as_ref().map(|s| &s[..]) {
+                    Some("svg") => Format::Svg,
+                    Some("gcode") => Format::Gcode,
+                    Some("hpgl") => Format::Hpgl,
+                    _ => return Err("--format must be svg, gcode or hpgl".into()),
+                });
+            }
+            "--epsilon" => options.config.epsilon = number(args.next(), &arg)?.into(),
+            "--prune-len" => options.prune_len = number(args.next(), &arg)?,
+            "--prune" => options.config.prune = true,
+            "--graph-stitch" => options.config.graph_stitch = true,
+            "--sort-travel" => options.sort_travel = true,
+            "-h" | "--help" => usage(),
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option {}", arg)),
+            _ if options.input.is_none() => options.input = Some(arg),
+            _ => return Err("more than one input".into()),
+        }
+    }
+
+    Ok(options)
+}
+
+fn read_text(text: &str) -> Result<Vec<Vec<Point>>, String> {
+    let mut out = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let points = line
+            .split_whitespace()
+            .map(|pair| {
+                let mut coords = pair.split(',').map(|c| c.parse::<f32>());
+                match (coords.next(), coords.next(), coords.next()) {
+                    (Some(Ok(x)), Some(Ok(y)), None) => Ok(point2(x, y)),
+                    _ => Err(format!("line {}: bad point {:?}", number + 1, pair)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        out.push(points);
+    }
+    Ok(out)
+}
+
+/// The value of `name` in the attributes of a tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let preceded_by_space = rest[..pos].ends_with(char::is_whitespace);
+        let after = rest[pos + name.len()..].trim_start();
+        rest = &rest[pos + name.len()..];
+        if !preceded_by_space || !after.starts_with('=') {
+            continue;
+        }
+        let after = after[1..].trim_start();
+        let quote = after.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let value = &after[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+fn numbers(s: &str) -> Vec<f32> {
+    let mut out = vec![];
+    let mut current = String::new();
+    let flush = |current: &mut String, out: &mut Vec<f32>| {
+        if let Ok(n) = current.parse() {
+            out.push(n);
+        }
+        current.clear();
+    };
+    for c in s.chars() {
+        let starts_new = (c == '-' || c == '+') && !current.ends_with(|e: char| e == 'e' || e == 'E')
+            || c == '.' && current.contains('.');
+        if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e' || c == 'E' {
+            if starts_new {
+                flush(&mut current, &mut out);
+            }
+            current.push(c);
+        } else {
+            flush(&mut current, &mut out);
+        }
+    }
+    flush(&mut current, &mut out);
+    out
+}
+
+fn pairs(numbers: &[f32]) -> Vec<Point> {
+    numbers.chunks(2).filter(|c| c.len() == 2).map(|c| point2(c[0], c[1])).collect()
+}
+
+/// Reads the sub-paths of SVG path data.
+fn path_data(d: &str) -> Vec<Vec<Point>> {
+    let mut out = vec![];
+    let mut current: Vec<Point> = vec![];
+    let mut position = point2(0.0, 0.0);
+    let mut start = position;
+
+    let mut commands = vec![];
+    let mut last = None;
+    for (i, c) in d.char_indices() {
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            if let Some((command, from)) = last {
+                commands.push((command, numbers(&d[from..i])));
+            }
+            last = Some((c, i + 1));
+        }
+    }
+    if let Some((command, from)) = last {
+        commands.push((command, numbers(&d[from..])));
+    }
+
+    for (command, args) in commands {
+        let relative = command.is_ascii_lowercase();
+        let offset = |p: Point, position: Point| if relative { position + p.to_vector() } else { p };
+        // How many numbers each step takes, and which pair is the end point.
+        let (stride, end) = match command.to_ascii_uppercase() {
+            'M' | 'L' | 'T' => (2, 0),
+            'H' | 'V' => (1, 0),
+            'C' => (6, 4),
+            'S' | 'Q' => (4, 2),
+            'A' => (7, 5),
+            'Z' => {
+                if !current.is_empty() {
+                    current.push(start);
+                    out.push(std::mem::replace(&mut current, vec![]));
+                }
+                position = start;
+                continue;
+            }
+            _ => continue,
+        };
+
+        for (n, step) in args.chunks(stride).filter(|s| s.len() == stride).enumerate() {
+            let target = match command.to_ascii_uppercase() {
+                'H' => point2(if relative { position.x + step[0] } else { step[0] }, position.y),
+                'V' => point2(position.x, if relative { position.y + step[0] } else { step[0] }),
+                _ => offset(point2(step[end], step[end + 1]), position),
+            };
+            if n == 0 && command.to_ascii_uppercase() == 'M' {
+                if current.len() > 1 {
+                    out.push(std::mem::replace(&mut current, vec![]));
+                }
+                current.clear();
+                start = target;
+            } else if current.is_empty() {
+                current.push(position);
+            }
+            current.push(target);
+            position = target;
+        }
+    }
+    if current.len() > 1 {
+        out.push(current);
+    }
+    out
+}
+
+/// An SVG transform, `matrix(a b c d e f)`, which maps `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Affine([f32; 6]);
+
+impl Affine {
+    fn identity() -> Affine {
+        Affine([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    /// The transform that applies `inner` first and then `self`.
+    fn then_after(&self, inner: &Affine) -> Affine {
+        let ([a, b, c, d, e, f], [g, h, i, j, k, l]) = (self.0, inner.0);
+        Affine([
+            a * g + c * h,
+            b * g + d * h,
+            a * i + c * j,
+            b * i + d * j,
+            a * k + c * l + e,
+            b * k + d * l + f,
+        ])
+    }
+
+    fn apply(&self, p: Point) -> Point {
+        let [a, b, c, d, e, f] = self.0;
+        point2(a * p.x + c * p.y + e, b * p.x + d * p.y + f)
+    }
+}
+
+/// Reads a `transform` attribute.
+fn transform(value: &str) -> Result<Affine, String> {
+    let mut out = Affine::identity();
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(|| format!("bad transform {:?}", value))?;
+        let close = rest.find(')').ok_or_else(|| format!("bad transform {:?}", value))?;
+        let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+        let args = numbers(&rest[open + 1..close]);
+        let radians = |degrees: f32| degrees.to_radians();
+        let step = match (name, &args[..]) {
+            ("matrix", &[a, b, c, d, e, f]) => Affine([a, b, c, d, e, f]),
+            ("translate", &[x]) => Affine([1.0, 0.0, 0.0, 1.0, x, 0.0]),
+            ("translate", &[x, y]) => Affine([1.0, 0.0, 0.0, 1.0, x, y]),
+            ("scale", &[s]) => Affine([s, 0.0, 0.0, s, 0.0, 0.0]),
+            ("scale", &[x, y]) => Affine([x, 0.0, 0.0, y, 0.0, 0.0]),
+            ("rotate", &[angle]) => rotation(radians(angle)),
+            ("rotate", &[angle, cx, cy]) => Affine([1.0, 0.0, 0.0, 1.0, cx, cy])
+                .then_after(&rotation(radians(angle)))
+                .then_after(&Affine([1.0, 0.0, 0.0, 1.0, -cx, -cy])),
+            ("skewX", &[angle]) => Affine([1.0, 0.0, radians(angle).tan(), 1.0, 0.0, 0.0]),
+            ("skewY", &[angle]) => Affine([1.0, radians(angle).tan(), 0.0, 1.0, 0.0, 0.0]),
+            _ => return Err(format!("unsupported transform {:?}", &rest[..close + 1])),
+        };
+        // The transform on the left is applied last.
+        out = out.then_after(&step);
+        rest = rest[close + 1..].trim_start();
+    }
+    Ok(out)
+}
+
+fn rotation(radians: f32) -> Affine {
+    let (sin, cos) = radians.sin_cos();
+    Affine([cos, sin, -sin, cos, 0.0, 0.0])
+}
+
+fn read_svg(text: &str) -> Result<Vec<Vec<Point>>, String> {
+    let mut out = vec![];
+    // The transforms of the groups that are open, outermost first.
+    let mut groups = vec![Affine::identity()];
+    for tag in text.split('<').skip(1) {
+        let tag = match tag.find('>') {
+            Some(end) => &tag[..end],
+            None => continue,
+        };
+        let name = tag.split_whitespace().next().unwrap_or("").trim_end_matches('/');
+        let parent = *groups.last().unwrap();
+        let current = match attribute(tag, "transform") {
+            Some(value) => parent.then_after(&transform(value)?),
+            None => parent,
+        };
+        let mut shapes = match name {
+            "g" => {
+                if !tag.ends_with('/') {
+                    groups.push(current);
+                }
+                continue;
+            }
+            "/g" => {
+                if groups.len() > 1 {
+                    groups.pop();
+                }
+                continue;
+            }
+            "line" => {
+                let coords: Vec<f32> = ["x1", "y1", "x2", "y2"]
+                    .iter()
+                    .map(|a| attribute(tag, a).and_then(|v| v.trim().parse().ok()).unwrap_or(0.0))
+                    .collect();
+                vec![pairs(&coords)]
+            }
+            "polyline" | "polygon" => {
+                let mut points = pairs(&numbers(attribute(tag, "points").unwrap_or("")));
+                if name == "polygon" && !points.is_empty() {
+                    let first = points[0];
+                    points.push(first);
+                }
+                vec![points]
+            }
+            "path" => path_data(attribute(tag, "d").unwrap_or("")),
+            _ => continue,
+        };
+        for shape in &mut shapes {
+            for p in shape.iter_mut() {
+                *p = current.apply(*p);
+            }
+        }
+        out.extend(shapes);
+    }
+    Ok(out)
+}
+
+/// The points to draw for `segment`, including the closing point of closed
+/// segments.
+fn drawn_points(segment: &PathSegment<UnknownUnit>) -> Vec<Point> {
+    let mut points: Vec<Point> = segment.path.iter().cloned().collect();
+    if segment.closed {
+        points.push(points[0]);
+    }
+    points
+}
+
+fn write_svg(plan: &Plan<UnknownUnit>) -> String {
+    let mut svg = String::new();
+    let mut min = point2(std::f32::INFINITY, std::f32::INFINITY);
+    let mut max = point2(std::f32::NEG_INFINITY, std::f32::NEG_INFINITY);
+    for m in &plan.moves {
+        if let Move::PenDown(ref segment) = *m {
+            for p in &segment.path {
+                min = point2(min.x.min(p.x), min.y.min(p.y));
+                max = point2(max.x.max(p.x), max.y.max(p.y));
+            }
+        }
+    }
+    if min.x > max.x {
+        min = point2(0.0, 0.0);
+        max = min;
+    }
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x,
+        min.y,
+        (max.x - min.x).max(1.0),
+        (max.y - min.y).max(1.0)
+    ).unwrap();
+    writeln!(svg, r#"<g fill="none" stroke="black">"#).unwrap();
+    for m in &plan.moves {
+        if let Move::PenDown(ref segment) = *m {
+            let element = if segment.closed { "polygon" } else { "polyline" };
+            write!(svg, r#"<{} points=""#, element).unwrap();
+            for (i, p) in segment.path.iter().enumerate() {
+                let separator = if i == 0 { "" } else { " " };
+                write!(svg, "{}{},{}", separator, p.x, p.y).unwrap();
+            }
+            writeln!(svg, r#""/>"#).unwrap();
+        }
+    }
+    writeln!(svg, "</g>").unwrap();
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+fn write_gcode(plan: &Plan<UnknownUnit>) -> String {
+    let mut gcode = String::new();
+    writeln!(gcode, "G21\nG90\nG0 Z5").unwrap();
+    for m in &plan.moves {
+        match *m {
+            Move::PenUp(ref points) => {
+                for p in &points[1..] {
+                    writeln!(gcode, "G0 X{} Y{}", p.x, p.y).unwrap();
+                }
+            }
+            Move::PenDown(ref segment) => {
+                let points = drawn_points(segment);
+                writeln!(gcode, "G0 X{} Y{}\nG1 Z0", points[0].x, points[0].y).unwrap();
+                for p in &points[1..] {
+                    writeln!(gcode, "G1 X{} Y{}", p.x, p.y).unwrap();
+                }
+                writeln!(gcode, "G0 Z5").unwrap();
+            }
+        }
+    }
+    gcode
+}
+
+/// HPGL uses integer plotter units of 0.025mm; the input is taken to be in
+/// millimeters.
+fn write_hpgl(plan: &Plan<UnknownUnit>) -> String {
+    let unit = |p: &Point| ((p.x * 40.0).round() as i64, (p.y * 40.0).round() as i64);
+    let mut hpgl = String::from("IN;SP1;\n");
+    for m in &plan.moves {
+        match *m {
+            Move::PenUp(ref points) => {
+                for p in &points[1..] {
+                    let (x, y) = unit(p);
+                    writeln!(hpgl, "PU{},{};", x, y).unwrap();
+                }
+            }
+            Move::PenDown(ref segment) => {
+                let points = drawn_points(segment);
+                let (x, y) = unit(&points[0]);
+                write!(hpgl, "PU{},{};PD", x, y).unwrap();
+                for (i, p) in points[1..].iter().enumerate() {
+                    let (x, y) = unit(p);
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(hpgl, "{}{},{}", separator, x, y).unwrap();
+                }
+                writeln!(hpgl, ";").unwrap();
+            }
+        }
+    }
+    hpgl.push_str("PU;SP0;\n");
+    hpgl
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut text = String::new();
+    match options.input {
+        Some(ref path) if path != "-" => {
+            text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        }
+        _ => {
+            io::stdin().read_to_string(&mut text).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let is_svg = options.input.as_ref().map_or(false, |p| p.ends_with(".svg"))
+        || text.trim_start().starts_with('<');
+    let polylines = if is_svg { read_svg(&text)? } else { read_text(&text)? };
+
+    let joined: Vec<PathSegment<UnknownUnit>> = run_pipeline(polylines, &options.config);
+    let joined: Vec<_> = joined
+        .into_iter()
+        .filter(|s| drawn_length(s) >= options.prune_len)
+        .collect();
+
+    let origin = point2(0.0, 0.0);
+    let plan = if options.sort_travel {
+        plan_travel(joined, origin)
+    } else {
+        Plan::from_ordered(joined, origin)
+    };
+
+    let format = options.format.unwrap_or_else(|| match options.output {
+        Some(ref p) if p.ends_with(".gcode") || p.ends_with(".nc") => Format::Gcode,
+        Some(ref p) if p.ends_with(".hpgl") || p.ends_with(".plt") => Format::Hpgl,
+        _ => Format::Svg,
+    });
+    let out = match format {
+        Format::Svg => write_svg(&plan),
+        Format::Gcode => write_gcode(&plan),
+        Format::Hpgl => write_hpgl(&plan),
+    };
+
+    match options.output {
+        Some(ref path) if path != "-" => fs::write(path, out).map_err(|e| format!("{}: {}", path, e)),
+        _ => io::stdout().write_all(out.as_bytes()).map_err(|e| e.to_string()),
+    }
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("line-stitch: {}", e);
+            usage();
+        }
+    };
+    if let Err(e) = run(options) {
+        eprintln!("line-stitch: {}", e);
+        process::exit(1);
+    }
+}
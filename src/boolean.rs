@@ -0,0 +1,310 @@
+use ::*;
+use std::cmp::Ordering;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
+use util::{compute_bounding_box, crossing};
+use euclid::TypedRect;
+use sanitize::is_finite;
+
+/// How a set of closed segments decides which areas it covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is covered if it is inside an odd number of the rings, so
+    /// nested rings alternate between shapes and holes whichever way they
+    /// run.
+    EvenOdd,
+    /// A point is covered if the rings wind around it a nonzero number of
+    /// times, so a hole has to run the other way from the ring around it.
+    NonZero,
+}
+
+impl Default for FillRule {
+    fn default() -> FillRule {
+        FillRule::NonZero
+    }
+}
+
+/// The boolean operations supported between two sets of closed segments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    fn covers(self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::Union => a || b,
+            Operation::Intersection => a && b,
+            Operation::Difference => a && !b,
+        }
+    }
+}
+
+/// The area covered by either set of closed segments, with `fill` deciding
+/// what each set covers.
+///
+/// The shapes in the result run counter-clockwise and their holes clockwise,
+/// so it reads the same under either fill rule and can be fed back in.  Edges
+/// are cut wherever they cross, a corner lies on another edge or two edges
+/// overlap, so shapes may share corners and edges.  Open segments are
+/// ignored, as are features narrower than `epsilon`.
+pub fn union<S: 'static, E: Into<Epsilon>>(
+    a: &[PathSegment<S>],
+    b: &[PathSegment<S>],
+    fill: FillRule,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    combine(a, b, fill, epsilon.into(), Operation::Union)
+}
+
+/// The area covered by both sets of closed segments.  See `union`.
+pub fn intersection<S: 'static, E: Into<Epsilon>>(
+    a: &[PathSegment<S>],
+    b: &[PathSegment<S>],
+    fill: FillRule,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    combine(a, b, fill, epsilon.into(), Operation::Intersection)
+}
+
+/// The area covered by `a` but not by `b`.  See `union`.
+pub fn difference<S: 'static, E: Into<Epsilon>>(
+    a: &[PathSegment<S>],
+    b: &[PathSegment<S>],
+    fill: FillRule,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    combine(a, b, fill, epsilon.into(), Operation::Difference)
+}
+
+/// Cuts every edge of both sets where it meets another, keeps the pieces
+/// that have the result on exactly one side, and chains them back into
+/// rings that have the result on their left.
+fn combine<S: 'static>(
+    a: &[PathSegment<S>],
+    b: &[PathSegment<S>],
+    fill: FillRule,
+    epsilon: Epsilon,
+    op: Operation,
+) -> Vec<PathSegment<S>> {
+    let epsilon = epsilon.resolve_with(|| compute_bounding_box(a.iter().chain(b).flat_map(|s| s.path.iter().cloned())));
+    let (a, b) = (rings(a), rings(b));
+    let inside = |p: Point<S>| op.covers(covers(&a, fill, p), covers(&b, fill, p));
+
+    let mut seen = HashSet::default();
+    let mut kept = vec![];
+    // Well inside of the offset that the sides are sampled at.
+    for (p0, p1) in pieces(a.iter().chain(&b), epsilon / 4.0) {
+        if !seen.insert(edge_key(p0, p1)) {
+            continue;
+        }
+
+        let d = p1 - p0;
+        let mid = p0 + d / 2.0;
+        let normal = euclid::vec2(-d.y, d.x) * (epsilon / 2.0 / d.length());
+        match (inside(mid + normal), inside(mid - normal)) {
+            (true, false) => kept.push((p0, p1)),
+            (false, true) => kept.push((p1, p0)),
+            _ => {}
+        }
+    }
+
+    to_segments(chain(kept), epsilon)
+}
+
+/// The closed segments as polygons.  Points with a NaN or infinite
+/// coordinate are left out.
+fn rings<S>(segments: &[PathSegment<S>]) -> Vec<Vec<Point<S>>> {
+    segments
+        .iter()
+        .filter(|s| s.closed)
+        .map(|s| s.path.iter().cloned().filter(|&p| is_finite(p)).collect::<Vec<_>>())
+        .filter(|r| r.len() > 2)
+        .collect()
+}
+
+fn to_segments<S, I: IntoIterator<Item = Vec<Point<S>>>>(rings: I, epsilon: f32) -> Vec<PathSegment<S>> {
+    rings
+        .into_iter()
+        .filter(|r| r.len() > 2)
+        .map(|mut r| {
+            let first = r[0];
+            r.push(first);
+            let mut segment = PathSegment::new(r, epsilon);
+            segment.closed = true;
+            segment
+        })
+        .collect()
+}
+
+/// How many times `ring` winds counter-clockwise around `point`.
+fn winding<S>(ring: &[Point<S>], point: Point<S>) -> i32 {
+    let mut winding = 0;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        let side = (b - a).cross(point - a);
+        if a.y <= point.y {
+            if b.y > point.y && side > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= point.y && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+fn covers<S>(rings: &[Vec<Point<S>>], fill: FillRule, point: Point<S>) -> bool {
+    let winding: i32 = rings.iter().map(|r| winding(r, point)).sum();
+    match fill {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+/// The edges of the rings, cut into pieces wherever two of them cross or an
+/// end of one lies within `tolerance` of another.  Both pieces on either
+/// side of a cut share the same point exactly, so edges that overlap along
+/// their length are cut into the same pieces.  Only edges whose boxes touch
+/// are compared.
+fn pieces<'a, S: 'static, I>(rings: I, tolerance: f32) -> Vec<(Point<S>, Point<S>)>
+where
+    I: IntoIterator<Item = &'a Vec<Point<S>>>,
+{
+    let edges: Vec<_> = rings
+        .into_iter()
+        .flat_map(|r| (0..r.len()).map(move |i| (r[i], r[(i + 1) % r.len()])))
+        .filter(|&(p0, p1)| p0 != p1)
+        .collect();
+    if edges.is_empty() {
+        return vec![];
+    }
+
+    let boxes: Vec<TypedRect<f32, S>> = edges
+        .iter()
+        .map(|&(p0, p1)| TypedRect::from_points(&[p0, p1]).inflate(tolerance, tolerance))
+        .collect();
+    let bounds = boxes.iter().fold(boxes[0], |bounds, b| bounds.union(b));
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+    let mut tree = QuadTree::default(bounds);
+    for (i, &b) in boxes.iter().enumerate() {
+        tree.insert_with_box(i, b);
+    }
+
+    let mut cuts = vec![vec![]; edges.len()];
+    for i in 0..edges.len() {
+        for (&j, _, _) in tree.query(boxes[i]) {
+            if j <= i {
+                continue;
+            }
+            let (a, b) = (edges[i], edges[j]);
+            cuts[i].extend(ends_on(b, a, tolerance));
+            cuts[j].extend(ends_on(a, b, tolerance));
+
+            if let Some((t, _)) = crossing(a.0, a.1, b.0, b.1) {
+                let point = a.0 + (a.1 - a.0) * t;
+                // A crossing at one of the ends was cut there already.
+                let at_an_end = [a.0, a.1, b.0, b.1].iter().any(|&p| (p - point).length() <= tolerance);
+                if !at_an_end {
+                    cuts[i].push(point);
+                    cuts[j].push(point);
+                }
+            }
+        }
+    }
+
+    let mut out = vec![];
+    for (&(p0, p1), mut cuts) in edges.iter().zip(cuts) {
+        let d = p1 - p0;
+        cuts.sort_by(|a, b| (*a - p0).dot(d).partial_cmp(&(*b - p0).dot(d)).unwrap_or(Ordering::Equal));
+        let mut from = p0;
+        for to in cuts.into_iter().chain(Some(p1)) {
+            if point_key(to) != point_key(from) {
+                out.push((from, to));
+                from = to;
+            }
+        }
+    }
+    out
+}
+
+/// The ends of `edge` that lie within `tolerance` of `on`, away from its
+/// ends.
+fn ends_on<S>(edge: (Point<S>, Point<S>), on: (Point<S>, Point<S>), tolerance: f32) -> Vec<Point<S>> {
+    let (a0, a1) = on;
+    let d = a1 - a0;
+    let mut out = vec![];
+    for &p in &[edge.0, edge.1] {
+        if (p - a0).length() <= tolerance || (p - a1).length() <= tolerance {
+            continue;
+        }
+        let t = (p - a0).dot(d) / d.square_length();
+        if t > 0.0 && t < 1.0 && (p - (a0 + d * t)).length() <= tolerance {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// Adding zero turns `-0.0` into `0.0`, so that both get the same key.
+fn point_key<S>(p: Point<S>) -> (u32, u32) {
+    ((p.x + 0.0).to_bits(), (p.y + 0.0).to_bits())
+}
+
+/// The same for an edge and its reverse.
+fn edge_key<S>(p0: Point<S>, p1: Point<S>) -> ((u32, u32), (u32, u32)) {
+    let (k0, k1) = (point_key(p0), point_key(p1));
+    if k0 <= k1 {
+        (k0, k1)
+    } else {
+        (k1, k0)
+    }
+}
+
+/// Joins directed edges end to start into rings.  Edges that don't make it
+/// back to where their ring started are dropped.
+fn chain<S>(edges: Vec<(Point<S>, Point<S>)>) -> Vec<Vec<Point<S>>> {
+    let mut from: HashMap<(u32, u32), Vec<usize>> = HashMap::default();
+    for (i, &(p0, _)) in edges.iter().enumerate().rev() {
+        from.entry(point_key(p0)).or_insert_with(Vec::new).push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut out = vec![];
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+
+        let mut ring = vec![];
+        let mut current = start;
+        let closed = loop {
+            used[current] = true;
+            let (p0, p1) = edges[current];
+            ring.push(p0);
+            if point_key(p1) == point_key(edges[start].0) {
+                break true;
+            }
+            let next = from.get_mut(&point_key(p1)).and_then(|ids| {
+                while let Some(id) = ids.pop() {
+                    if !used[id] {
+                        return Some(id);
+                    }
+                }
+                None
+            });
+            match next {
+                Some(next) => current = next,
+                None => break false,
+            }
+        };
+        if closed {
+            out.push(ring);
+        }
+    }
+    out
+}
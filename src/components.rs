@@ -0,0 +1,46 @@
+use ::*;
+use util::UnionFind;
+use sanitize::sanitize_segments;
+
+/// Groups the segments into connected figures: two segments are in the same
+/// group if an end of one is within `epsilon` of an end of the other, or if
+/// they are linked through other segments like that.
+///
+/// Groups are listed in the order that their first segment appears in the
+/// input, and keep the input order within them.  A relative `epsilon` is
+/// resolved against the bounds of the segments.
+pub fn components<S: 'static, E: Into<Epsilon>>(segments: Vec<PathSegment<S>>, epsilon: E) -> Vec<Vec<PathSegment<S>>> {
+    let segments = sanitize_segments(segments);
+    if segments.is_empty() {
+        return vec![];
+    }
+    let epsilon = epsilon.into().resolve_segments(&segments);
+
+    let mut sets = UnionFind::new(segments.len());
+    {
+        let index = EndpointIndex::new(&segments);
+        for (i, segment) in segments.iter().enumerate() {
+            for &point in &[segment.first(), segment.last()] {
+                for hit in index.endpoints_within(point, epsilon) {
+                    sets.union(i, hit.segment);
+                }
+            }
+        }
+    }
+
+    let mut group_of_root = vec![None; segments.len()];
+    let mut out: Vec<Vec<PathSegment<S>>> = vec![];
+    for (i, segment) in segments.into_iter().enumerate() {
+        let root = sets.find(i);
+        let group = match group_of_root[root] {
+            Some(group) => group,
+            None => {
+                out.push(vec![]);
+                group_of_root[root] = Some(out.len() - 1);
+                out.len() - 1
+            }
+        };
+        out[group].push(segment);
+    }
+    out
+}
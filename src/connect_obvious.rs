@@ -1,40 +1,366 @@
 use ::*;
 use util::*;
-use std::cell::RefCell;
-use itertools::{repeat_call, Itertools};
+use progress::*;
+use std::ops::ControlFlow;
+use std::cmp::Ordering;
+
+/// The order in which `connect_obvious` picks the segments that it grows
+/// chains from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeedOrder {
+    /// Whatever order is quickest.
+    Arbitrary,
+    /// Longest segments first, so that chains grow out of the main strokes
+    /// rather than out of small fragments.
+    LongestFirst,
+    /// The order the segments were given in, so that callers can sort them
+    /// however they like.
+    InputOrder,
+}
+
+impl Default for SeedOrder {
+    fn default() -> SeedOrder {
+        SeedOrder::Arbitrary
+    }
+}
 
 /// todo: doc
-pub fn connect_obvious<P, I, S: 'static>(
+pub fn connect_obvious<P, I, S: 'static, E: Into<Epsilon>>(
     segments: I,
-    epsilon: f32,
+    epsilon: E,
+    only_starts: bool,
+    allow_ambiguous: bool,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    connect_obvious_counted(
+        segments,
+        epsilon.into(),
+        IndexBackend::default(),
+        SeedOrder::Arbitrary,
+        only_starts,
+        allow_ambiguous,
+        &mut ignore_progress,
+    ).0
+}
+
+/// Like `connect_obvious`, but grows chains from the segments in `order`.
+pub fn connect_obvious_ordered<P, I, S: 'static, E: Into<Epsilon>>(
+    segments: I,
+    epsilon: E,
+    only_starts: bool,
+    allow_ambiguous: bool,
+    order: SeedOrder,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    connect_obvious_counted(
+        segments,
+        epsilon.into(),
+        IndexBackend::default(),
+        order,
+        only_starts,
+        allow_ambiguous,
+        &mut ignore_progress,
+    ).0
+}
+
+/// Like `connect_obvious`, but also returns the number of joins performed
+/// and the number of points that were found to be ambiguous.
+///
+/// If `on_progress` asks to stop, the segments that haven't been looked at
+/// yet are returned untouched.
+pub(crate) fn connect_obvious_counted<P, I, S: 'static>(
+    segments: I,
+    epsilon: Epsilon,
+    index: IndexBackend,
+    order: SeedOrder,
+    only_starts: bool,
+    allow_ambiguous: bool,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+) -> (Vec<PathSegment<S>>, usize, usize)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let (mut dual_qt, _) = populate_with_index(segments, epsilon, index);
+    let (joins, ambiguities) =
+        connect_obvious_in_place_counted(&mut dual_qt, order, only_starts, allow_ambiguous, on_progress);
+    (dual_qt.into_iter().collect(), joins, ambiguities)
+}
+
+/// Like `connect_obvious`, but works on the segments in `dual_qt`, leaving
+/// the joined segments in it.  Returns the number of joins performed.
+pub fn connect_obvious_in_place<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    only_starts: bool,
+    allow_ambiguous: bool,
+) -> usize {
+    connect_obvious_in_place_counted(dual_qt, SeedOrder::Arbitrary, only_starts, allow_ambiguous, &mut ignore_progress).0
+}
+
+/// Like `connect_obvious_in_place`, but grows chains from the segments in
+/// `order`.  `SeedOrder::InputOrder` is the order they were added in.
+pub fn connect_obvious_in_place_ordered<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    only_starts: bool,
+    allow_ambiguous: bool,
+    order: SeedOrder,
+) -> usize {
+    connect_obvious_in_place_counted(dual_qt, order, only_starts, allow_ambiguous, &mut ignore_progress).0
+}
+
+/// Adds `pieces` to `dual_qt` and joins them to each other and to the
+/// segments around them, returning the ids of the paths that were rebuilt.
+///
+/// Only chains that grow out of the new pieces, or out of segments that
+/// touched a path taken out with `DualQuadTree::remove_segment`, are looked
+/// at, so this is much quicker than stitching everything again after a small
+/// edit.  Take out the paths being edited first, then hand their edited
+/// pieces to this.
+pub fn restitch<P, I, S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    pieces: I,
+    only_starts: bool,
+    allow_ambiguous: bool,
+) -> Vec<DqtId>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let epsilon = dual_qt.epsilon();
+    let (pieces, _) = sanitize(pieces);
+
+    let mut seeds = vec![];
+    for piece in pieces {
+        let piece = PathSegment::new(piece, epsilon);
+        if piece.length_2() < epsilon {
+            continue;
+        }
+        dual_qt.forget_ambiguities_near(piece.first());
+        dual_qt.forget_ambiguities_near(piece.last());
+        seeds.push(dual_qt.insert(piece));
+    }
+    for point in std::mem::replace(&mut dual_qt.dirty, vec![]) {
+        seeds.extend(dual_qt.candidates(point, epsilon, None).into_iter().map(|(id, _)| id));
+    }
+
+    let mut out = vec![];
+    for seed in seeds {
+        // Seeds that were swallowed by an earlier chain are gone.
+        let head = match dual_qt.remove_if_present(seed) {
+            Some(head) => head,
+            None => continue,
+        };
+        let joined = match chain_single(head, dual_qt, epsilon, only_starts, allow_ambiguous) {
+            Some(chain) => recombine_segments(chain, epsilon),
+            None => continue,
+        };
+        out.retain(|&id| dual_qt.get(id).is_some());
+        out.push(dual_qt.insert(joined));
+    }
+    out
+}
+
+/// Like `connect_obvious_in_place`, but also returns the number of
+/// ambiguities and reports progress.
+pub(crate) fn connect_obvious_in_place_counted<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    order: SeedOrder,
     only_starts: bool,
     allow_ambiguous: bool,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+) -> (usize, usize) {
+    let epsilon = dual_qt.epsilon();
+    dual_qt.clear_ambiguities();
+    let total = dual_qt.len();
+    let (queries_before, hits_before) = dual_qt.query_counts();
+    let mut joins = 0;
+    let mut out = vec![];
+    debug_event!("connect_obvious: start, {} segments", total);
+    let mut seeds = seeds(dual_qt, order).into_iter();
+
+    loop {
+        let head = match order {
+            SeedOrder::Arbitrary => dual_qt.pop(),
+            // Seeds that were swallowed by an earlier chain are gone.
+            _ => seeds.by_ref().filter_map(|id| dual_qt.remove_if_present(id)).next(),
+        };
+        let head = match head {
+            Some(head) => head,
+            None => break,
+        };
+        if let Some(chain) = chain_single(head, dual_qt, epsilon, only_starts, allow_ambiguous) {
+            joins += chain.len() - 1;
+            out.push(recombine_segments(chain, epsilon));
+        }
+
+        let progress = PassProgress {
+            pass: Pass::ConnectObvious,
+            done: total - dual_qt.len(),
+            total: total,
+        };
+        if let ControlFlow::Break(()) = on_progress(progress) {
+            break;
+        }
+    }
+
+    let ambiguities = dual_qt.ambiguity_count();
+    for segment in out {
+        dual_qt.insert(segment);
+    }
+
+    let (queries, hits) = dual_qt.query_counts();
+    debug_event!(
+        "connect_obvious: done, {} joins, {} ambiguities, {}/{} index queries hit",
+        joins,
+        ambiguities,
+        hits - hits_before,
+        queries - queries_before
+    );
+    (joins, ambiguities)
+}
+
+/// Like `connect_obvious`, but `cost` picks which join to make when there
+/// is more than one candidate.
+///
+/// `cost(a, end, b, start)` is the cost of continuing from the point `end`
+/// of `a` onto `b` at its point `start`.  `start` may be the last point of
+/// `b`, in which case `b` is reversed.  The cheapest join wins, and joins
+/// with a cost that isn't finite are never made.
+pub fn connect_obvious_with_cost<P, I, S: 'static, E, F>(
+    segments: I,
+    epsilon: E,
+    only_starts: bool,
+    mut cost: F,
 ) -> Vec<PathSegment<S>>
 where
     I: IntoIterator<Item = P>,
-    P: Into<smallvec::SmallVec<[Point<S>; 2]>>,
+    P: Into<Points<S>>,
+    E: Into<Epsilon>,
+    F: FnMut(&PathSegment<S>, Point<S>, &PathSegment<S>, Point<S>) -> f32,
 {
-    let dual_qt = RefCell::new(populate(segments, epsilon));
+    let (mut dual_qt, epsilon) = populate(segments, epsilon.into());
+    let mut out = vec![];
+
+    while let Some(head) = dual_qt.pop() {
+        let chain = chain_with_cost(head, &mut dual_qt, epsilon, only_starts, &mut cost);
+        out.push(recombine_segments(chain, epsilon));
+    }
+
+    out
+}
+
+/// The ids of the segments in `dual_qt`, sorted into `order`.
+fn seeds<S: 'static>(dual_qt: &DualQuadTree<S>, order: SeedOrder) -> Vec<DqtId> {
+    let mut ids: Vec<(DqtId, f32)> = match order {
+        SeedOrder::Arbitrary => return vec![],
+        SeedOrder::LongestFirst => dual_qt.iter().map(|(id, s)| (id, -s.length())).collect(),
+        SeedOrder::InputOrder => dual_qt.iter().map(|(id, _)| (id, 0.0)).collect(),
+    };
+    // Ids count up as segments are added, so they break ties in input order.
+    ids.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal).then(a.0.cmp(&b.0)));
+    ids.into_iter().map(|(id, _)| id).collect()
+}
+
+/// Joins a chain into one segment.  The points of the first segment stay
+/// where they are, and the rest are moved onto the end of them.
+fn recombine_segments<S>(segments: Vec<PathSegment<S>>, epsilon: f32) -> PathSegment<S> {
+    let total: usize = segments.iter().map(|p| p.path.len()).sum();
+    let layer = segments[0].layer;
+    let mut segments = segments.into_iter();
+    let mut segment = segments.next().unwrap().path;
+    segment.reserve(total - segment.len());
+
+    for other_segment in segments {
+        segment.extend(other_segment.path.into_iter().skip(1));
+    }
 
-    return repeat_call(|| dual_qt.borrow_mut().pop())
-        .while_some()
-        .filter_map(|head| {
-            let mut borrowed = dual_qt.borrow_mut();
-            chain_single(head, &mut *borrowed, epsilon, only_starts, allow_ambiguous)
-        })
-        .map(|a| recombine_segments(a, epsilon))
-        .collect();
+    PathSegment::new(segment, epsilon).with_layer(layer)
+}
 
+fn chain_with_cost<S: 'static>(
+    start: PathSegment<S>,
+    dual_qt: &mut DualQuadTree<S>,
+    epsilon: f32,
+    only_starts: bool,
+    cost: &mut FnMut(&PathSegment<S>, Point<S>, &PathSegment<S>, Point<S>) -> f32,
+) -> Vec<PathSegment<S>> {
+    let mut combined = vec![start];
 
-    fn recombine_segments<S>(segments: Vec<PathSegment<S>>, epsilon: f32) -> PathSegment<S> {
-        let mut segment = SmallVec::with_capacity(segments.iter().map(|p| p.path.len()).sum());
-        segment.extend_from_slice(&segments[0].path);
+    loop {
+        let best = {
+            let last = combined.last().unwrap();
+            let point = last.last();
+            cheapest(dual_qt, point, last.layer, epsilon, |id, is_end| {
+                if only_starts && is_end {
+                    return None;
+                }
+                let next = dual_qt.get(id).unwrap();
+                let at = if is_end { next.last() } else { next.first() };
+                Some(cost(last, point, next, at))
+            })
+        };
+        match best {
+            Some((id, is_end)) => {
+                let mut next = dual_qt.remove(id).unwrap();
+                if is_end {
+                    next.path.reverse();
+                }
+                combined.push(next);
+            }
+            None => break,
+        }
+    }
 
-        for other_segment in &segments[1..] {
-            segment.extend_from_slice(&other_segment.path[1..]);
+    loop {
+        let best = {
+            let first = &combined[0];
+            let point = first.first();
+            cheapest(dual_qt, point, first.layer, epsilon, |id, is_end| {
+                if only_starts && !is_end {
+                    return None;
+                }
+                let prev = dual_qt.get(id).unwrap();
+                let at = if is_end { prev.last() } else { prev.first() };
+                Some(cost(prev, at, first, point))
+            })
+        };
+        match best {
+            Some((id, is_end)) => {
+                let mut prev = dual_qt.remove(id).unwrap();
+                if !is_end {
+                    prev.path.reverse();
+                }
+                combined.insert(0, prev);
+            }
+            None => break,
         }
+    }
+
+    return combined;
 
-        PathSegment::new(segment, epsilon)
+    fn cheapest<S: 'static, F>(
+        dual_qt: &DualQuadTree<S>,
+        point: Point<S>,
+        layer: LayerId,
+        epsilon: f32,
+        mut cost: F,
+    ) -> Option<(DqtId, bool)>
+    where
+        F: FnMut(DqtId, bool) -> Option<f32>,
+    {
+        dual_qt
+            .candidates(point, epsilon, Some(layer))
+            .into_iter()
+            .filter_map(|(id, is_end)| cost(id, is_end).map(|c| (id, is_end, c)))
+            .filter(|&(_, _, c)| c.is_finite())
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .map(|(id, is_end, _)| (id, is_end))
     }
 }
 
@@ -47,11 +373,13 @@ fn chain_single<S: 'static>(
 ) -> Option<Vec<PathSegment<S>>> {
     let mut last_going_forward = start.last();
     let mut first_going_backwards = start.first();
+    let layer = start.layer;
     let mut combined: Vec<_> = vec![start];
 
     loop {
-        let next = dual_qt.query_forward(last_going_forward, epsilon, only_starts, allow_ambiguous);
+        let next = dual_qt.query_forward(last_going_forward, layer, epsilon, only_starts, allow_ambiguous);
         if let Some(next) = next {
+            trace_event!("join forward at {:?} onto {:?}", last_going_forward, next.last());
             last_going_forward = next.last();
             combined.push(next);
         } else {
@@ -61,8 +389,9 @@ fn chain_single<S: 'static>(
 
     loop {
         let next =
-            dual_qt.query_backward(first_going_backwards, epsilon, only_starts, allow_ambiguous);
+            dual_qt.query_backward(first_going_backwards, layer, epsilon, only_starts, allow_ambiguous);
         if let Some(next) = next {
+            trace_event!("join backward at {:?} onto {:?}", first_going_backwards, next.first());
             first_going_backwards = next.first();
             combined.insert(0, next);
         } else {
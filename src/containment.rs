@@ -0,0 +1,93 @@
+use ::*;
+use util::{area, compute_bounding_box, contains_point};
+use sanitize::{is_finite, sanitize_segments};
+use std::cmp::Ordering;
+
+/// For every segment, the index of the smallest closed segment that
+/// contains it.  Open segments are never parents, and always get `None`.
+/// So do closed segments with a NaN or infinite point.
+pub fn nesting<S: 'static>(segments: &[PathSegment<S>]) -> Vec<Option<usize>> {
+    let closed: Vec<usize> = (0..segments.len())
+        .filter(|&i| segments[i].closed && segments[i].path.len() > 2)
+        .filter(|&i| segments[i].path.iter().all(|&p| is_finite(p)))
+        .collect();
+    let mut parents = vec![None; segments.len()];
+    if closed.is_empty() {
+        return parents;
+    }
+
+    let areas: Vec<f32> = segments.iter().map(|s| area(&s.path).abs()).collect();
+    let boxes: Vec<_> = segments
+        .iter()
+        .map(|s| compute_bounding_box(s.path.iter().cloned()))
+        .collect();
+
+    let mut bounds = boxes[closed[0]];
+    for &i in &closed {
+        bounds = bounds.union(&boxes[i]);
+    }
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+    let mut tree = QuadTree::default(bounds);
+    for &i in &closed {
+        tree.insert_with_box(i, boxes[i]);
+    }
+
+    for &i in &closed {
+        let probe = segments[i].first();
+        parents[i] = tree.query(probe.aabb())
+            .into_iter()
+            .map(|(&j, _, _)| j)
+            .filter(|&j| j != i && areas[j] > areas[i])
+            .filter(|&j| contains_point(&segments[j].path, probe))
+            .min_by(|&a, &b| areas[a].partial_cmp(&areas[b]).unwrap_or(Ordering::Equal));
+    }
+
+    parents
+}
+
+/// Orders closed segments so that every loop comes before the loops that
+/// contain it, and normalizes their winding: loops at an even depth (outer
+/// boundaries) get a positive signed area, loops at an odd depth (holes) get
+/// a negative one.
+///
+/// Open segments keep their relative order and are placed after the loops.
+pub fn order_by_containment<S: 'static>(segments: Vec<PathSegment<S>>) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let parents = nesting(&segments);
+    let depth = |mut i: usize| {
+        let mut depth = 0;
+        while let Some(parent) = parents[i] {
+            depth += 1;
+            i = parent;
+        }
+        depth
+    };
+
+    let mut keyed: Vec<(Option<usize>, PathSegment<S>)> = segments
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| (if s.closed { Some(depth(i)) } else { None }, s))
+        .collect();
+
+    for &mut (depth, ref mut segment) in &mut keyed {
+        if let Some(depth) = depth {
+            let positive = area(&segment.path) > 0.0;
+            if positive != (depth % 2 == 0) {
+                segment.path.reverse();
+            }
+        }
+    }
+
+    // Deepest loops first, open segments last.
+    keyed.sort_by(|&(a, _), &(b, _)| match (a, b) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    keyed.into_iter().map(|(_, s)| s).collect()
+}
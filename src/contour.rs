@@ -0,0 +1,84 @@
+use ::*;
+
+/// Traces the lines where a scalar field crosses `threshold` with marching
+/// squares, returning them as a soup of two-point segments ready for
+/// `stitch_segment_soup`.
+///
+/// `field` holds `height` rows of `width` samples each.  The sample at
+/// column `x` and row `y` sits at the point `(x, y)`, and crossings are
+/// placed along the edges between samples by linear interpolation.  Samples
+/// at or above `threshold` count as inside.  Ambiguous cells are resolved
+/// using the average of their corners, and cells with a NaN corner are
+/// skipped.
+pub fn marching_squares<S>(field: &[f32], width: usize, height: usize, threshold: f32) -> Vec<(Point<S>, Point<S>)> {
+    assert_eq!(field.len(), width * height);
+    let at = |x: usize, y: usize| field[y * width + x];
+
+    let mut out = vec![];
+    for y in 0..height.saturating_sub(1) {
+        for x in 0..width.saturating_sub(1) {
+            let (tl, tr, br, bl) = (at(x, y), at(x + 1, y), at(x + 1, y + 1), at(x, y + 1));
+            if tl.is_nan() || tr.is_nan() || br.is_nan() || bl.is_nan() {
+                continue;
+            }
+            let inside = |v: f32| v >= threshold;
+            let case = (inside(tl) as u8) << 3 | (inside(tr) as u8) << 2 | (inside(br) as u8) << 1
+                | inside(bl) as u8;
+            if case == 0 || case == 15 {
+                continue;
+            }
+
+            // Each edge is always interpolated in the same direction so that
+            // neighboring cells agree exactly on where the crossing is.
+            let (fx, fy) = (x as f32, y as f32);
+            let cross = |a: f32, b: f32| (threshold - a) / (b - a);
+            let top = || Point::new(fx + cross(tl, tr), fy);
+            let bottom = || Point::new(fx + cross(bl, br), fy + 1.0);
+            let left = || Point::new(fx, fy + cross(tl, bl));
+            let right = || Point::new(fx + 1.0, fy + cross(tr, br));
+            let center_inside = || inside((tl + tr + br + bl) / 4.0);
+
+            match case {
+                1 | 14 => out.push((left(), bottom())),
+                2 | 13 => out.push((bottom(), right())),
+                3 | 12 => out.push((left(), right())),
+                4 | 11 => out.push((top(), right())),
+                6 | 9 => out.push((top(), bottom())),
+                7 | 8 => out.push((left(), top())),
+                5 => {
+                    if center_inside() {
+                        out.push((left(), top()));
+                        out.push((bottom(), right()));
+                    } else {
+                        out.push((left(), bottom()));
+                        out.push((top(), right()));
+                    }
+                }
+                10 => {
+                    if center_inside() {
+                        out.push((top(), right()));
+                        out.push((left(), bottom()));
+                    } else {
+                        out.push((left(), top()));
+                        out.push((bottom(), right()));
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+    out
+}
+
+/// Traces the iso-lines of a scalar field at `threshold` and stitches them
+/// into paths.  See `marching_squares`.  A relative `epsilon` is resolved
+/// against the bounds of the traced lines.
+pub fn contour<S, E: Into<Epsilon>>(
+    field: &[f32],
+    width: usize,
+    height: usize,
+    threshold: f32,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    stitch_segment_soup(marching_squares(field, width, height, threshold), epsilon)
+}
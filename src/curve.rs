@@ -0,0 +1,400 @@
+use ::*;
+use util::{centered_with_radius, compute_bounding_box};
+use sanitize::is_finite;
+use std::f32::consts::PI;
+
+/// The smallest tolerance that `flatten` works to, as a fraction of the size
+/// of the path.
+const MIN_TOLERANCE: f32 = 1.0e-6;
+
+/// A single piece of a `CurvedPathSegment`.  Each span starts where the
+/// previous one ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Span<S> {
+    /// A straight line.
+    Line {
+        /// Where the line ends.
+        to: Point<S>,
+    },
+    /// A circular arc around `center`.
+    Arc {
+        /// The center of the circle.
+        center: Point<S>,
+        /// Where the arc ends.
+        to: Point<S>,
+        /// True if the arc sweeps in the direction of decreasing angle.
+        clockwise: bool,
+    },
+    /// A cubic Bézier curve.
+    Cubic {
+        /// The first control point.
+        ctrl1: Point<S>,
+        /// The second control point.
+        ctrl2: Point<S>,
+        /// Where the curve ends.
+        to: Point<S>,
+    },
+}
+
+impl<S> Span<S> {
+    fn to(&self) -> Point<S> {
+        match *self {
+            Span::Line { to } | Span::Arc { to, .. } | Span::Cubic { to, .. } => to,
+        }
+    }
+
+    /// The points that the span bends around, including where it ends.
+    fn control_points(&self) -> Vec<Point<S>> {
+        match *self {
+            Span::Line { to } => vec![to],
+            Span::Arc { center, to, .. } => vec![center, to],
+            Span::Cubic { ctrl1, ctrl2, to } => vec![ctrl1, ctrl2, to],
+        }
+    }
+
+    /// The span that goes from `self.to()` back to `from`.
+    fn reversed(&self, from: Point<S>) -> Span<S> {
+        match *self {
+            Span::Line { .. } => Span::Line { to: from },
+            Span::Arc { center, clockwise, .. } => Span::Arc {
+                center: center,
+                to: from,
+                clockwise: !clockwise,
+            },
+            Span::Cubic { ctrl1, ctrl2, .. } => Span::Cubic {
+                ctrl1: ctrl2,
+                ctrl2: ctrl1,
+                to: from,
+            },
+        }
+    }
+
+    fn length(&self, from: Point<S>) -> f32 {
+        match *self {
+            Span::Line { to } => (to - from).length(),
+            Span::Arc { center, to, clockwise } => {
+                (from - center).length() * arc_sweep(from, center, to, clockwise).abs()
+            }
+            Span::Cubic { ctrl1, ctrl2, to } => cubic_length(from, ctrl1, ctrl2, to, 0),
+        }
+    }
+
+    /// Pushes points approximating the span (excluding `from`) onto `out`.
+    fn flatten_into(&self, from: Point<S>, tolerance: f32, out: &mut Vec<Point<S>>) {
+        match *self {
+            Span::Line { to } => out.push(to),
+            Span::Arc { center, to, clockwise } => {
+                let radius = (from - center).length();
+                let sweep = arc_sweep(from, center, to, clockwise);
+                let start_angle = (from.y - center.y).atan2(from.x - center.x);
+                let step = if tolerance < radius {
+                    2.0 * (1.0 - tolerance / radius).acos()
+                } else {
+                    PI
+                };
+                let count = (sweep.abs() / step).ceil().max(1.0) as usize;
+                for i in 1..count {
+                    let angle = start_angle + sweep * (i as f32 / count as f32);
+                    out.push(Point::new(
+                        center.x + radius * angle.cos(),
+                        center.y + radius * angle.sin(),
+                    ));
+                }
+                out.push(to);
+            }
+            Span::Cubic { ctrl1, ctrl2, to } => {
+                // The distance between a cubic and its chords is bounded by
+                // its second derivative, which is at most 6 times the
+                // largest second difference of the control points.
+                let dd = (from.to_vector() - ctrl1.to_vector() * 2.0 + ctrl2.to_vector())
+                    .length()
+                    .max((ctrl1.to_vector() - ctrl2.to_vector() * 2.0 + to.to_vector()).length());
+                let count = (0.75 * dd / tolerance).sqrt().ceil().max(1.0) as usize;
+                for i in 1..count {
+                    out.push(cubic_at(from, ctrl1, ctrl2, to, i as f32 / count as f32));
+                }
+                out.push(to);
+            }
+        }
+    }
+}
+
+/// A path made of straight lines, arcs and cubic Béziers.
+///
+/// Stitching only looks at the endpoints; lengths and flattening use the
+/// true curves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurvedPathSegment<S> {
+    /// Where the first span starts.
+    pub start: Point<S>,
+    /// The spans that make up the path.
+    pub spans: Vec<Span<S>>,
+    /// True if the last span ends at `start`.
+    pub closed: bool,
+}
+
+impl<S> CurvedPathSegment<S> {
+    /// Creates a curved segment.  It is closed if the last span ends within
+    /// `epsilon` of `start`.
+    pub fn new<E: Into<Epsilon>>(start: Point<S>, spans: Vec<Span<S>>, epsilon: E) -> CurvedPathSegment<S> {
+        assert!(!spans.is_empty());
+        let epsilon = epsilon.into().resolve_with(|| {
+            compute_bounding_box(Some(start).into_iter().chain(spans.iter().map(Span::to)))
+        });
+        let mut segment = CurvedPathSegment {
+            start: start,
+            spans: spans,
+            closed: false,
+        };
+        segment.closed = is_close(segment.start, segment.last(), epsilon);
+        segment
+    }
+
+    /// The first point of the path.
+    pub fn first(&self) -> Point<S> {
+        self.start
+    }
+
+    /// The last point of the path.
+    pub fn last(&self) -> Point<S> {
+        self.spans.last().map(|s| s.to()).unwrap_or(self.start)
+    }
+
+    /// The length of the path along the true curves.
+    pub fn length(&self) -> f32 {
+        let mut from = self.start;
+        let mut length = 0.0;
+        for span in &self.spans {
+            length += span.length(from);
+            from = span.to();
+        }
+        length
+    }
+
+    /// The same path, traced in the opposite direction.
+    pub fn reversed(&self) -> CurvedPathSegment<S> {
+        let mut froms = Vec::with_capacity(self.spans.len());
+        let mut from = self.start;
+        for span in &self.spans {
+            froms.push(from);
+            from = span.to();
+        }
+
+        CurvedPathSegment {
+            start: self.last(),
+            spans: self.spans
+                .iter()
+                .zip(froms)
+                .rev()
+                .map(|(span, from)| span.reversed(from))
+                .collect(),
+            closed: self.closed,
+        }
+    }
+
+    /// Approximates the path with straight lines that stay within
+    /// `tolerance` of the true curves.
+    ///
+    /// A `tolerance` below a millionth of the size of the path, including
+    /// zero, negative and NaN ones, is raised to that, so that there is a
+    /// bound on how many points come out.
+    pub fn flatten<E: Into<Epsilon>>(&self, tolerance: f32, epsilon: E) -> PathSegment<S> {
+        let bounds = compute_bounding_box(
+            Some(self.start)
+                .into_iter()
+                .chain(self.spans.iter().flat_map(|s| s.control_points())),
+        );
+        let tolerance = tolerance.max(bounds.size.width.max(bounds.size.height) * MIN_TOLERANCE);
+
+        let mut points = vec![self.start];
+        for span in &self.spans {
+            let from = *points.last().unwrap();
+            span.flatten_into(from, tolerance, &mut points);
+        }
+        PathSegment::new(points, epsilon)
+    }
+
+    fn append(&mut self, other: CurvedPathSegment<S>, epsilon: f32) {
+        self.spans.extend(other.spans);
+        self.closed = is_close(self.start, self.last(), epsilon);
+    }
+}
+
+/// Joins curved segments end to end wherever there is exactly one
+/// candidate, reversing segments where needed.
+///
+/// Segments are cut where a span has a NaN or infinite point, and the
+/// pieces are open.
+pub fn connect_obvious_curved<S: 'static, E: Into<Epsilon>>(
+    segments: Vec<CurvedPathSegment<S>>,
+    epsilon: E,
+) -> Vec<CurvedPathSegment<S>> {
+    let segments = sanitize_curved(segments);
+    let (mut out, open): (Vec<_>, Vec<_>) = segments.into_iter().partition(|s| s.closed);
+    if open.is_empty() {
+        return out;
+    }
+
+    let bounds = compute_bounding_box(open.iter().flat_map(|s| vec![s.first(), s.last()]));
+    let epsilon = epsilon.into().resolve(&bounds);
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+    let mut tree = QuadTree::default(bounds);
+    let mut ids = Vec::with_capacity(open.len());
+    for (i, segment) in open.iter().enumerate() {
+        let start_id = tree.insert_with_box((i, false), segment.first().aabb()).unwrap();
+        let end_id = tree.insert_with_box((i, true), segment.last().aabb()).unwrap();
+        ids.push((start_id, end_id));
+    }
+
+    let mut slots: Vec<_> = open.into_iter().map(Some).collect();
+
+    for i in 0..slots.len() {
+        if slots[i].is_none() {
+            continue;
+        }
+        let mut chain = take(&mut tree, &mut slots, &ids, i);
+
+        while !chain.closed {
+            match unique_neighbor(&tree, chain.last(), epsilon) {
+                Some((j, at_end)) => {
+                    let next = take(&mut tree, &mut slots, &ids, j);
+                    let next = if at_end { next.reversed() } else { next };
+                    chain.append(next, epsilon);
+                }
+                None => break,
+            }
+        }
+
+        while !chain.closed {
+            match unique_neighbor(&tree, chain.first(), epsilon) {
+                Some((j, at_end)) => {
+                    let prev = take(&mut tree, &mut slots, &ids, j);
+                    let mut prev = if at_end { prev } else { prev.reversed() };
+                    prev.append(chain, epsilon);
+                    chain = prev;
+                }
+                None => break,
+            }
+        }
+
+        out.push(chain);
+    }
+
+    return out;
+
+    fn take<S>(
+        tree: &mut QuadTree<(usize, bool), S>,
+        slots: &mut [Option<CurvedPathSegment<S>>],
+        ids: &[(ItemId, ItemId)],
+        i: usize,
+    ) -> CurvedPathSegment<S> {
+        let (start_id, end_id) = ids[i];
+        tree.remove(start_id);
+        tree.remove(end_id);
+        slots[i].take().unwrap()
+    }
+}
+
+/// Like `sanitize_segments`, but drops whole spans: a span with a NaN or
+/// infinite point ends the piece before it, and the next piece starts where
+/// the span ends, if that point is finite.
+fn sanitize_curved<S>(segments: Vec<CurvedPathSegment<S>>) -> Vec<CurvedPathSegment<S>> {
+    let finite = |span: &Span<S>| span.control_points().into_iter().all(|p| is_finite(p));
+
+    let mut out = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if is_finite(segment.start) && segment.spans.iter().all(&finite) {
+            out.push(segment);
+            continue;
+        }
+
+        let mut start = Some(segment.start).filter(|&p| is_finite(p));
+        let mut spans = vec![];
+        for span in segment.spans {
+            if start.is_some() && finite(&span) {
+                spans.push(span);
+                continue;
+            }
+            if let Some(start) = start {
+                if !spans.is_empty() {
+                    out.push(CurvedPathSegment {
+                        start: start,
+                        spans: std::mem::replace(&mut spans, vec![]),
+                        closed: false,
+                    });
+                }
+            }
+            spans.clear();
+            start = Some(span.to()).filter(|&p| is_finite(p));
+        }
+        if let Some(start) = start {
+            if !spans.is_empty() {
+                out.push(CurvedPathSegment {
+                    start: start,
+                    spans: spans,
+                    closed: false,
+                });
+            }
+        }
+    }
+    out
+}
+
+fn unique_neighbor<S>(tree: &QuadTree<(usize, bool), S>, point: Point<S>, epsilon: f32) -> Option<(usize, bool)> {
+    let hits = tree.query(centered_with_radius(point, epsilon));
+    if hits.len() == 1 {
+        Some(*hits[0].0)
+    } else {
+        None
+    }
+}
+
+fn is_close<S>(a: Point<S>, b: Point<S>, epsilon: f32) -> bool {
+    centered_with_radius(a, epsilon).contains(&b)
+}
+
+/// The signed angle swept by an arc from `from` to `to` around `center`.
+fn arc_sweep<S>(from: Point<S>, center: Point<S>, to: Point<S>, clockwise: bool) -> f32 {
+    let a0 = (from.y - center.y).atan2(from.x - center.x);
+    let a1 = (to.y - center.y).atan2(to.x - center.x);
+    let mut sweep = a1 - a0;
+    if clockwise {
+        while sweep >= 0.0 {
+            sweep -= 2.0 * PI;
+        }
+    } else {
+        while sweep <= 0.0 {
+            sweep += 2.0 * PI;
+        }
+    }
+    sweep
+}
+
+fn cubic_at<S>(p0: Point<S>, p1: Point<S>, p2: Point<S>, p3: Point<S>, t: f32) -> Point<S> {
+    let mt = 1.0 - t;
+    let v = p0.to_vector() * (mt * mt * mt) + p1.to_vector() * (3.0 * mt * mt * t)
+        + p2.to_vector() * (3.0 * mt * t * t) + p3.to_vector() * (t * t * t);
+    v.to_point()
+}
+
+fn cubic_length<S>(p0: Point<S>, p1: Point<S>, p2: Point<S>, p3: Point<S>, depth: u32) -> f32 {
+    let chord = (p3 - p0).length();
+    let polygon = (p1 - p0).length() + (p2 - p1).length() + (p3 - p2).length();
+    if depth >= 16 || polygon - chord <= polygon * 1.0e-4 {
+        return (2.0 * chord + polygon) / 3.0;
+    }
+
+    // Split in half with de Casteljau.
+    let mid = |a: Point<S>, b: Point<S>| ((a.to_vector() + b.to_vector()) * 0.5).to_point();
+    let p01 = mid(p0, p1);
+    let p12 = mid(p1, p2);
+    let p23 = mid(p2, p3);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let center = mid(p012, p123);
+
+    cubic_length(p0, p01, p012, center, depth + 1) + cubic_length(center, p123, p23, p3, depth + 1)
+}
@@ -0,0 +1,78 @@
+use ::*;
+use stats::raw_length;
+use sanitize::sanitize_segments;
+
+/// Cuts every segment into dashes following `pattern`, which alternates
+/// between the lengths of dashes and gaps, starting with a dash.
+///
+/// The pattern starts `phase` units in at the beginning of every segment
+/// and is measured along the path, so dashes carry on around corners.  As
+/// with SVG, a pattern with an odd number of entries is repeated twice.
+pub fn dash<S>(segments: Vec<PathSegment<S>>, pattern: &[f32], phase: f32) -> Vec<PathSegment<S>> {
+    assert!(!pattern.is_empty());
+    assert!(pattern.iter().all(|&p| p >= 0.0));
+    let segments = sanitize_segments(segments);
+    let mut pattern = pattern.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(&pattern.clone());
+    }
+    let period: f32 = pattern.iter().sum();
+    assert!(period > 0.0);
+
+    // Find where in the pattern `phase` lands.
+    let mut start = 0;
+    let mut offset = phase % period;
+    if offset < 0.0 {
+        offset += period;
+    }
+    while offset >= pattern[start] {
+        offset -= pattern[start];
+        start = (start + 1) % pattern.len();
+    }
+
+    let mut out = vec![];
+    for segment in segments {
+        let (layer, first) = (segment.layer, out.len());
+        let points: Points<S> = segment.into();
+        let mut index = start;
+        let mut remaining = pattern[index] - offset;
+        let mut current = if index % 2 == 0 { vec![points[0]] } else { vec![] };
+
+        for edge in points.windows(2) {
+            let (a, b) = (edge[0], edge[1]);
+            let length = (b - a).length();
+            let mut walked = 0.0;
+            while length - walked > remaining {
+                walked += remaining;
+                let p = a + (b - a) * (walked / length);
+                if index % 2 == 0 {
+                    current.push(p);
+                    emit(&mut out, &mut current);
+                } else {
+                    current = vec![p];
+                }
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+            }
+            remaining -= length - walked;
+            if index % 2 == 0 {
+                current.push(b);
+            }
+        }
+
+        if index % 2 == 0 {
+            emit(&mut out, &mut current);
+        }
+        for dash in &mut out[first..] {
+            dash.layer = layer;
+        }
+    }
+    return out;
+
+    fn emit<S>(out: &mut Vec<PathSegment<S>>, current: &mut Vec<Point<S>>) {
+        let points = std::mem::replace(current, vec![]);
+        if points.len() > 1 && raw_length(&points) > 0.0 {
+            out.push(PathSegment::new(points, 0.0));
+        }
+    }
+}
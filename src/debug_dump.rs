@@ -0,0 +1,99 @@
+use ::*;
+use std::fmt::Write;
+use util::{centered_with_radius, compute_bounding_box};
+
+/// Renders the state that `connect_obvious` would start from as an SVG
+/// document.
+///
+/// Segment bodies are drawn in black, starts in green and ends in red.
+/// Endpoints where more than one join is possible are circled in orange,
+/// and the cells of the start and end quadtrees are outlined in grey.
+pub fn debug_dump<P, I, S: 'static, E: Into<Epsilon>>(segments: I, epsilon: E) -> String
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    debug_dump_tree(&DualQuadTree::from_segments(segments, epsilon))
+}
+
+/// Renders paths that have already been taken out of the index, as the
+/// passes that run after stitching leave them.
+pub(crate) fn debug_dump_paths<S: 'static>(paths: &[PathSegment<S>], epsilon: f32, index: IndexBackend) -> String {
+    let aabb = compute_bounding_box(paths.iter().flat_map(|s| s.path.iter().cloned()));
+    let mut dual_qt = DualQuadTree::new(aabb, epsilon, index);
+    for path in paths {
+        dual_qt.insert(path.clone());
+    }
+    debug_dump_tree(&dual_qt)
+}
+
+/// Like `debug_dump`, but renders a `DualQuadTree` as it is, including the
+/// ambiguities found by passes that have already run over it.
+pub fn debug_dump_tree<S: 'static>(dual_qt: &DualQuadTree<S>) -> String {
+    let epsilon = dual_qt.epsilon();
+    let bounds = compute_bounding_box(dual_qt.iter().flat_map(|(_, s)| s.path.iter().cloned()));
+    let margin = bounds.size.width.max(bounds.size.height).max(1.0) / 20.0;
+    let bounds = bounds.inflate(margin, margin);
+    let radius = margin / 5.0;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        bounds.origin.x, bounds.origin.y, bounds.size.width, bounds.size.height
+    ).unwrap();
+
+    writeln!(svg, r#"<g fill="none" stroke="grey" stroke-width="{}">"#, radius / 4.0).unwrap();
+    for tree in &[&dual_qt.starts, &dual_qt.ends] {
+        tree.inspect(|rect| {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                rect.origin.x, rect.origin.y, rect.size.width, rect.size.height
+            ).unwrap();
+        });
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    writeln!(svg, r#"<g fill="none" stroke="black" stroke-width="{}">"#, radius / 2.0).unwrap();
+    for (_, segment) in dual_qt.iter() {
+        let element = if segment.closed { "polygon" } else { "polyline" };
+        write!(svg, r#"<{} points=""#, element).unwrap();
+        for p in &segment.path {
+            write!(svg, "{},{} ", p.x, p.y).unwrap();
+        }
+        writeln!(svg, r#""/>"#).unwrap();
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    let mut ambiguities = dual_qt.ambiguity_points();
+    for (_, segment) in dual_qt.iter() {
+        for &(point, color) in &[(segment.first(), "green"), (segment.last(), "red")] {
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                point.x, point.y, radius, color
+            ).unwrap();
+
+            // Every endpoint finds itself, and a clean join finds one more.
+            let query = centered_with_radius(point, epsilon);
+            let found = dual_qt.starts.query(query).len() + dual_qt.ends.query(query).len();
+            if found > 2 {
+                ambiguities.push(point);
+            }
+        }
+    }
+
+    writeln!(svg, r#"<g fill="none" stroke="orange" stroke-width="{}">"#, radius / 2.0).unwrap();
+    for point in ambiguities {
+        writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+            point.x, point.y, radius * 2.0
+        ).unwrap();
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
@@ -0,0 +1,73 @@
+use ::*;
+use stats::drawn_length;
+use util::{centered_with_radius, compute_bounding_box};
+use sanitize::sanitize_segments;
+
+/// The settings for `prune_by_density`, for use in `PipelineConfig`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DensityPrune {
+    /// The length a segment needs once its neighborhood is crowded.
+    pub min_length: f32,
+    /// How far from its center a segment looks for neighbors.
+    pub radius: f32,
+    /// How many neighbors make a neighborhood crowded.
+    pub crowd: usize,
+}
+
+/// Drops short segments, but only where the drawing is crowded.
+///
+/// The length a segment needs to survive grows with the number of other
+/// segments within `radius` of its center: none at all for a segment on
+/// its own, up to `min_length` once there are `crowd` or more neighbors.
+/// Short strokes in sparse areas are kept as detail, while the same
+/// strokes in the middle of dense hatching are dropped as noise.
+///
+/// A `crowd` of zero counts every neighborhood as crowded, so every segment
+/// shorter than `min_length` is dropped.
+pub fn prune_by_density<S: 'static>(
+    segments: Vec<PathSegment<S>>,
+    min_length: f32,
+    radius: f32,
+    crowd: usize,
+) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    if crowd == 0 {
+        return segments.into_iter().filter(|s| drawn_length(s) >= min_length).collect();
+    }
+    if segments.is_empty() {
+        return segments;
+    }
+
+    let boxes: Vec<_> = segments
+        .iter()
+        .map(|s| compute_bounding_box(s.path.iter().cloned()))
+        .collect();
+    let bounds = boxes.iter().skip(1).fold(boxes[0], |a, b| a.union(b));
+    let bounds = bounds.inflate(radius.max(1.0), radius.max(1.0));
+    let mut tree = QuadTree::default(bounds);
+    for (i, b) in boxes.iter().enumerate() {
+        tree.insert_with_box(i, *b);
+    }
+
+    let keep: Vec<bool> = segments
+        .iter()
+        .zip(&boxes)
+        .enumerate()
+        .map(|(i, (segment, b))| {
+            let neighbors = tree
+                .query(centered_with_radius(b.origin + b.size.to_vector() * 0.5, radius))
+                .into_iter()
+                .filter(|&(&j, _, _)| j != i)
+                .count();
+            let crowding = (neighbors as f32 / crowd as f32).min(1.0);
+            drawn_length(segment) >= min_length * crowding
+        })
+        .collect();
+
+    segments
+        .into_iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(segment, _)| segment)
+        .collect()
+}
@@ -0,0 +1,119 @@
+use ::*;
+use sanitize::sanitize_segments;
+
+/// The direction that `orient` makes paths run in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// From small to large `x`.
+    IncreasingX,
+    /// From large to small `x`.
+    DecreasingX,
+    /// From small to large `y` (top to bottom in screen coordinates).
+    IncreasingY,
+    /// From large to small `y`.
+    DecreasingY,
+    /// Along the axis that most of the drawing's edges follow, weighted by
+    /// length, pointing towards increasing `x` (or increasing `y` if the
+    /// axis is vertical).
+    Dominant,
+}
+
+/// Reverses open segments so that they run in `direction`.
+///
+/// A segment runs in `direction` if its end is further along `direction`
+/// than its start.  With `split`, segments are also cut wherever they turn
+/// back on themselves, so that every edge of the output runs in
+/// `direction`.  Closed segments are left alone.
+pub fn orient<S>(segments: Vec<PathSegment<S>>, direction: Direction, split: bool) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let along = match direction {
+        Direction::IncreasingX => euclid::vec2(1.0, 0.0),
+        Direction::DecreasingX => euclid::vec2(-1.0, 0.0),
+        Direction::IncreasingY => euclid::vec2(0.0, 1.0),
+        Direction::DecreasingY => euclid::vec2(0.0, -1.0),
+        Direction::Dominant => dominant_axis(&segments),
+    };
+
+    let mut out = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if segment.closed {
+            out.push(segment);
+        } else if split {
+            for mut piece in monotone_pieces(segment, along) {
+                orient_one(&mut piece, along);
+                out.push(piece);
+            }
+        } else {
+            let mut segment = segment;
+            orient_one(&mut segment, along);
+            out.push(segment);
+        }
+    }
+    out
+}
+
+fn orient_one<S>(segment: &mut PathSegment<S>, along: euclid::TypedVector2D<f32, S>) {
+    if (segment.last() - segment.first()).dot(along) < 0.0 {
+        segment.path.reverse();
+    }
+}
+
+/// Cuts `segment` at every vertex where it switches between moving with
+/// and against `along`.
+fn monotone_pieces<S>(segment: PathSegment<S>, along: euclid::TypedVector2D<f32, S>) -> Vec<PathSegment<S>> {
+    let (points, layer) = (segment.path, segment.layer);
+    let mut out = vec![];
+    let mut piece = vec![points[0]];
+    let mut forward = None;
+
+    for w in points.windows(2) {
+        let d = (w[1] - w[0]).dot(along);
+        let this = if d > 0.0 {
+            Some(true)
+        } else if d < 0.0 {
+            Some(false)
+        } else {
+            None
+        };
+
+        if let (Some(a), Some(b)) = (forward, this) {
+            if a != b {
+                out.push(PathSegment::new(std::mem::replace(&mut piece, vec![w[0]]), 0.0).with_layer(layer));
+            }
+        }
+        if this.is_some() {
+            forward = this;
+        }
+        piece.push(w[1]);
+    }
+
+    out.push(PathSegment::new(piece, 0.0).with_layer(layer));
+    out
+}
+
+/// The length-weighted average direction of every edge, treating opposite
+/// directions as the same.
+fn dominant_axis<S>(segments: &[PathSegment<S>]) -> euclid::TypedVector2D<f32, S> {
+    // Doubling the angle of each edge maps opposite directions onto each
+    // other, so they add up instead of cancelling out.
+    let (mut c, mut s) = (0.0, 0.0);
+    for segment in segments {
+        for w in segment.path.windows(2) {
+            let d = w[1] - w[0];
+            let length = d.length();
+            if length > 0.0 {
+                let angle = 2.0 * d.y.atan2(d.x);
+                c += length * angle.cos();
+                s += length * angle.sin();
+            }
+        }
+    }
+
+    let angle = s.atan2(c) / 2.0;
+    let axis = euclid::vec2(angle.cos(), angle.sin());
+    if axis.x < -1.0e-6 || (axis.x.abs() <= 1.0e-6 && axis.y < 0.0) {
+        -axis
+    } else {
+        axis
+    }
+}
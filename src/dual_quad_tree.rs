@@ -1,52 +1,169 @@
 use fnv::FnvHashMap as HashMap;
+use std::collections::hash_map;
 use ::*;
 use euclid;
+use spatial_index::{Index, IndexBackend, IndexId};
 
-#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+/// Identifies a segment inside of a `DualQuadTree`.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub struct DqtId(u32);
+
+/// A set of segments indexed by their starts and ends.
+///
+/// Build one with `from_segments` and hand it to the `*_in_place` passes to
+/// run several passes without rebuilding the index each time.  Segments on
+/// different layers share the index but never see each other.
 pub struct DualQuadTree<S> {
     id: u32,
-    id_to_segment: HashMap<DqtId, (PathSegment<S>, ItemId, ItemId)>,
-    pub starts: QuadTree<DqtId, S>,
-    pub ends: QuadTree<DqtId, S>,
-    ambiguity_points: QuadTree<Point<S>, S>,
+    epsilon: f32,
+    aabb: euclid::TypedRect<f32, S>,
+    index: IndexBackend,
+    id_to_segment: HashMap<DqtId, (PathSegment<S>, IndexId, IndexId)>,
+    pub(crate) starts: Index<DqtId, S>,
+    pub(crate) ends: Index<DqtId, S>,
+    ambiguity_points: QuadTree<(Point<S>, LayerId), S>,
+    /// Ends of removed segments whose neighbors `restitch` should revisit.
+    pub(crate) dirty: Vec<Point<S>>,
+    /// How many times the start and end indices were queried for a join,
+    /// and how many of those queries found something.
+    queries: usize,
+    query_hits: usize,
 }
 
 impl <S: 'static> DualQuadTree<S> {
-    pub fn new(aabb: euclid::TypedRect<f32, S>) -> DualQuadTree<S> {
+    pub(crate) fn new(aabb: euclid::TypedRect<f32, S>, epsilon: f32, index: IndexBackend) -> DualQuadTree<S> {
         DualQuadTree {
             id: 0,
+            epsilon: epsilon,
+            aabb: aabb,
+            index: index,
             id_to_segment: HashMap::default(),
-            starts: QuadTree::default(aabb),
-            ends: QuadTree::default(aabb),
+            starts: Index::new(index, aabb, epsilon),
+            ends: Index::new(index, aabb, epsilon),
             ambiguity_points: QuadTree::default(aabb),
+            dirty: vec![],
+            queries: 0,
+            query_hits: 0,
         }
     }
 
-    pub fn iter<'a>(&'a self) -> Box<Iterator<Item = (DqtId, &'a PathSegment<S>)> + 'a> {
-        let iterator = self.id_to_segment.iter().map(|(&k, &(ref p, _, _))| (k, p));
-        Box::new(iterator) as Box<Iterator<Item = (DqtId, &PathSegment<S>)> + 'a>
+    /// Empties the tree and gets it ready for segments inside of `aabb`,
+    /// keeping as much of its memory as it can.
+    pub(crate) fn refill(&mut self, aabb: euclid::TypedRect<f32, S>, epsilon: f32) {
+        self.id = 0;
+        self.epsilon = epsilon;
+        self.aabb = aabb;
+        self.id_to_segment.clear();
+        self.starts.reset(self.index, aabb, epsilon);
+        self.ends.reset(self.index, aabb, epsilon);
+        self.ambiguity_points = QuadTree::default(aabb);
+        self.dirty.clear();
+        self.queries = 0;
+        self.query_hits = 0;
+    }
+
+    /// Indexes the segments.  A relative `epsilon` is resolved against the
+    /// bounds of all of them.
+    pub fn from_segments<P, I, E>(segments: I, epsilon: E) -> DualQuadTree<S>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+        E: Into<Epsilon>,
+    {
+        util::populate(segments, epsilon.into()).0
     }
 
-    pub fn into_iter(self) -> Box<Iterator<Item = PathSegment<S>>> {
-        let iterator = self.id_to_segment.into_iter().map(|(_, (p, _, _))| p);
-        Box::new(iterator) as Box<Iterator<Item = PathSegment<S>>>
+    /// Like `from_segments`, but keeps the endpoints in `index`.
+    pub fn from_segments_with_index<P, I, E>(segments: I, epsilon: E, index: IndexBackend) -> DualQuadTree<S>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Points<S>>,
+        E: Into<Epsilon>,
+    {
+        util::populate_with_index(segments, epsilon.into(), index).0
     }
 
-    pub fn insert(&mut self, segment: PathSegment<S>) {
+    /// The distance under which two points are considered the same.
+    pub fn epsilon(&self) -> f32 {
+        self.epsilon
+    }
+
+    /// Iterates over the segments in no particular order.
+    pub fn iter(&self) -> Iter<S> {
+        Iter {
+            inner: self.id_to_segment.iter(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, segment: PathSegment<S>) -> DqtId {
         let id = self.id;
         self.id += 1;
         let id = DqtId(id);
 
         let start = segment.first();
         let end = segment.last();
+        if !self.aabb.contains(&start) || !self.aabb.contains(&end) {
+            self.grow_to_fit(start, end);
+        }
 
-        let start_id = self.starts.insert_with_box(id, start.aabb()).unwrap();
-        let end_id = self.ends.insert_with_box(id, end.aabb()).unwrap();
+        let start_id = self.starts.insert(id, start.aabb()).unwrap();
+        let end_id = self.ends.insert(id, end.aabb()).unwrap();
         self.id_to_segment.insert(id, (segment, start_id, end_id));
+        id
+    }
+
+    /// Rebuilds the indices over bounds that also cover `a` and `b`.
+    fn grow_to_fit(&mut self, a: Point<S>, b: Point<S>) {
+        let rect = self.aabb.union(&euclid::TypedRect::from_points(&[a, b]));
+        self.aabb = rect.inflate(
+            self.epsilon.max(rect.size.width / 10.0),
+            self.epsilon.max(rect.size.height / 10.0),
+        );
+
+        self.starts = Index::new(self.index, self.aabb, self.epsilon);
+        self.ends = Index::new(self.index, self.aabb, self.epsilon);
+        for (&id, &mut (ref segment, ref mut start_id, ref mut end_id)) in self.id_to_segment.iter_mut() {
+            *start_id = self.starts.insert(id, segment.first().aabb()).unwrap();
+            *end_id = self.ends.insert(id, segment.last().aabb()).unwrap();
+        }
+
+        let mut ambiguity_points = QuadTree::default(self.aabb);
+        for (_, &(item, _)) in self.ambiguity_points.iter() {
+            ambiguity_points.insert_with_box(item, item.0.aabb());
+        }
+        self.ambiguity_points = ambiguity_points;
     }
 
-    pub fn pop(&mut self) -> Option<PathSegment<S>> {
+    /// Takes a segment out, for instance to replace it with an edited
+    /// version through `restitch`.
+    ///
+    /// Ambiguities found at its ends are forgotten, since they may have been
+    /// caused by this segment, and the next `restitch` revisits the
+    /// segments around them.
+    pub fn remove_segment(&mut self, dqt_id: DqtId) -> Option<PathSegment<S>> {
+        let segment = self.remove_if_present(dqt_id);
+        if let Some(ref segment) = segment {
+            for &p in &[segment.first(), segment.last()] {
+                self.forget_ambiguities_near(p);
+                self.dirty.push(p);
+            }
+        }
+        segment
+    }
+
+    pub(crate) fn forget_ambiguities_near(&mut self, point: Point<S>) {
+        let query_aabb = point.aabb().inflate(self.epsilon, self.epsilon);
+        let found: Vec<ItemId> = self.ambiguity_points
+            .query(query_aabb)
+            .into_iter()
+            .map(|(_, _, item_id)| item_id)
+            .collect();
+        for item_id in found {
+            self.ambiguity_points.remove(item_id);
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<PathSegment<S>> {
         let dqt_id = {
             let first = self.id_to_segment.iter().next();
             if let Some((&dqt_id, _)) = first {
@@ -59,65 +176,127 @@ impl <S: 'static> DualQuadTree<S> {
         self.remove(dqt_id)
     }
 
-    pub fn remove(&mut self, dqt_id: DqtId) -> Option<PathSegment<S>> {
+    pub(crate) fn get(&self, dqt_id: DqtId) -> Option<&PathSegment<S>> {
+        self.id_to_segment.get(&dqt_id).map(|&(ref segment, _, _)| segment)
+    }
+
+    /// Every segment with an endpoint near `point`, along with whether it
+    /// was the end (rather than the start) that matched.  Only segments on
+    /// `layer` are returned, unless it is `None`.
+    pub(crate) fn candidates(&self, point: Point<S>, epsilon: f32, layer: Option<LayerId>) -> Vec<(DqtId, bool)> {
+        let query_aabb = point.aabb().inflate(epsilon, epsilon);
+        let starts = self.starts.query(query_aabb).into_iter().map(|(&id, _)| (id, false));
+        let ends = self.ends.query(query_aabb).into_iter().map(|(&id, _)| (id, true));
+        starts
+            .chain(ends)
+            .filter(|&(id, _)| layer.map_or(true, |layer| self.layer_of(id) == layer))
+            .collect()
+    }
+
+    pub(crate) fn remove_if_present(&mut self, dqt_id: DqtId) -> Option<PathSegment<S>> {
+        if self.id_to_segment.contains_key(&dqt_id) {
+            self.remove(dqt_id)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn remove(&mut self, dqt_id: DqtId) -> Option<PathSegment<S>> {
         let (segment, start_id, end_id) = self.id_to_segment.remove(&dqt_id).unwrap();
         self.starts.remove(start_id);
         self.ends.remove(end_id);
         return Some(segment);
     }
 
-    #[allow(dead_code)]
+    /// The number of segments.
+    pub fn len(&self) -> usize {
+        self.id_to_segment.len()
+    }
+
+    /// True if there are no segments.
     pub fn is_empty(&self) -> bool {
         self.id_to_segment.is_empty()
     }
 
-    pub fn has_forward_neighbor(&self, id: DqtId, point: Point<S>, epsilon: f32) -> bool {
+    pub(crate) fn clear_ambiguities(&mut self) {
+        self.ambiguity_points = QuadTree::default(self.aabb);
+    }
+
+    pub(crate) fn drain(&mut self) -> Vec<PathSegment<S>> {
+        let mut out = Vec::with_capacity(self.len());
+        while let Some(segment) = self.pop() {
+            out.push(segment);
+        }
+        out
+    }
+
+    pub(crate) fn ambiguity_count(&self) -> usize {
+        self.ambiguity_points.len()
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn ambiguity_points(&self) -> Vec<Point<S>> {
+        self.ambiguity_points.iter().map(|(_, &((p, _), _))| p).collect()
+    }
+
+    fn mark_ambiguous(&mut self, point: Point<S>, layer: LayerId) {
+        self.ambiguity_points.insert_with_box((point, layer), point.aabb());
+    }
+
+    fn layer_of(&self, id: DqtId) -> LayerId {
+        self.id_to_segment[&id].0.layer
+    }
+
+    pub(crate) fn has_forward_neighbor(&self, id: DqtId, point: Point<S>, epsilon: f32) -> bool {
         let query_aabb = point.aabb().inflate(epsilon * 2.0, epsilon * 2.0);
+        let layer = self.layer_of(id);
         self.ends
             .query(query_aabb)
             .into_iter()
-            .filter(|&(&qid, _, _)| qid != id)
-            .count() != 0
+            .any(|(&qid, _)| qid != id && self.layer_of(qid) == layer)
     }
 
-    pub fn has_backward_neighbor(&self, id: DqtId, point: Point<S>, epsilon: f32) -> bool {
+    pub(crate) fn has_backward_neighbor(&self, id: DqtId, point: Point<S>, epsilon: f32) -> bool {
         let query_aabb = point.aabb().inflate(epsilon * 2.0, epsilon * 2.0);
+        let layer = self.layer_of(id);
         self.starts
             .query(query_aabb)
             .into_iter()
-            .filter(|&(&qid, _, _)| qid != id)
-            .count() != 0
+            .any(|(&qid, _)| qid != id && self.layer_of(qid) == layer)
     }
 
-    pub fn query_forward(
+    pub(crate) fn query_forward(
         &mut self,
         point: Point<S>,
+        layer: LayerId,
         epsilon: f32,
         only_starts: bool,
         allow_ambiguous: bool,
     ) -> Option<PathSegment<S>> {
-        self.query_direction(false, point, epsilon, only_starts, allow_ambiguous)
+        self.query_direction(false, point, layer, epsilon, only_starts, allow_ambiguous)
     }
 
-    pub fn query_backward(
+    pub(crate) fn query_backward(
         &mut self,
         point: Point<S>,
+        layer: LayerId,
         epsilon: f32,
         only_starts: bool,
         allow_ambiguous: bool,
     ) -> Option<PathSegment<S>> {
-        self.query_direction(true, point, epsilon, only_starts, allow_ambiguous)
+        self.query_direction(true, point, layer, epsilon, only_starts, allow_ambiguous)
     }
 
     fn query_direction(
         &mut self,
         should_swap: bool,
         point: Point<S>,
+        layer: LayerId,
         epsilon: f32,
         only_starts: bool,
         allow_ambiguous: bool,
     ) -> Option<PathSegment<S>> {
-        let (mut start, mut end) = self.query_impl(point, epsilon, allow_ambiguous);
+        let (mut start, mut end) = self.query_impl(point, layer, epsilon, allow_ambiguous);
         if should_swap {
             std::mem::swap(&mut start, &mut end);
         }
@@ -128,88 +307,139 @@ impl <S: 'static> DualQuadTree<S> {
                 // A start and an end at this point means that there is likely a better
                 // path between those two segments.
                 (Ok(Some(_)), Ok(Some(_))) => {
-                    self.ambiguity_points.insert(point);
+                    self.mark_ambiguous(point, layer);
                     None
                 }
                 // ignore errors here for now
-                (Ok(Some(a)), _) => self.remove(a),
+                (Ok(Some((a, _))), _) => self.remove(a),
                 (Ok(None), _) => None,
                 (Err(_), _) => {
-                    self.ambiguity_points.insert(point);
+                    self.mark_ambiguous(point, layer);
                     None
                 }
             }
         } else {
             match (start, end, allow_ambiguous) {
                 (Ok(None), Ok(None), _) => None,
-                (Ok(Some(a)), Ok(Some(_)), true) => self.remove(a),
+                // The closer of the two wins.
+                (Ok(Some((a, da))), Ok(Some((b, db))), true) => if db < da {
+                    self.remove(b).map(reverse_and_return)
+                } else {
+                    self.remove(a)
+                },
                 (Ok(Some(_)), Ok(Some(_)), false) => {
-                    self.ambiguity_points.insert(point);
+                    self.mark_ambiguous(point, layer);
                     None
                 }
-                (Ok(Some(a)), Ok(None), _) => self.remove(a),
-                (Ok(None), Ok(Some(b)), _) => self.remove(b).map(reverse_and_return),
+                (Ok(Some((a, _))), Ok(None), _) => self.remove(a),
+                (Ok(None), Ok(Some((b, _))), _) => self.remove(b).map(reverse_and_return),
                 (Err(_), _, _) | (_, Err(_), _) => {
-                    self.ambiguity_points.insert(point);
+                    self.mark_ambiguous(point, layer);
                     None
                 }
             }
         }
     }
 
+    /// Finds the start and the end on `layer` closest to `point`, along
+    /// with their squared distances.  Unless `allow_ambiguous` is set, more
+    /// than one candidate is an error.
     fn query_impl(
         &mut self,
         point: Point<S>,
+        layer: LayerId,
         epsilon: f32,
         allow_ambiguous: bool,
-    ) -> (Result<Option<DqtId>, ()>, Result<Option<DqtId>, ()>) {
+    ) -> (Result<Option<(DqtId, f32)>, ()>, Result<Option<(DqtId, f32)>, ()>) {
         let query_aabb = point.aabb().inflate(epsilon, epsilon);
-        if self.ambiguity_points.query(query_aabb).len() > 0 {
+        if self.ambiguity_points.query(query_aabb).iter().any(|&(&(_, l), _, _)| l == layer) {
             return (Ok(None), Ok(None));
         }
 
-        let query_starts = || {
-            let mut out = None;
-            let query = self.starts
-                .query(query_aabb)
-                .into_iter()
-                .map(|(&id, _, _)| id);
-            for id in query {
-                if allow_ambiguous {
-                    return Ok(Some(id));
-                } else {
-                    if out.is_some() {
-                        return Err(());
-                    }
-                    out = Some(id)
-                }
+        let nearest = |hits: Vec<(DqtId, euclid::TypedRect<f32, S>)>| {
+            if hits.len() > 1 && !allow_ambiguous {
+                return Err(());
             }
-            return Ok(out);
-        };
-
-        let query_ends = || {
-            let mut out = None;
-            let query = self.ends
-                .query(query_aabb)
+            Ok(hits
                 .into_iter()
-                .map(|(&id, _, _)| id);
-            for id in query {
-                if allow_ambiguous {
-                    return Ok(Some(id));
-                } else {
-                    if out.is_some() {
-                        return Err(());
-                    }
-                    out = Some(id)
-                }
-            }
-            return Ok(out);
+                .map(|(id, rect)| (id, (rect.origin - point).square_length()))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)))
         };
 
-        (query_starts(), query_ends())
+        let starts = self.on_layer(self.starts.query(query_aabb), layer);
+        let ends = self.on_layer(self.ends.query(query_aabb), layer);
+        self.queries += 2;
+        self.query_hits += (!starts.is_empty()) as usize + (!ends.is_empty()) as usize;
+        (nearest(starts), nearest(ends))
+    }
+
+    fn on_layer(
+        &self,
+        hits: Vec<(&DqtId, euclid::TypedRect<f32, S>)>,
+        layer: LayerId,
+    ) -> Vec<(DqtId, euclid::TypedRect<f32, S>)> {
+        hits.into_iter()
+            .map(|(&id, rect)| (id, rect))
+            .filter(|&(id, _)| self.layer_of(id) == layer)
+            .collect()
+    }
+
+    /// How many times the indices were queried for a join so far, and how
+    /// many of those queries found at least one endpoint.
+    pub(crate) fn query_counts(&self) -> (usize, usize) {
+        (self.queries, self.query_hits)
     }
 }
 
+impl<S> IntoIterator for DualQuadTree<S> {
+    type Item = PathSegment<S>;
+    type IntoIter = IntoIter<S>;
+
+    fn into_iter(self) -> IntoIter<S> {
+        IntoIter {
+            inner: self.id_to_segment.into_iter(),
+        }
+    }
+}
+
+/// Iterator over the segments of a `DualQuadTree`, created by `iter`.
+pub struct Iter<'a, S: 'a> {
+    inner: hash_map::Iter<'a, DqtId, (PathSegment<S>, IndexId, IndexId)>,
+}
+
+impl<'a, S> Iterator for Iter<'a, S> {
+    type Item = (DqtId, &'a PathSegment<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(&k, &(ref p, _, _))| (k, p))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, S> ExactSizeIterator for Iter<'a, S> {}
+
+/// Owning iterator over the segments of a `DualQuadTree`.
+pub struct IntoIter<S> {
+    inner: hash_map::IntoIter<DqtId, (PathSegment<S>, IndexId, IndexId)>,
+}
+
+impl<S> Iterator for IntoIter<S> {
+    type Item = PathSegment<S>;
+
+    fn next(&mut self) -> Option<PathSegment<S>> {
+        self.inner.next().map(|(_, (p, _, _))| p)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> ExactSizeIterator for IntoIter<S> {}
+
 fn reverse_and_return<S>(mut v: PathSegment<S>) -> PathSegment<S> {
     v.path.reverse();
     v
@@ -0,0 +1,129 @@
+use ::*;
+use plan::{Move, Plan};
+
+/// How a plotter moves, used by `estimate_duration`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PenKinematics {
+    /// The top speed while drawing.  Zero or less means the pen can't
+    /// draw, so any plan that draws something takes forever.
+    pub draw_speed: f32,
+    /// The top speed while moving with the pen up.  Zero or less means the
+    /// pen can't travel, so any plan with a pen-up move takes forever.
+    pub travel_speed: f32,
+    /// How quickly the pen speeds up and slows down.  Zero or less means
+    /// the pen reaches full speed instantly.
+    pub accel: f32,
+    /// The time taken to lower the pen before a stroke plus the time taken
+    /// to raise it again afterwards.
+    pub pen_lift_time: f32,
+}
+
+/// Estimates how long it takes to carry out `plan`.
+///
+/// Every move (and every leg of a routed pen-up move) uses a trapezoidal
+/// speed profile that starts and ends at rest.  While drawing, the pen only
+/// slows down at corners as much as the turn requires: it comes to a stop at
+/// a reversal and keeps full speed through a straight continuation.
+///
+/// A speed of zero or less, or NaN, gives an infinite estimate for any plan
+/// that needs it, rather than a negative or NaN one.
+pub fn estimate_duration<S>(plan: &Plan<S>, kinematics: &PenKinematics) -> f32 {
+    plan.moves
+        .iter()
+        .map(|m| match *m {
+            Move::PenUp(ref points) => points
+                .windows(2)
+                .map(|w| {
+                    edge_time(
+                        (w[1] - w[0]).length(),
+                        0.0,
+                        0.0,
+                        top_speed(kinematics.travel_speed),
+                        accel(kinematics),
+                    )
+                })
+                .sum(),
+            Move::PenDown(ref segment) => stroke_time(segment, kinematics) + kinematics.pen_lift_time,
+        })
+        .sum()
+}
+
+fn stroke_time<S>(segment: &PathSegment<S>, kinematics: &PenKinematics) -> f32 {
+    let mut points = segment.path.to_vec();
+    if segment.closed {
+        points.push(segment.first());
+    }
+    let edges: Vec<_> = points
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|e| e.length() > 0.0)
+        .collect();
+    if edges.is_empty() {
+        return 0.0;
+    }
+
+    let top = top_speed(kinematics.draw_speed);
+    let accel = accel(kinematics);
+
+    // The fastest the pen may go through each vertex, ignoring
+    // acceleration.  The ends of the stroke are taken at rest.
+    let mut speeds = vec![0.0; edges.len() + 1];
+    for i in 1..edges.len() {
+        let (a, b) = (edges[i - 1], edges[i]);
+        let cos = a.dot(b) / (a.length() * b.length());
+        speeds[i] = top * cos.max(0.0);
+    }
+
+    // Then limit them to what acceleration allows in both directions.
+    for i in 0..edges.len() {
+        let reachable = (speeds[i] * speeds[i] + 2.0 * accel * edges[i].length()).sqrt();
+        speeds[i + 1] = speeds[i + 1].min(reachable);
+    }
+    for i in (0..edges.len()).rev() {
+        let reachable = (speeds[i + 1] * speeds[i + 1] + 2.0 * accel * edges[i].length()).sqrt();
+        speeds[i] = speeds[i].min(reachable);
+    }
+
+    edges
+        .iter()
+        .enumerate()
+        .map(|(i, e)| edge_time(e.length(), speeds[i], speeds[i + 1], top, accel))
+        .sum()
+}
+
+/// An infinite acceleration stands in for an instant one, which keeps the
+/// sums below from dividing by zero.
+fn accel(kinematics: &PenKinematics) -> f32 {
+    if kinematics.accel > 0.0 {
+        kinematics.accel
+    } else {
+        std::f32::INFINITY
+    }
+}
+
+/// A speed that can't be reached is taken as zero, which makes every move
+/// at it take forever.
+fn top_speed(speed: f32) -> f32 {
+    if speed > 0.0 {
+        speed
+    } else {
+        0.0
+    }
+}
+
+/// The time taken to cover `length` starting at speed `v0` and ending at
+/// speed `v1`, never going faster than `top`.
+fn edge_time(length: f32, v0: f32, v1: f32, top: f32, accel: f32) -> f32 {
+    if length <= 0.0 {
+        return 0.0;
+    }
+
+    let peak = ((2.0 * accel * length + v0 * v0 + v1 * v1) / 2.0).sqrt();
+    if peak <= top {
+        (peak - v0) / accel + (peak - v1) / accel
+    } else {
+        let ramp_up = (top * top - v0 * v0) / (2.0 * accel);
+        let ramp_down = (top * top - v1 * v1) / (2.0 * accel);
+        (top - v0) / accel + (top - v1) / accel + (length - ramp_up - ramp_down) / top
+    }
+}
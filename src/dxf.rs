@@ -0,0 +1,174 @@
+use ::*;
+use std::fmt::Write;
+
+/// One entity out of the `ENTITIES` section, as group code and value pairs.
+struct Entity<'a> {
+    kind: &'a str,
+    groups: Vec<(i32, &'a str)>,
+}
+
+impl<'a> Entity<'a> {
+    fn layer(&self) -> &'a str {
+        self.groups.iter().find(|g| g.0 == 8).map_or("0", |g| g.1)
+    }
+
+    fn flags(&self) -> Result<i32, Error> {
+        match self.groups.iter().find(|g| g.0 == 70) {
+            Some(g) => g.1.parse().map_err(|_| Error::Malformed),
+            None => Ok(0),
+        }
+    }
+
+    /// Every `(x, y)` pair written with the group codes `x_code` and
+    /// `x_code + 10`, in order.
+    fn points<S>(&self, x_code: i32) -> Result<Vec<Point<S>>, Error> {
+        let mut out = vec![];
+        let mut x = None;
+        for &(code, value) in &self.groups {
+            if code == x_code {
+                x = Some(number(value)?);
+            } else if code == x_code + 10 {
+                let x = x.take().ok_or(Error::Malformed)?;
+                out.push(Point::new(x, number(value)?));
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn number(value: &str) -> Result<f32, Error> {
+    value.parse().map_err(|_| Error::Malformed)
+}
+
+fn entities(text: &str) -> Result<Vec<Entity>, Error> {
+    let mut lines = text.lines();
+    let mut groups = vec![];
+    while let Some(code) = lines.next() {
+        let value = lines.next().ok_or(Error::Malformed)?;
+        let code: i32 = code.trim().parse().map_err(|_| Error::Malformed)?;
+        groups.push((code, value.trim()));
+    }
+
+    let mut out = vec![];
+    let mut in_entities = false;
+    let mut i = 0;
+    while i < groups.len() {
+        let (code, value) = groups[i];
+        i += 1;
+        if code != 0 {
+            continue;
+        }
+        let start = i;
+        while i < groups.len() && groups[i].0 != 0 {
+            i += 1;
+        }
+        let entity = Entity {
+            kind: value,
+            groups: groups[start..i].to_vec(),
+        };
+        match value {
+            "SECTION" => in_entities = entity.groups.iter().any(|&g| g == (2, "ENTITIES")),
+            "ENDSEC" => in_entities = false,
+            _ if in_entities => out.push(entity),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// Reads the `LINE`, `LWPOLYLINE` and `POLYLINE` entities of a DXF file,
+/// grouped by layer in the order that the layers first show up.
+///
+/// Polylines flagged as closed become closed segments.  Arcs in polylines
+/// (bulges) are read as straight edges.  A relative `epsilon` is resolved
+/// against the bounds of all of the points.
+pub fn from_dxf<S, E: Into<Epsilon>>(text: &str, epsilon: E) -> Result<Vec<(String, Vec<PathSegment<S>>)>, Error> {
+    let mut lines: Vec<(&str, Vec<Point<S>>, bool)> = vec![];
+    let mut polyline: Option<(&str, Vec<Point<S>>, bool)> = None;
+
+    for entity in entities(text)? {
+        match entity.kind {
+            "LINE" => {
+                let mut points = entity.points(10)?;
+                points.extend(entity.points(11)?);
+                lines.push((entity.layer(), points, false));
+            }
+            "LWPOLYLINE" => {
+                let closed = entity.flags()? & 1 != 0;
+                lines.push((entity.layer(), entity.points(10)?, closed));
+            }
+            "POLYLINE" => {
+                let closed = entity.flags()? & 1 != 0;
+                polyline = Some((entity.layer(), vec![], closed));
+            }
+            "VERTEX" => match polyline {
+                Some((_, ref mut points, _)) => points.extend(entity.points(10)?),
+                None => return Err(Error::Malformed),
+            },
+            "SEQEND" => lines.extend(polyline.take()),
+            _ => {}
+        }
+    }
+
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| util::compute_bounding_box(lines.iter().flat_map(|l| l.1.iter().cloned())));
+
+    let mut out: Vec<(String, Vec<PathSegment<S>>)> = vec![];
+    for (layer, points, closed) in lines {
+        let mut segment = PathSegment::try_new(points, epsilon)?;
+        segment.closed |= closed;
+        match out.iter().position(|l| l.0 == layer) {
+            Some(i) => out[i].1.push(segment),
+            None => out.push((layer.to_string(), vec![segment])),
+        }
+    }
+    Ok(out)
+}
+
+/// Writes the segments out as an AutoCAD R12 DXF file, with one `POLYLINE`
+/// per segment on the layer that it is listed under.
+///
+/// R12 is the oldest version that current programs still read, and the
+/// simplest: besides the entities, the file only needs its version in the
+/// header and a table declaring each layer and the line type they use.
+pub fn to_dxf<S, L: AsRef<str>>(layers: &[(L, Vec<PathSegment<S>>)]) -> String {
+    let mut names: Vec<&str> = vec!["0"];
+    for &(ref layer, _) in layers {
+        if !names.contains(&layer.as_ref()) {
+            names.push(layer.as_ref());
+        }
+    }
+
+    let mut dxf = String::new();
+    writeln!(dxf, "0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1009\n0\nENDSEC").unwrap();
+
+    writeln!(dxf, "0\nSECTION\n2\nTABLES").unwrap();
+    writeln!(dxf, "0\nTABLE\n2\nLTYPE\n70\n1").unwrap();
+    writeln!(dxf, "0\nLTYPE\n2\nCONTINUOUS\n70\n0\n3\nSolid line\n72\n65\n73\n0\n40\n0.0").unwrap();
+    writeln!(dxf, "0\nENDTAB").unwrap();
+    writeln!(dxf, "0\nTABLE\n2\nLAYER\n70\n{}", names.len()).unwrap();
+    for name in &names {
+        writeln!(dxf, "0\nLAYER\n2\n{}\n70\n0\n62\n7\n6\nCONTINUOUS", name).unwrap();
+    }
+    writeln!(dxf, "0\nENDTAB\n0\nENDSEC").unwrap();
+
+    writeln!(dxf, "0\nSECTION\n2\nENTITIES").unwrap();
+    for &(ref layer, ref segments) in layers {
+        let layer = layer.as_ref();
+        for segment in segments {
+            writeln!(
+                dxf,
+                "0\nPOLYLINE\n8\n{}\n66\n1\n10\n0.0\n20\n0.0\n30\n0.0\n70\n{}",
+                layer,
+                if segment.closed { 1 } else { 0 }
+            ).unwrap();
+            for p in &segment.path {
+                writeln!(dxf, "0\nVERTEX\n8\n{}\n10\n{}\n20\n{}\n30\n0.0", layer, p.x, p.y).unwrap();
+            }
+            writeln!(dxf, "0\nSEQEND\n8\n{}", layer).unwrap();
+        }
+    }
+    writeln!(dxf, "0\nENDSEC\n0\nEOF").unwrap();
+    dxf
+}
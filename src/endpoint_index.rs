@@ -0,0 +1,108 @@
+use ::*;
+use util::{centered_with_radius, compute_bounding_box};
+use sanitize::is_finite;
+use std::cmp::Ordering;
+
+/// Which end of a segment an `Endpoint` is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum End {
+    /// The first point of the segment.
+    Start,
+    /// The last point of the segment.
+    End,
+}
+
+/// A single hit from an `EndpointIndex` query.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Endpoint<S> {
+    /// The index of the segment in the slice that the index was built from.
+    pub segment: usize,
+    /// Which end of the segment this is.
+    pub end: End,
+    /// Where the endpoint is.
+    pub point: Point<S>,
+}
+
+/// A read-only spatial index over the endpoints of a set of segments.
+pub struct EndpointIndex<S> {
+    tree: QuadTree<(usize, End), S>,
+    bounds: euclid::TypedRect<f32, S>,
+}
+
+impl<S: 'static> EndpointIndex<S> {
+    /// Indexes the first and last point of every segment.  Endpoints with a
+    /// NaN or infinite coordinate are left out.
+    pub fn new(segments: &[PathSegment<S>]) -> EndpointIndex<S> {
+        let bounds = compute_bounding_box(segments.iter().flat_map(|s| vec![s.first(), s.last()]));
+        let bounds = bounds.inflate(
+            1.0f32.max(bounds.size.width / 10.0),
+            1.0f32.max(bounds.size.height / 10.0),
+        );
+
+        let mut tree = QuadTree::default(bounds);
+        for (i, segment) in segments.iter().enumerate() {
+            for &(end, point) in &[(End::Start, segment.first()), (End::End, segment.last())] {
+                if is_finite(point) {
+                    tree.insert_with_box((i, end), point.aabb());
+                }
+            }
+        }
+
+        EndpointIndex {
+            tree: tree,
+            bounds: bounds,
+        }
+    }
+
+    /// The number of indexed endpoints.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// True if no endpoints are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Every endpoint that is at most `radius` away from `point`, closest
+    /// first.  There are none for a point that isn't finite.
+    pub fn endpoints_within(&self, point: Point<S>, radius: f32) -> Vec<Endpoint<S>> {
+        if !is_finite(point) {
+            return vec![];
+        }
+        let mut hits: Vec<(f32, Endpoint<S>)> = self.tree
+            .query(centered_with_radius(point, radius))
+            .into_iter()
+            .map(|(&(segment, end), rect, _)| {
+                let hit = Endpoint {
+                    segment: segment,
+                    end: end,
+                    point: rect.origin,
+                };
+                ((rect.origin - point).square_length(), hit)
+            })
+            .filter(|&(d2, _)| d2 <= radius * radius)
+            .collect();
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+
+    /// The `k` endpoints closest to `point`, closest first.  There are none
+    /// for a point that isn't finite.
+    pub fn nearest_endpoint(&self, point: Point<S>, k: usize) -> Vec<Endpoint<S>> {
+        let k = k.min(self.len());
+        if k == 0 || !is_finite(point) {
+            return vec![];
+        }
+
+        let mut radius = self.bounds.size.width.max(self.bounds.size.height) / (self.len() as f32).sqrt();
+        loop {
+            let mut hits = self.endpoints_within(point, radius);
+            if hits.len() >= k {
+                hits.truncate(k);
+                return hits;
+            }
+            radius *= 2.0;
+        }
+    }
+}
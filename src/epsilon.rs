@@ -0,0 +1,67 @@
+use ::*;
+use util::compute_bounding_box;
+
+/// How close two points need to be before they are considered the same.
+///
+/// Every function that takes an epsilon accepts anything that converts into
+/// an `Epsilon`; a plain `f32` is an absolute distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Epsilon {
+    /// A distance in the same units as the points.
+    Absolute(f32),
+    /// A fraction of the length of the diagonal of the bounding box of the
+    /// input.
+    RelativeToBounds(f32),
+}
+
+impl Epsilon {
+    /// The absolute distance that this epsilon stands for when the input is
+    /// contained in `bounds`.
+    pub fn resolve<S>(&self, bounds: &euclid::TypedRect<f32, S>) -> f32 {
+        self.resolve_with(|| *bounds)
+    }
+
+    /// Like `resolve`, but only computes the bounds if they are needed.
+    pub(crate) fn resolve_with<S, F>(&self, bounds: F) -> f32
+    where
+        F: FnOnce() -> euclid::TypedRect<f32, S>,
+    {
+        match *self {
+            Epsilon::Absolute(e) => e,
+            Epsilon::RelativeToBounds(fraction) => {
+                let size = bounds().size;
+                let diagonal = (size.width * size.width + size.height * size.height).sqrt();
+                if diagonal.is_finite() {
+                    fraction * diagonal
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Resolves this epsilon against the bounds of a set of segments.
+    pub(crate) fn resolve_segments<S>(&self, segments: &[PathSegment<S>]) -> f32 {
+        self.resolve_with(|| compute_bounding_box(segments.iter().flat_map(|s| s.path.iter().cloned())))
+    }
+}
+
+impl Default for Epsilon {
+    fn default() -> Epsilon {
+        Epsilon::Absolute(DEFAULT_EPSILON)
+    }
+}
+
+impl From<f32> for Epsilon {
+    fn from(e: f32) -> Epsilon {
+        Epsilon::Absolute(e)
+    }
+}
+
+/// Lets plain float literals (which default to `f64`) be passed as an
+/// epsilon.
+impl From<f64> for Epsilon {
+    fn from(e: f64) -> Epsilon {
+        Epsilon::Absolute(e as f32)
+    }
+}
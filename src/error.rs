@@ -0,0 +1,28 @@
+use std::error;
+use std::fmt;
+
+/// Errors produced when building segments from untrusted input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A segment needs at least two points.
+    TooFewPoints,
+    /// The operation needs a closed segment.
+    NotClosed,
+    /// A point has a NaN or infinite coordinate.
+    NonFinite,
+    /// The input isn't well-formed for its file format.
+    Malformed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooFewPoints => write!(f, "a segment needs at least two points"),
+            Error::NotClosed => write!(f, "the segment is not closed"),
+            Error::NonFinite => write!(f, "a point has a NaN or infinite coordinate"),
+            Error::Malformed => write!(f, "the input is malformed"),
+        }
+    }
+}
+
+impl error::Error for Error {}
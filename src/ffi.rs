@@ -0,0 +1,220 @@
+//! A flat C ABI over the standard pipeline.
+//!
+//! Points cross the boundary as interleaved `x, y` pairs of `f32`s.  Every
+//! object handed out must be released with its matching `_free` function.
+//! The declarations for C are in `include/line_stitch.h`.
+//!
+//! The crate only builds an `rlib` by default.  Build the C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`, or with
+//! `--crate-type staticlib` for a static one.
+//!
+//! No panic ever unwinds into the caller: a function that panics returns
+//! null, zero or false instead, as do functions handed a null pointer or an
+//! index that is out of range.
+
+use ::*;
+use euclid::UnknownUnit;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// Runs `f`, returning `fallback` if it panics.
+fn guard<T, F: FnOnce() -> T>(fallback: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// The settings of `line_stitch_run`.  See `PipelineConfig`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LineStitchConfig {
+    /// Points closer than this are considered to be the same point.
+    pub epsilon: f32,
+    /// If true, `epsilon` is a fraction of the diagonal of the bounding box
+    /// of the input rather than a distance.
+    pub epsilon_is_relative: bool,
+    /// Only join the end of a segment to the start of another.
+    pub only_starts: bool,
+    /// Join segments even when there is more than one candidate.
+    pub allow_ambiguous: bool,
+    /// Run `prune` after `connect_obvious`.
+    pub prune: bool,
+    /// Run `graph_stitch` after `connect_obvious`.
+    pub graph_stitch: bool,
+}
+
+impl LineStitchConfig {
+    fn to_pipeline(&self) -> PipelineConfig {
+        PipelineConfig {
+            epsilon: if self.epsilon_is_relative {
+                Epsilon::RelativeToBounds(self.epsilon)
+            } else {
+                Epsilon::Absolute(self.epsilon)
+            },
+            only_starts: self.only_starts,
+            allow_ambiguous: self.allow_ambiguous,
+            prune: self.prune,
+            graph_stitch: self.graph_stitch,
+            ..PipelineConfig::default()
+        }
+    }
+}
+
+/// A list of input polylines.
+pub struct LineStitchSegments {
+    segments: Vec<Points<UnknownUnit>>,
+}
+
+/// The polylines produced by `line_stitch_run`.
+pub struct LineStitchPaths {
+    /// Interleaved coordinates of each path, and whether it is closed.
+    paths: Vec<(Vec<f32>, bool)>,
+}
+
+/// Creates an empty list of polylines.
+#[no_mangle]
+pub extern "C" fn line_stitch_segments_new() -> *mut LineStitchSegments {
+    guard(ptr::null_mut(), || Box::into_raw(Box::new(LineStitchSegments { segments: vec![] })))
+}
+
+/// Adds a polyline of `point_count` points to `segments`.  Polylines with
+/// fewer than two points are ignored, as are null pointers.
+///
+/// # Safety
+///
+/// `segments` must come from `line_stitch_segments_new`, and `coords` must
+/// point to `2 * point_count` readable `f32`s.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_segments_push(
+    segments: *mut LineStitchSegments,
+    coords: *const f32,
+    point_count: usize,
+) {
+    if segments.is_null() || coords.is_null() || point_count < 2 {
+        return;
+    }
+    guard((), || {
+        let coords = slice::from_raw_parts(coords, point_count * 2);
+        let points = coords
+            .chunks(2)
+            .map(|c| Point::new(c[0], c[1]))
+            .collect();
+        (*segments).segments.push(points);
+    })
+}
+
+/// Frees a list of polylines.
+///
+/// # Safety
+///
+/// `segments` must come from `line_stitch_segments_new` and not have been
+/// freed yet.  Null is allowed.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_segments_free(segments: *mut LineStitchSegments) {
+    if !segments.is_null() {
+        guard((), || drop(Box::from_raw(segments)));
+    }
+}
+
+/// Runs the pipeline described by `config` over `segments`, which are left
+/// untouched.  Returns null if either pointer is null or the pipeline
+/// fails.
+///
+/// # Safety
+///
+/// `segments` must come from `line_stitch_segments_new` and `config` must
+/// point to a valid `LineStitchConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_run(
+    segments: *const LineStitchSegments,
+    config: *const LineStitchConfig,
+) -> *mut LineStitchPaths {
+    if segments.is_null() || config.is_null() {
+        return ptr::null_mut();
+    }
+    guard(ptr::null_mut(), || {
+        let config = (*config).to_pipeline();
+        let out: Vec<PathSegment<UnknownUnit>> =
+            run_pipeline((*segments).segments.iter().cloned(), &config);
+        let paths = out
+            .into_iter()
+            .map(|segment| {
+                let closed = segment.closed;
+                let coords = flat_from_points(&segment.path);
+                (coords, closed)
+            })
+            .collect();
+        Box::into_raw(Box::new(LineStitchPaths { paths: paths }))
+    })
+}
+
+/// The number of paths in `paths`, or zero if it is null.
+///
+/// # Safety
+///
+/// `paths` must come from `line_stitch_run`.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_paths_len(paths: *const LineStitchPaths) -> usize {
+    if paths.is_null() {
+        return 0;
+    }
+    guard(0, || (*paths).paths.len())
+}
+
+/// The points of the path at `index`, writing how many there are to
+/// `point_count`.  Closed paths don't repeat their first point at the end.
+/// An `index` that is out of range gives null and a count of zero.
+///
+/// The returned pointer stays valid until `paths` is freed.
+///
+/// # Safety
+///
+/// `paths` must come from `line_stitch_run`, and `point_count` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_paths_points(
+    paths: *const LineStitchPaths,
+    index: usize,
+    point_count: *mut usize,
+) -> *const f32 {
+    if point_count.is_null() {
+        return ptr::null();
+    }
+    *point_count = 0;
+    if paths.is_null() {
+        return ptr::null();
+    }
+    guard(ptr::null(), || match (*paths).paths.get(index) {
+        Some(&(ref coords, _)) => {
+            *point_count = coords.len() / 2;
+            coords.as_ptr()
+        }
+        None => ptr::null(),
+    })
+}
+
+/// True if the path at `index` is closed.  False if `index` is out of
+/// range.
+///
+/// # Safety
+///
+/// `paths` must come from `line_stitch_run`.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_paths_closed(paths: *const LineStitchPaths, index: usize) -> bool {
+    if paths.is_null() {
+        return false;
+    }
+    guard(false, || (*paths).paths.get(index).map_or(false, |p| p.1))
+}
+
+/// Frees the output of `line_stitch_run`.
+///
+/// # Safety
+///
+/// `paths` must come from `line_stitch_run` and not have been freed yet.
+/// Null is allowed.
+#[no_mangle]
+pub unsafe extern "C" fn line_stitch_paths_free(paths: *mut LineStitchPaths) {
+    if !paths.is_null() {
+        guard((), || drop(Box::from_raw(paths)));
+    }
+}
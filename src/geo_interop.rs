@@ -0,0 +1,88 @@
+use ::*;
+use util::compute_bounding_box;
+use geo_types::{Coordinate, LineString, MultiLineString, Polygon};
+use std::convert::TryFrom;
+
+fn to_coordinates<S>(segment: &PathSegment<S>) -> Vec<Coordinate<f32>> {
+    let mut coords: Vec<_> = segment
+        .path
+        .iter()
+        .map(|p| Coordinate { x: p.x, y: p.y })
+        .collect();
+    if segment.closed {
+        let first = coords[0];
+        coords.push(first);
+    }
+    coords
+}
+
+fn bounds<'a, S, I>(coords: I) -> euclid::TypedRect<f32, S>
+where
+    I: IntoIterator<Item = &'a Coordinate<f32>>,
+{
+    compute_bounding_box(coords.into_iter().map(|c| Point::new(c.x, c.y)))
+}
+
+/// Converts a line string into a segment.  Line strings that end where they
+/// started become closed segments.
+pub fn from_line_string<S, E: Into<Epsilon>>(line: &LineString<f32>, epsilon: E) -> Result<PathSegment<S>, Error> {
+    PathSegment::try_new(
+        line.0.iter().map(|c| Point::new(c.x, c.y)).collect::<Vec<_>>(),
+        epsilon,
+    )
+}
+
+/// Converts every ring of a polygon (exterior first) into a closed segment.
+pub fn from_polygon<S, E: Into<Epsilon>>(polygon: &Polygon<f32>, epsilon: E) -> Result<Vec<PathSegment<S>>, Error> {
+    let epsilon = epsilon.into().resolve_with(|| bounds(&polygon.exterior().0));
+    Some(polygon.exterior())
+        .into_iter()
+        .chain(polygon.interiors())
+        .map(|ring| -> Result<PathSegment<S>, Error> {
+            let mut segment = from_line_string(ring, epsilon)?;
+            segment.closed = true;
+            Ok(segment)
+        })
+        .collect()
+}
+
+/// Converts a whole batch of segments into a multi line string.
+pub fn to_multi_line_string<S>(segments: &[PathSegment<S>]) -> MultiLineString<f32> {
+    MultiLineString(segments.iter().map(|s| LineString(to_coordinates(s))).collect())
+}
+
+/// Converts a multi line string into segments, skipping lines that have
+/// fewer than two points.
+pub fn from_multi_line_string<S, E: Into<Epsilon>>(lines: &MultiLineString<f32>, epsilon: E) -> Vec<PathSegment<S>> {
+    let epsilon = epsilon.into().resolve_with(|| bounds(lines.0.iter().flat_map(|l| &l.0)));
+    lines
+        .0
+        .iter()
+        .filter_map(|line| from_line_string(line, epsilon).ok())
+        .collect()
+}
+
+impl<S> From<PathSegment<S>> for LineString<f32> {
+    fn from(segment: PathSegment<S>) -> LineString<f32> {
+        LineString(to_coordinates(&segment))
+    }
+}
+
+impl<S> TryFrom<LineString<f32>> for PathSegment<S> {
+    type Error = Error;
+
+    fn try_from(line: LineString<f32>) -> Result<PathSegment<S>, Error> {
+        from_line_string(&line, DEFAULT_EPSILON)
+    }
+}
+
+impl<S> TryFrom<PathSegment<S>> for Polygon<f32> {
+    type Error = Error;
+
+    fn try_from(segment: PathSegment<S>) -> Result<Polygon<f32>, Error> {
+        if !segment.closed {
+            return Err(Error::NotClosed);
+        }
+        Ok(Polygon::new(LineString(to_coordinates(&segment)), vec![]))
+    }
+}
@@ -0,0 +1,137 @@
+use ::*;
+use serde_json::{self, Value};
+
+/// Reads every `LineString`, `MultiLineString`, `Polygon` and
+/// `MultiPolygon` in a GeoJSON document into segments.  Other geometries are
+/// skipped.
+///
+/// Each position is handed to `project` as `(x, y)`, which is longitude and
+/// latitude for most data, to get a point in planar units.  Polygon rings
+/// become closed segments, and line strings that end where they started
+/// become closed segments too.  A relative `epsilon` is resolved against
+/// the bounds of the projected points.
+pub fn from_geojson<S, E, F>(json: &str, epsilon: E, mut project: F) -> Result<Vec<PathSegment<S>>, Error>
+where
+    E: Into<Epsilon>,
+    F: FnMut(f64, f64) -> Point<S>,
+{
+    let value: Value = serde_json::from_str(json).map_err(|_| Error::Malformed)?;
+    let mut lines = vec![];
+    collect(&value, &mut project, &mut lines)?;
+
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| util::compute_bounding_box(lines.iter().flat_map(|l| l.0.iter().cloned())));
+    lines
+        .into_iter()
+        .map(|(points, ring)| {
+            let mut segment = PathSegment::try_new(points, epsilon)?;
+            segment.closed |= ring;
+            Ok(segment)
+        })
+        .collect()
+}
+
+/// Collects the lines of `value`, along with whether each is a polygon ring.
+fn collect<S, F>(value: &Value, project: &mut F, out: &mut Vec<(Vec<Point<S>>, bool)>) -> Result<(), Error>
+where
+    F: FnMut(f64, f64) -> Point<S>,
+{
+    let coordinates = || value.get("coordinates").ok_or(Error::Malformed);
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            for feature in value.get("features").and_then(Value::as_array).ok_or(Error::Malformed)? {
+                collect(feature, project, out)?;
+            }
+        }
+        Some("Feature") => match value.get("geometry") {
+            Some(&Value::Null) | None => {}
+            Some(geometry) => collect(geometry, project, out)?,
+        },
+        Some("GeometryCollection") => {
+            for geometry in value.get("geometries").and_then(Value::as_array).ok_or(Error::Malformed)? {
+                collect(geometry, project, out)?;
+            }
+        }
+        Some("LineString") => out.push((line(coordinates()?, project)?, false)),
+        Some("MultiLineString") => {
+            for l in array(coordinates()?)? {
+                out.push((line(l, project)?, false));
+            }
+        }
+        Some("Polygon") => {
+            for ring in array(coordinates()?)? {
+                out.push((line(ring, project)?, true));
+            }
+        }
+        Some("MultiPolygon") => {
+            for polygon in array(coordinates()?)? {
+                for ring in array(polygon)? {
+                    out.push((line(ring, project)?, true));
+                }
+            }
+        }
+        Some(_) => {}
+        None => return Err(Error::Malformed),
+    }
+    Ok(())
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, Error> {
+    value.as_array().ok_or(Error::Malformed)
+}
+
+fn line<S, F>(value: &Value, project: &mut F) -> Result<Vec<Point<S>>, Error>
+where
+    F: FnMut(f64, f64) -> Point<S>,
+{
+    array(value)?
+        .iter()
+        .map(|position| {
+            let position = array(position)?;
+            match (position.get(0).and_then(Value::as_f64), position.get(1).and_then(Value::as_f64)) {
+                (Some(x), Some(y)) => Ok(project(x, y)),
+                _ => Err(Error::Malformed),
+            }
+        })
+        .collect()
+}
+
+/// Writes the segments out as a GeoJSON `FeatureCollection` with one
+/// feature per segment.
+///
+/// Each point is handed to `unproject` to get its GeoJSON position.  Open
+/// segments become `LineString`s.  Closed segments become `Polygon`s if
+/// `closed_as_polygons` is set, and `LineString`s that end where they start
+/// otherwise.
+pub fn to_geojson<S, F>(segments: &[PathSegment<S>], closed_as_polygons: bool, mut unproject: F) -> String
+where
+    F: FnMut(Point<S>) -> (f64, f64),
+{
+    let features: Vec<Value> = segments
+        .iter()
+        .map(|segment| {
+            let mut positions: Vec<Value> = segment
+                .path
+                .iter()
+                .map(|&p| {
+                    let (x, y) = unproject(p);
+                    json!([x, y])
+                })
+                .collect();
+            if segment.closed {
+                let first = positions[0].clone();
+                positions.push(first);
+            }
+
+            let geometry = if segment.closed && closed_as_polygons {
+                json!({ "type": "Polygon", "coordinates": [positions] })
+            } else {
+                json!({ "type": "LineString", "coordinates": positions })
+            };
+            json!({ "type": "Feature", "properties": {}, "geometry": geometry })
+        })
+        .collect();
+
+    json!({ "type": "FeatureCollection", "features": features }).to_string()
+}
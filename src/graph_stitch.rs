@@ -1,164 +1,265 @@
 use super::*;
-use aabb_quadtree::{ItemId, QuadTree};
-use euclid::{TypedRect, point2, vec2};
+use euclid::point2;
 use euclid::approxeq::ApproxEq;
-use util::{centered_with_radius, compute_bounding_box};
-use std::collections::{HashMap, HashSet};
-
-// TODO: *LOTS* of optimization opporitunities here
-
-type Rect<S> = TypedRect<f32, S>;
-
-#[derive(Clone)]
-struct Graph<S> {
-    tree: QuadTree<PathSegment<S>, S>,
-}
-
-type VisitedSet = HashMap<ItemId, f32>;
-type Path = Vec<(ItemId, f32)>;
-
-fn is_close<S>(p1: Point<S>, p2: Point<S>) -> bool {
-    p1.approx_eq_eps(&p2, &point2(0.001, 0.001))
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
+use util::{centered_with_radius, UnionFind};
+use std::ops::ControlFlow;
+use std::vec;
+use progress::*;
+use sanitize::sanitize_segments;
+
+/// Segments are referred to by their id in the `DualQuadTree`.
+type Id = DqtId;
+type Path = Vec<(Id, f32)>;
+type Cost<'a, S> = FnMut(&PathSegment<S>, Point<S>, &PathSegment<S>, Point<S>) -> f32 + 'a;
+
+/// How many times one search may walk through a segment, at least.  A
+/// segment is walked again when it is reached by a longer path, which on
+/// densely branched input could otherwise happen once for every path through
+/// the graph.  Segments that more branches lead into may be walked once for
+/// each of them, so that the longest loop through a junction is still found.
+const MAX_VISITS: u32 = 4;
+
+/// The open segments of a `DualQuadTree`, looked up through its index of
+/// start points.  Closed segments are never part of the graph.
+struct Graph<'a, S: 'a> {
+    dual_qt: &'a mut DualQuadTree<S>,
+    remaining: usize,
 }
 
-impl<S> Graph<S> {
-    fn new(v: Vec<PathSegment<S>>) -> Graph<S> {
-        let v: Vec<_> = v.into_iter()
-            .map(|v| (compute_bounding_box(vec![v.first(), v.last()]), v))
-            .collect();
-
-        let mut rect = Rect::new(point2(0.0, 0.0), vec2(0.0, 0.0).to_size());
-        for &(ref r, _) in &v {
-            rect = rect.union(r);
+impl<'a, S: 'static> Graph<'a, S> {
+    fn new(dual_qt: &'a mut DualQuadTree<S>) -> Graph<'a, S> {
+        let remaining = dual_qt.iter().filter(|&(_, s)| !s.closed).count();
+        Graph {
+            dual_qt: dual_qt,
+            remaining: remaining,
         }
-        let rect = rect.inflate(
-            2.0f32.max(rect.size.width / 10.0),
-            2.0f32.max(rect.size.height / 10.0),
-        );
-
-        let mut tree = QuadTree::new(rect, true, 4, 16, 4);
+    }
 
-        for (bb, v) in v {
-            tree.insert_with_box(v, bb);
-        }
+    fn epsilon(&self) -> f32 {
+        self.dual_qt.epsilon()
+    }
 
-        Graph { tree: tree }
+    fn get(&self, id: Id) -> &PathSegment<S> {
+        self.dual_qt.get(id).unwrap()
     }
 
-    fn connected_to(&self, id: ItemId) -> Vec<ItemId> {
-        let segment = self.tree.get(id).unwrap();
+    fn contains(&self, id: Id) -> bool {
+        self.dual_qt.get(id).is_some()
+    }
 
-        let last_point = segment.last();
-        let query = centered_with_radius(last_point, 1.0 / 4.0);
-        return self.tree
-            .query(query)
+    /// The open segments on the same layer that start where `id` ends, in
+    /// id order.
+    fn connected_to(&self, id: Id) -> Vec<Id> {
+        let (last_point, layer) = (self.get(id).last(), self.get(id).layer);
+        let epsilon = self.epsilon();
+        let eps = point2(epsilon, epsilon);
+
+        let mut out: Vec<Id> = self
+            .dual_qt
+            .starts
+            .query(centered_with_radius(last_point, epsilon))
             .into_iter()
-            .filter_map(|(ref v, _, id2)| {
-                if id == id2 {
-                    return None;
-                } else if is_close(segment.last(), v.first()) {
-                    Some(id2)
-                } else {
-                    None
-                }
+            .map(|(&other, _)| other)
+            .filter(|&other| {
+                let v = self.get(other);
+                other != id && !v.closed && v.layer == layer && last_point.approx_eq_eps(&v.first(), &eps)
             })
             .collect();
+        out.sort();
+        out
+    }
+
+    /// How many open segments on the same layer end where `id` starts.
+    fn leading_to(&self, id: Id) -> usize {
+        let (first_point, layer) = (self.get(id).first(), self.get(id).layer);
+        let epsilon = self.epsilon();
+        let eps = point2(epsilon, epsilon);
+
+        self.dual_qt
+            .ends
+            .query(centered_with_radius(first_point, epsilon))
+            .into_iter()
+            .filter(|&(&other, _)| {
+                let v = self.get(other);
+                other != id && !v.closed && v.layer == layer && first_point.approx_eq_eps(&v.last(), &eps)
+            })
+            .count()
+    }
+
+    fn cost_of(&self, from: Id, to: Id, cost: &mut Cost<S>) -> f32 {
+        let from = self.get(from);
+        let to = self.get(to);
+        cost(from, from.last(), to, to.first())
+    }
+
+    fn length_of(&self, id: Id) -> f32 {
+        self.get(id).length()
     }
 
-    fn length_of(&self, id: ItemId) -> f32 {
-        let segment = self.tree.get(id).unwrap();
-        segment.length()
+    fn remove(&mut self, id: Id) -> PathSegment<S> {
+        self.remaining -= 1;
+        self.dual_qt.remove(id).unwrap()
     }
 
-    fn remove(&mut self, id: ItemId) -> PathSegment<S> {
-        self.tree.remove(id).unwrap().0
+    fn try_remove(&mut self, id: Id) {
+        if self.contains(id) {
+            self.remove(id);
+        }
     }
-    fn try_remove(&mut self, id: ItemId) {
-        self.tree.remove(id);
+
+    /// Groups the open segments into connected components, each listed in
+    /// ascending id order.
+    fn components(&self) -> Vec<Vec<Id>> {
+        let mut ids: Vec<Id> = self.dual_qt.iter().filter(|&(_, s)| !s.closed).map(|(id, _)| id).collect();
+        ids.sort();
+        let index_of: HashMap<Id, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let mut sets = UnionFind::new(ids.len());
+        for (i, &id) in ids.iter().enumerate() {
+            for other in self.connected_to(id) {
+                sets.union(i, index_of[&other]);
+            }
+        }
+
+        let mut index_of_root: HashMap<usize, usize> = HashMap::default();
+        let mut components: Vec<Vec<Id>> = vec![];
+        for (i, &id) in ids.iter().enumerate() {
+            let root = sets.find(i);
+            let index = *index_of_root.entry(root).or_insert_with(|| {
+                components.push(vec![]);
+                components.len() - 1
+            });
+            components[index].push(id);
+        }
+        components
     }
 }
 
-fn recur<S>(
-    at: ItemId,
-    current_length: f32,
-    graph: &Graph<S>,
-    visited: &mut VisitedSet,
-    path: &mut Path,
-    best_possible: &mut f32,
-    possible: &mut Vec<Path>,
-    dead_ends: &mut Vec<Path>,
-) {
-    let length = graph.length_of(at);
+/// The state of one depth first search for loops.
+struct Search {
+    /// The longest length at which each segment has been reached, and how
+    /// many times it has been walked.
+    visited: HashMap<Id, (f32, u32)>,
+    path: Path,
+    /// Where each segment on `path` is.
+    on_path: HashMap<Id, usize>,
+    possible: Vec<Path>,
+    /// Every segment that lies on the way to a dead end.
+    trash: HashSet<Id>,
+}
+
+impl Search {
+    /// Arrives at `at`, having come `current_length` so far.  Returns the
+    /// neighbors that still need to be explored from `at`, in which case it
+    /// has been pushed onto the path.
+    fn enter<S: 'static>(
+        &mut self,
+        at: Id,
+        current_length: f32,
+        graph: &Graph<S>,
+        cost: &mut Cost<S>,
+    ) -> Option<vec::IntoIter<Id>> {
+        let length = graph.length_of(at);
+
+        let mut visits = 0;
+        if let Some(&(prior_length, times)) = self.visited.get(&at) {
+            if prior_length >= current_length {
+                return None;
+            }
+
+            if let Some(&pos) = self.on_path.get(&at) {
+                self.possible.push(self.path[pos..].to_vec());
+                return None;
+            }
 
-    if let Some(prior_length) = visited.get(&at) {
-        if *prior_length >= current_length {
-            return;
+            if times >= MAX_VISITS.max(graph.leading_to(at) as u32) {
+                return None;
+            }
+            visits = times;
         }
 
-        if let Some(pos) = path.iter().position(|&(id, _)| id == at) {
-            let mut pos_path = path.clone();
-            pos_path.drain(0..pos);
-            possible.push(pos_path);
-            *best_possible = (current_length + length).max(*best_possible);
-            return;
+        let mut neighbors: Vec<_> = graph
+            .connected_to(at)
+            .into_iter()
+            .map(|a| (a, graph.cost_of(at, a, cost)))
+            .filter(|&(_, c)| c.is_finite())
+            .collect();
+
+        if neighbors.is_empty() {
+            // Everything on the path leads here.  Once one segment on the
+            // path is trash, everything before it already is too.
+            self.trash.insert(at);
+            for &(id, _) in self.path.iter().rev() {
+                if !self.trash.insert(id) {
+                    break;
+                }
+            }
+            return None;
         }
+
+        self.on_path.insert(at, self.path.len());
+        self.path.push((at, length));
+        self.visited.insert(at, (current_length, visits + 1));
+
+        // Cheapest first.  Reversing before the (stable) sort keeps ties in the
+        // order that they have always been explored in.
+        neighbors.reverse();
+        neighbors.sort_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap());
+        let neighbors: Vec<_> = neighbors.into_iter().map(|(n, _)| n).collect();
+        Some(neighbors.into_iter())
     }
 
-    let mut neighbors: Vec<_> = graph
-        .connected_to(at)
-        .into_iter()
-        .map(|a| (a, graph.length_of(a)))
-        .collect();
+    fn leave(&mut self) {
+        if let Some((id, _)) = self.path.pop() {
+            self.on_path.remove(&id);
+        }
+    }
+}
 
-    if neighbors.is_empty() {
-        let mut dead_path = path.clone();
-        dead_path.push((at, length));
-        dead_ends.push(dead_path);
-        return;
-    }
-
-    path.push((at, length));
-    visited.insert(at, current_length);
-
-    // TODO: is this actually faster?  Back up with data
-    neighbors.sort_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap());
-
-    for (neighbor, _) in neighbors.into_iter().rev() {
-        if neighbor != at {
-            recur(
-                neighbor,
-                current_length + length,
-                graph,
-                visited,
-                path,
-                best_possible,
-                possible,
-                dead_ends,
-            );
+/// Searches for loops starting at `first_id`.  The search keeps its own
+/// stack rather than recursing so that long chains of segments can't
+/// overflow the call stack.
+fn search<S: 'static>(first_id: Id, graph: &Graph<S>, cost: &mut Cost<S>) -> Search {
+    let mut search = Search {
+        visited: HashMap::default(),
+        path: vec![],
+        on_path: HashMap::default(),
+        possible: vec![],
+        trash: HashSet::default(),
+    };
+
+    // Each frame holds the neighbors left to explore and the length of the
+    // path once the segment that it belongs to has been walked.
+    let mut stack = vec![];
+    if let Some(neighbors) = search.enter(first_id, 0.0, graph, cost) {
+        stack.push((neighbors, graph.length_of(first_id)));
+    }
+
+    loop {
+        let next = match stack.last_mut() {
+            Some(&mut (ref mut neighbors, length)) => neighbors.next().map(|n| (n, length)),
+            None => break,
+        };
+        match next {
+            Some((neighbor, length)) => {
+                if let Some(neighbors) = search.enter(neighbor, length, graph, cost) {
+                    stack.push((neighbors, length + graph.length_of(neighbor)));
+                }
+            }
+            None => {
+                stack.pop();
+                search.leave();
+            }
         }
     }
 
-    path.pop();
-    // visited.remove(&at);
+    search
 }
 
-fn one_iter<S>(mut graph: Graph<S>) -> (Graph<S>, Vec<PathSegment<S>>) {
+fn one_iter<S: 'static>(first_id: Id, graph: &mut Graph<S>, cost: &mut Cost<S>) -> (Vec<PathSegment<S>>, usize) {
     use std::cmp::{Ordering, PartialOrd};
-    let mut best_possible = 0.0;
-    let mut possible = vec![];
-    let mut dead_ends = vec![];
-    let first_id = graph.tree.iter().next().unwrap().0.clone();
-
-    recur(
-        first_id,
-        0.0,
-        &graph,
-        &mut HashMap::new(),
-        &mut vec![],
-        &mut best_possible,
-        &mut possible,
-        &mut dead_ends,
-    );
+    let Search { possible, trash, .. } = search(first_id, graph, cost);
 
     let mut possible: Vec<_> = possible
         .into_iter()
@@ -176,13 +277,9 @@ fn one_iter<S>(mut graph: Graph<S>) -> (Graph<S>, Vec<PathSegment<S>>) {
     possible.reverse();
 
     let mut out = vec![];
-    let mut visited_loops = HashSet::new();
-    let mut trash_points = HashSet::new();
-    trash_points.extend(
-        dead_ends
-            .into_iter()
-            .flat_map(|v| v.into_iter().map(|(p, _)| p)),
-    );
+    let mut joins = 0;
+    let mut visited_loops = HashSet::default();
+    let mut trash_points = trash;
 
     for (l00p, _) in possible {
         let intersects = l00p.iter().any(|point| visited_loops.contains(point));
@@ -190,9 +287,16 @@ fn one_iter<S>(mut graph: Graph<S>) -> (Graph<S>, Vec<PathSegment<S>>) {
             trash_points.extend(l00p);
         } else {
             visited_loops.extend(l00p.iter().cloned());
+            joins += l00p.len() - 1;
+            trace_event!(
+                "loop through {:?} starting at {:?}",
+                l00p,
+                graph.get(l00p[0]).first()
+            );
             // TODO: this flattens things but the edge conditions might
             // be weird.
 
+            let layer = graph.get(l00p[0]).layer;
             let mut loop_out = vec![];
             let mut first_iteration = true;
             for mut segment in l00p.into_iter().map(|id| graph.remove(id)) {
@@ -206,37 +310,167 @@ fn one_iter<S>(mut graph: Graph<S>) -> (Graph<S>, Vec<PathSegment<S>>) {
                 }
             }
 
-            out.push(PathSegment::new(loop_out, 0.001));
+            out.push(PathSegment::new(loop_out, graph.epsilon()).with_layer(layer));
         }
     }
 
-    for pt in visited_loops.into_iter().chain(trash_points.into_iter()) {
+    for pt in trash_points {
         graph.try_remove(pt);
     }
 
-    (graph, out)
+    (out, joins)
 }
 
-fn try_solve<S>(mut graph: Graph<S>) -> Vec<PathSegment<S>> {
+/// Joins the open segments in `graph` into loops, which are returned.
+/// Segments that can't be part of a loop are taken out of the graph, and
+/// the ones that weren't looked at because of `on_progress` are left in it.
+fn try_solve<S: 'static>(
+    mut graph: Graph<S>,
+    cost: &mut Cost<S>,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+) -> (Vec<PathSegment<S>>, usize) {
     let mut out = vec![];
-    while !graph.tree.is_empty() {
-        let (ng, pts) = one_iter(graph);
-        graph = ng;
-        out.extend(pts);
+    let mut joins = 0;
+    let total = graph.remaining;
+    debug_event!("graph_stitch: start, {} open segments", total);
+
+    // Segments in different components can never end up in the same loop,
+    // so each search only has to look at its own component.
+    'components: for component in graph.components() {
+        let mut cursor = 0;
+        loop {
+            while cursor < component.len() && !graph.contains(component[cursor]) {
+                cursor += 1;
+            }
+            if cursor == component.len() {
+                break;
+            }
+
+            let before = graph.remaining;
+            let (pts, j) = one_iter(component[cursor], &mut graph, cost);
+            if graph.remaining == before {
+                // Nothing more can be made out of this segment.
+                graph.remove(component[cursor]);
+            }
+            out.extend(pts);
+            joins += j;
+
+            let progress = PassProgress {
+                pass: Pass::GraphStitch,
+                done: total - graph.remaining,
+                total: total,
+            };
+            if let ControlFlow::Break(()) = on_progress(progress) {
+                break 'components;
+            }
+        }
     }
 
-    out
+    debug_event!(
+        "graph_stitch: done, {} joins, {} segments left unjoined",
+        joins,
+        graph.remaining
+    );
+    (out, joins)
+}
+
+/// Indexes the open segments for `try_solve`.  Endpoints are looked up in
+/// a hash grid, which suits the evenly spread ends left by
+/// `connect_obvious`.
+fn open_segments<S: 'static>(segments: Vec<PathSegment<S>>, epsilon: f32) -> DualQuadTree<S> {
+    let ends: Vec<_> = segments.iter().flat_map(|s| vec![s.first(), s.last()]).collect();
+    let bounds = euclid::TypedRect::from_points(&ends[..]);
+    let bounds = bounds.inflate(epsilon.max(bounds.size.width / 10.0), epsilon.max(bounds.size.height / 10.0));
+    let mut dual_qt = DualQuadTree::new(bounds, epsilon, IndexBackend::Grid { cell_size: 4.0 });
+    for segment in segments {
+        dual_qt.insert(segment);
+    }
+    dual_qt
+}
+
+/// Takes the segments left in `dual_qt` out in the order they were added.
+fn drain_in_order<S: 'static>(dual_qt: &mut DualQuadTree<S>) -> Vec<PathSegment<S>> {
+    let mut ids: Vec<Id> = dual_qt.iter().map(|(id, _)| id).collect();
+    ids.sort();
+    ids.into_iter().filter_map(|id| dual_qt.remove(id)).collect()
 }
 
 ///
 /// TODO: document
-pub fn connect_unconnected<S>(joined: Vec<PathSegment<S>>) -> Vec<PathSegment<S>> {
+pub fn connect_unconnected<S: 'static>(joined: Vec<PathSegment<S>>) -> Vec<PathSegment<S>> {
+    connect_unconnected_with_epsilon(joined, Epsilon::default())
+}
+
+/// Like `graph_stitch`, but ends within `epsilon` of each other are joined
+/// rather than ends within the default epsilon.
+pub fn connect_unconnected_with_epsilon<S: 'static, E: Into<Epsilon>>(joined: Vec<PathSegment<S>>, epsilon: E) -> Vec<PathSegment<S>> {
+    let joined = sanitize_segments(joined);
+    let epsilon = epsilon.into().resolve_segments(&joined);
+    connect_unconnected_counted(joined, epsilon, &mut ignore_progress).0
+}
+
+/// Like `graph_stitch`, but `cost` decides which joins are tried first.
+///
+/// `cost(a, end, b, start)` is the cost of continuing from the point `end`
+/// of `a` onto `b` at its point `start`.  Cheaper joins are explored first,
+/// and joins with a cost that isn't finite are never made.  Ends are joined
+/// when they are within `epsilon` of each other.
+pub fn graph_stitch_with_cost<S: 'static, E, F>(joined: Vec<PathSegment<S>>, epsilon: E, mut cost: F) -> Vec<PathSegment<S>>
+where
+    E: Into<Epsilon>,
+    F: FnMut(&PathSegment<S>, Point<S>, &PathSegment<S>, Point<S>) -> f32,
+{
+    let joined = sanitize_segments(joined);
+    let epsilon = epsilon.into().resolve_segments(&joined);
     let (mut good, bad) = joined.into_iter().partition::<Vec<_>, _>(|a| a.closed);
+    let mut open = open_segments(bad, epsilon);
+    let (solved, _) = try_solve(Graph::new(&mut open), &mut cost, &mut ignore_progress);
+    good.extend(solved);
+    good.extend(drain_in_order(&mut open));
+    good
+}
 
-    let graph = Graph::new(bad);
-    let solved = try_solve(graph);
+/// Explores the longest neighbors first.
+fn longest_first<S>(_: &PathSegment<S>, _: Point<S>, b: &PathSegment<S>, _: Point<S>) -> f32 {
+    -b.length()
+}
+
+/// Like `connect_unconnected`, but also returns the number of joins performed.
+///
+/// If `on_progress` asks to stop, the segments that haven't been looked at
+/// yet are returned untouched.
+pub(crate) fn connect_unconnected_counted<S: 'static>(
+    joined: Vec<PathSegment<S>>,
+    epsilon: f32,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+) -> (Vec<PathSegment<S>>, usize) {
+    let (mut good, bad) = joined.into_iter().partition::<Vec<_>, _>(|a| a.closed);
+
+    let mut open = open_segments(bad, epsilon);
+    let (solved, joins) = try_solve(Graph::new(&mut open), &mut longest_first, on_progress);
 
     good.extend(solved.into_iter());
+    good.extend(drain_in_order(&mut open));
 
-    good
+    (good, joins)
+}
+
+/// Like `graph_stitch`, but works on the segments in `dual_qt`, leaving the
+/// result in it.  Ends are looked up in the index of `dual_qt` itself, so
+/// nothing is built again.
+pub fn graph_stitch_in_place<S: 'static>(dual_qt: &mut DualQuadTree<S>) {
+    graph_stitch_in_place_counted(dual_qt, &mut ignore_progress);
+}
+
+/// Like `graph_stitch_in_place`, but also returns the number of joins
+/// performed and reports progress.
+pub(crate) fn graph_stitch_in_place_counted<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+) -> usize {
+    let (solved, joins) = try_solve(Graph::new(dual_qt), &mut longest_first, on_progress);
+    for segment in solved {
+        dual_qt.insert(segment);
+    }
+    joins
 }
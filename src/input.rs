@@ -0,0 +1,83 @@
+use ::*;
+use std::iter::FromIterator;
+
+// Helpers for getting plain coordinates into the crate without spelling out
+// euclid types.  The results can be handed to anything that takes
+// `Into<Points<S>>`.
+
+/// Converts `(x, y)` tuples into points.
+pub fn points_from_tuples<S, I>(tuples: I) -> Points<S>
+where
+    I: IntoIterator<Item = (f32, f32)>,
+{
+    tuples.into_iter().map(|(x, y)| Point::new(x, y)).collect()
+}
+
+/// Converts `[x, y]` pairs into points.
+pub fn points_from_pairs<S>(pairs: &[[f32; 2]]) -> Points<S> {
+    pairs.iter().map(|p| Point::new(p[0], p[1])).collect()
+}
+
+/// Converts interleaved coordinates (`x0, y0, x1, y1, ...`) into points.
+///
+/// Panics if there is an odd number of coordinates.
+pub fn points_from_flat<S>(coords: &[f32]) -> Points<S> {
+    assert!(coords.len() % 2 == 0, "flat coordinates must come in pairs");
+    coords.chunks(2).map(|c| Point::new(c[0], c[1])).collect()
+}
+
+/// Converts points into interleaved coordinates (`x0, y0, x1, y1, ...`),
+/// the reverse of `points_from_flat`.
+pub fn flat_from_points<S>(points: &[Point<S>]) -> Vec<f32> {
+    let mut coords = Vec::with_capacity(points.len() * 2);
+    for p in points {
+        coords.push(p.x);
+        coords.push(p.y);
+    }
+    coords
+}
+
+/// Converts a batch of polylines made of `(x, y)` tuples.
+pub fn batch_from_tuples<S, I, J>(polylines: I) -> Vec<Points<S>>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator<Item = (f32, f32)>,
+{
+    polylines.into_iter().map(points_from_tuples).collect()
+}
+
+/// Converts a batch of polylines stored back to back in one buffer of
+/// interleaved coordinates.  `point_counts` holds the number of points in
+/// each polyline.
+///
+/// Panics if `point_counts` doesn't add up to the length of `coords`.
+pub fn batch_from_flat<S>(coords: &[f32], point_counts: &[usize]) -> Vec<Points<S>> {
+    assert_eq!(point_counts.iter().sum::<usize>() * 2, coords.len());
+    let mut rest = coords;
+    point_counts
+        .iter()
+        .map(|&count| {
+            let (polyline, tail) = rest.split_at(count * 2);
+            rest = tail;
+            points_from_flat(polyline)
+        })
+        .collect()
+}
+
+impl<S> FromIterator<(f32, f32)> for PathSegment<S> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (f32, f32)>,
+    {
+        PathSegment::new(points_from_tuples(iter), DEFAULT_EPSILON)
+    }
+}
+
+impl<S> FromIterator<[f32; 2]> for PathSegment<S> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = [f32; 2]>,
+    {
+        PathSegment::new(points_from_tuples(iter.into_iter().map(|p| (p[0], p[1]))), DEFAULT_EPSILON)
+    }
+}
@@ -0,0 +1,153 @@
+use ::*;
+use sanitize::sanitize_segments;
+use std::f32::consts::PI;
+
+/// Straightens out jogs that are narrower than the pen.
+///
+/// Walks along each path and drops every point that lies within
+/// `pen_width` of the straight line between the points kept on either side
+/// of it, so zigzags and the staircases left by tracing pixels become
+/// straight lines.  Unlike a tolerance handed to a simplifier, `pen_width`
+/// is the physical width of the line being drawn, and runs are built from
+/// one end of the path to the other rather than by splitting at the worst
+/// point, so a staircase collapses into a single diagonal.
+///
+/// The first and last points of open paths are always kept.
+pub fn remove_jogs<S>(segments: Vec<PathSegment<S>>, pen_width: f32) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    if !(pen_width > 0.0) {
+        return segments;
+    }
+
+    segments
+        .into_iter()
+        .map(|segment| {
+            let closed = segment.closed;
+            let mut points: Vec<Point<S>> = segment.path.to_vec();
+            if closed {
+                points.push(points[0]);
+            }
+
+            let mut kept = straighten(&points, pen_width);
+            if closed {
+                kept.pop();
+                // A loop needs at least three corners to enclose anything.
+                if kept.len() < 3 {
+                    return segment;
+                }
+            }
+
+            let mut straightened = PathSegment::new(kept, 0.0).with_layer(segment.layer);
+            straightened.closed = closed;
+            straightened
+        })
+        .collect()
+}
+
+/// Greedily extends a straight run from the last kept point for as long as
+/// every point it skips stays within `pen_width` of it.
+///
+/// Each skipped point further than `pen_width` from the anchor narrows the
+/// range of directions the run can leave in, so the range is kept as a pair
+/// of angles and each new end point is checked against it in constant time.
+/// Only a run that comes back towards its anchor has to look at the skipped
+/// points again, since one of them may then lie past its end.
+fn straighten<S>(points: &[Point<S>], pen_width: f32) -> Vec<Point<S>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut kept = vec![points[0]];
+    let mut anchor = 0;
+    while anchor < points.len() - 1 {
+        let a = points[anchor];
+        let mut sleeve = Sleeve::new();
+        let mut end = anchor + 1;
+        while end + 1 < points.len() {
+            sleeve.narrow(points[end] - a, pen_width);
+            let candidate = points[end + 1];
+            let fits = sleeve.contains(candidate - a)
+                && ((candidate - a).length() >= sleeve.reach
+                    || points[anchor + 1..end + 1]
+                        .iter()
+                        .all(|&p| distance_to_segment(p, a, candidate) < pen_width));
+            if !fits {
+                break;
+            }
+            end += 1;
+        }
+        kept.push(points[end]);
+        anchor = end;
+    }
+    kept
+}
+
+/// The directions a run can leave its anchor in, as angles relative to the
+/// first skipped point that constrained them.
+struct Sleeve {
+    reference: Option<f32>,
+    low: f32,
+    high: f32,
+    /// How far the furthest skipped point is from the anchor.
+    reach: f32,
+}
+
+impl Sleeve {
+    fn new() -> Sleeve {
+        Sleeve {
+            reference: None,
+            low: -PI,
+            high: PI,
+            reach: 0.0,
+        }
+    }
+
+    /// Keeps only the directions that pass within `pen_width` of the point at
+    /// `offset` from the anchor.
+    fn narrow<S>(&mut self, offset: euclid::TypedVector2D<f32, S>, pen_width: f32) {
+        let distance = offset.length();
+        self.reach = self.reach.max(distance);
+        if !(distance >= pen_width) {
+            return;
+        }
+
+        let half = (pen_width / distance).asin();
+        let angle = offset.y.atan2(offset.x);
+        let reference = *self.reference.get_or_insert(angle);
+        let centre = wrap(angle - reference);
+        self.low = self.low.max(centre - half);
+        self.high = self.high.min(centre + half);
+    }
+
+    fn contains<S>(&self, offset: euclid::TypedVector2D<f32, S>) -> bool {
+        match self.reference {
+            None => true,
+            Some(reference) => {
+                let angle = wrap(offset.y.atan2(offset.x) - reference);
+                self.low < angle && angle < self.high
+            }
+        }
+    }
+}
+
+/// Brings an angle into `-PI..=PI`.
+fn wrap(angle: f32) -> f32 {
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+fn distance_to_segment<S>(p: Point<S>, a: Point<S>, b: Point<S>) -> f32 {
+    let ab = b - a;
+    let length_2 = ab.square_length();
+    let t = if length_2 > 0.0 {
+        ((p - a).dot(ab) / length_2).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+    (p - (a + ab * t)).length()
+}
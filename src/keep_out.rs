@@ -0,0 +1,156 @@
+use ::*;
+use util::{contains_point, crossing};
+
+/// Finds short pen-up routes around closed keep-out regions using a
+/// visibility graph over the regions' corners.
+pub(crate) struct Router<S> {
+    polygons: Vec<Vec<Point<S>>>,
+    corners: Vec<Point<S>>,
+    /// `visible[i][j]` is true if corners `i` and `j` can see each other.
+    visible: Vec<Vec<bool>>,
+}
+
+impl<S> Router<S> {
+    pub(crate) fn new(keep_out: &[PathSegment<S>]) -> Router<S> {
+        let polygons: Vec<Vec<Point<S>>> = keep_out
+            .iter()
+            .filter(|s| s.closed && s.path.len() > 2)
+            .map(|s| s.path.to_vec())
+            .collect();
+        let corners: Vec<Point<S>> = polygons.iter().flat_map(|p| p.iter().cloned()).collect();
+
+        let mut router = Router {
+            polygons: polygons,
+            corners: corners,
+            visible: vec![],
+        };
+        let n = router.corners.len();
+        let mut visible = vec![vec![false; n]; n];
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let clear = router.is_clear(router.corners[i], router.corners[j]);
+                visible[i][j] = clear;
+                visible[j][i] = clear;
+            }
+        }
+        router.visible = visible;
+        router
+    }
+
+    /// The shortest route from `from` to `to` that doesn't pass through a
+    /// keep-out region, including both ends.
+    pub(crate) fn route(&self, from: Point<S>, to: Point<S>) -> Vec<Point<S>> {
+        if self.is_clear(from, to) || self.is_inside(from) || self.is_inside(to) {
+            return vec![from, to];
+        }
+
+        // Dijkstra over the corners, with `from` and `to` as two extra nodes
+        // at the end.
+        let n = self.corners.len();
+        let point = |i: usize| match i {
+            i if i < n => self.corners[i],
+            i if i == n => from,
+            _ => to,
+        };
+        let from_sees: Vec<bool> = self.corners.iter().map(|&c| self.is_clear(from, c)).collect();
+        let to_sees: Vec<bool> = self.corners.iter().map(|&c| self.is_clear(c, to)).collect();
+        let sees = |a: usize, b: usize| match (a, b) {
+            (a, b) if a < n && b < n => self.visible[a][b],
+            (a, b) if a == n && b < n => from_sees[b],
+            (a, b) if a < n && b > n => to_sees[a],
+            _ => false,
+        };
+
+        let mut distance = vec![std::f32::INFINITY; n + 2];
+        let mut previous = vec![None; n + 2];
+        let mut done = vec![false; n + 2];
+        distance[n] = 0.0;
+
+        loop {
+            let current = (0..n + 2)
+                .filter(|&i| !done[i] && distance[i].is_finite())
+                .min_by(|&a, &b| distance[a].partial_cmp(&distance[b]).unwrap());
+            let current = match current {
+                Some(current) => current,
+                // `to` can't be reached.
+                None => return vec![from, to],
+            };
+            if current == n + 1 {
+                break;
+            }
+            done[current] = true;
+
+            for next in 0..n + 2 {
+                if done[next] || next == current || !sees(current, next) {
+                    continue;
+                }
+                let d = distance[current] + (point(next) - point(current)).length();
+                if d < distance[next] {
+                    distance[next] = d;
+                    previous[next] = Some(current);
+                }
+            }
+        }
+
+        let mut route = vec![to];
+        let mut at = n + 1;
+        while let Some(p) = previous[at] {
+            route.push(point(p));
+            at = p;
+        }
+        route.reverse();
+        route
+    }
+
+    fn is_inside(&self, point: Point<S>) -> bool {
+        self.polygons.iter().any(|p| contains_point(p, point))
+    }
+
+    /// True if the straight line from `a` to `b` stays out of every
+    /// keep-out region.  Running along an edge or touching a corner is fine.
+    fn is_clear(&self, a: Point<S>, b: Point<S>) -> bool {
+        let d = b - a;
+        let length_2 = d.square_length();
+        if length_2 == 0.0 {
+            return true;
+        }
+
+        for polygon in &self.polygons {
+            for i in 0..polygon.len() {
+                let (p0, p1) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                if crossing(a, b, p0, p1).is_some() {
+                    return false;
+                }
+            }
+
+            // The line may still slip inside through corners, so check the
+            // middle of every piece between the corners that it touches.
+            let mut ts = vec![0.0, 1.0];
+            for &c in polygon {
+                let t = (c - a).dot(d) / length_2;
+                let off = a + d * t - c;
+                if t > 0.0 && t < 1.0 && off.square_length() <= length_2 * 1.0e-10 {
+                    ts.push(t);
+                }
+            }
+            ts.sort_by(|x, y| x.partial_cmp(y).unwrap());
+            for w in ts.windows(2) {
+                let middle = a + d * ((w[0] + w[1]) / 2.0);
+                if contains_point(polygon, middle) && !on_boundary(polygon, middle) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+fn on_boundary<S>(polygon: &[Point<S>], point: Point<S>) -> bool {
+    (0..polygon.len()).any(|i| {
+        let (p0, p1) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let edge = p1 - p0;
+        let length_2 = edge.square_length();
+        let t = ((point - p0).dot(edge) / length_2).max(0.0).min(1.0);
+        (p0 + edge * t - point).square_length() <= length_2 * 1.0e-10
+    })
+}
@@ -0,0 +1,66 @@
+use ::*;
+use util::{compute_bounding_box, subpath};
+use kurbo::{BezPath, PathEl};
+
+/// Converts a kurbo path into segments, one per sub-path.
+///
+/// Curves are flattened to within `tolerance`.  Sub-paths that end with a
+/// `ClosePath` element are returned as closed segments; any other sub-path
+/// is open, even one that ends where it started.
+pub fn from_bez_path<S, E: Into<Epsilon>>(path: &BezPath, tolerance: f64, epsilon: E) -> Vec<PathSegment<S>> {
+    let mut out = vec![];
+    let mut current: Vec<Point<S>> = vec![];
+
+    path.flatten(tolerance, |el| match el {
+        PathEl::MoveTo(p) => {
+            finish(&mut current, false, &mut out);
+            current.push(Point::new(p.x as f32, p.y as f32));
+        }
+        PathEl::LineTo(p) => {
+            current.push(Point::new(p.x as f32, p.y as f32));
+        }
+        PathEl::ClosePath => {
+            finish(&mut current, true, &mut out);
+        }
+        // `flatten` only produces lines.
+        PathEl::QuadTo(..) | PathEl::CurveTo(..) => unreachable!(),
+    });
+    finish(&mut current, false, &mut out);
+
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| compute_bounding_box(out.iter().flat_map(|p| p.0.iter().cloned())));
+    return out.into_iter().map(|(p, closed)| subpath(p, closed, epsilon)).collect();
+
+    fn finish<S>(current: &mut Vec<Point<S>>, closed: bool, out: &mut Vec<(Vec<Point<S>>, bool)>) {
+        if current.len() > 1 {
+            out.push((current.clone(), closed));
+        }
+        current.clear();
+    }
+}
+
+/// Converts segments into a single kurbo path with one sub-path per segment.
+pub fn to_bez_path<'a, S: 'a, I>(segments: I) -> BezPath
+where
+    I: IntoIterator<Item = &'a PathSegment<S>>,
+{
+    let mut path = BezPath::new();
+    for segment in segments {
+        let first = segment.first();
+        path.move_to((first.x as f64, first.y as f64));
+        for p in &segment.path[1..] {
+            path.line_to((p.x as f64, p.y as f64));
+        }
+        if segment.closed {
+            path.close_path();
+        }
+    }
+    path
+}
+
+impl<'a, S> From<&'a PathSegment<S>> for BezPath {
+    fn from(segment: &'a PathSegment<S>) -> BezPath {
+        to_bez_path(Some(segment))
+    }
+}
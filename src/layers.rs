@@ -0,0 +1,95 @@
+use ::*;
+use pipeline::run_pipeline_layered;
+use travel::{apply_order, exit_point, nearest_endpoint, travel_order};
+use util::compute_bounding_box;
+use std::collections::HashMap;
+
+/// Identifies a single pen (or layer) in a multi-pen drawing.  Segments
+/// start out on `LayerId(0)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LayerId(pub u32);
+
+/// Runs the pipeline over every layer, then orders the layers and the
+/// segments inside of them to cut down on pen-up travel.
+///
+/// All of the layers go through one run of the pipeline, with each segment
+/// tagged with its layer so that segments on different layers are never
+/// joined.  Every non-empty layer shows up exactly once in the output, so
+/// the number of pen changes is as small as it can be.  The next layer is
+/// the one with an endpoint closest to where the pen is, which starts at
+/// the origin.
+pub fn process_layers<P, S: 'static>(
+    layers: HashMap<LayerId, Vec<P>>,
+    config: &PipelineConfig,
+) -> Vec<(LayerId, Vec<PathSegment<S>>)>
+where
+    P: Into<Points<S>>,
+{
+    let mut layers: Vec<(LayerId, Vec<P>)> = layers.into_iter().collect();
+    // HashMap iteration order is random; keep ties deterministic.
+    layers.sort_by_key(|&(id, _)| id);
+
+    let mut groups: Vec<(LayerId, Vec<PathSegment<S>>)> = layers.iter().map(|&(id, _)| (id, vec![])).collect();
+    for segment in run_pipeline_layered(layers, config) {
+        let index = groups.binary_search_by_key(&segment.layer, |g| g.0).unwrap();
+        groups[index].1.push(segment);
+    }
+    groups.retain(|&(_, ref segments)| !segments.is_empty());
+    order_layers(groups)
+}
+
+/// Puts the layers in order, each after the one that the pen last left off
+/// closest to, and orders the segments inside of each one.  Each layer's
+/// segments are ordered once, so this is about linear in the number of
+/// segments rather than quadratic in the number of layers.
+fn order_layers<S: 'static>(groups: Vec<(LayerId, Vec<PathSegment<S>>)>) -> Vec<(LayerId, Vec<PathSegment<S>>)> {
+    if groups.is_empty() {
+        return vec![];
+    }
+
+    let bounds = compute_bounding_box(
+        groups
+            .iter()
+            .flat_map(|g| g.1.iter())
+            .flat_map(|s| vec![s.first(), s.last()])
+            .chain(Some(Point::origin())),
+    );
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+
+    // Where each layer can be entered; closed segments only at their start.
+    let mut tree = QuadTree::default(bounds);
+    let mut entries = Vec::with_capacity(groups.len());
+    for (index, &(_, ref segments)) in groups.iter().enumerate() {
+        let mut ids = vec![];
+        for segment in segments {
+            ids.extend(tree.insert_with_box(index, segment.first().aabb()));
+            if !segment.closed {
+                ids.extend(tree.insert_with_box(index, segment.last().aabb()));
+            }
+        }
+        entries.push(ids);
+    }
+    let initial_radius = bounds.size.width.max(bounds.size.height) / (tree.len() as f32).sqrt();
+
+    let mut groups: Vec<Option<(LayerId, Vec<PathSegment<S>>)>> = groups.into_iter().map(Some).collect();
+    let mut position = Point::origin();
+    let mut out = Vec::with_capacity(groups.len());
+    while let Some(index) = nearest_endpoint(&tree, position, initial_radius) {
+        for &id in &entries[index] {
+            tree.remove(id);
+        }
+
+        let (id, segments) = groups[index].take().unwrap();
+        let order = travel_order(&segments, position);
+        let ordered = apply_order(segments, &order);
+        if let Some(last) = ordered.last() {
+            position = exit_point(last);
+        }
+        out.push((id, ordered));
+    }
+
+    out
+}
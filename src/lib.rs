@@ -5,58 +5,250 @@ extern crate aabb_quadtree;
 extern crate euclid;
 extern crate fnv;
 extern crate itertools;
+#[cfg(feature = "lyon")]
+extern crate lyon_path;
+#[cfg(feature = "geo")]
+extern crate geo_types;
+#[cfg(feature = "kurbo")]
+extern crate kurbo;
+#[cfg(feature = "geojson")]
+#[macro_use]
+extern crate serde_json;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "preview")]
+extern crate png;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
 #[cfg(test)]
 extern crate permutohedron;
+#[cfg(all(test, feature = "dxf"))]
+extern crate dxf_reader;
+#[cfg(all(test, feature = "wasm", target_arch = "wasm32"))]
+extern crate wasm_bindgen_test;
 extern crate smallvec;
 
+#[macro_use]
+mod trace;
 mod dual_quad_tree;
+mod spatial_index;
 mod graph_stitch;
 mod connect_obvious;
 mod test;
 mod prune;
 mod zero_area_loop;
+mod pipeline;
+mod stats;
+mod progress;
+mod travel;
+mod layers;
+mod curve;
+mod split;
+mod occlude;
+mod boolean;
+mod containment;
+mod self_intersection;
+mod error;
+mod epsilon;
+mod snap;
+mod resample;
+mod smooth;
+mod plan;
+mod keep_out;
+mod duration;
+mod endpoint_index;
+mod overstroke;
+mod dash;
+mod direction;
+mod validate;
+mod sanitize;
+mod segment_ops;
+mod approx;
+mod transform;
+mod input;
+mod soup;
+mod components;
+mod jogs;
+mod t_junction;
+mod text_format;
+mod density_prune;
+mod batch;
+#[cfg(feature = "debug")]
+mod debug_dump;
+#[cfg(feature = "lyon")]
+mod lyon_interop;
+#[cfg(feature = "geo")]
+mod geo_interop;
+#[cfg(feature = "kurbo")]
+mod kurbo_interop;
+#[cfg(feature = "geojson")]
+mod geojson;
+#[cfg(feature = "dxf")]
+mod dxf;
+#[cfg(feature = "contour")]
+mod contour;
+#[cfg(feature = "preview")]
+mod preview;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+mod wasm;
 pub(crate) mod util;
 
 use aabb_quadtree::*;
-use smallvec::SmallVec;
+use smallvec::{Array, SmallVec};
 use dual_quad_tree::*;
-use std::cell::Cell;
 use std::iter::{IntoIterator, FromIterator};
 
-pub use connect_obvious::connect_obvious;
-pub use prune::prune;
-pub use graph_stitch::connect_unconnected as graph_stitch;
-pub use zero_area_loop::remove_zero_area_loops;
+pub use connect_obvious::{connect_obvious, connect_obvious_in_place, connect_obvious_in_place_ordered,
+                          connect_obvious_ordered, connect_obvious_with_cost, restitch, SeedOrder};
+pub use prune::{prune, prune_in_place, prune_open_in_place, prune_with_removed};
+pub use density_prune::{prune_by_density, DensityPrune};
+pub use batch::BatchProcessor;
+pub use graph_stitch::{connect_unconnected as graph_stitch, connect_unconnected_with_epsilon as graph_stitch_with_epsilon,
+                       graph_stitch_in_place, graph_stitch_with_cost};
+pub use dual_quad_tree::{DqtId, DualQuadTree};
+pub use spatial_index::IndexBackend;
+pub use zero_area_loop::{remove_zero_area_loops, remove_zero_area_loops_with_removed, remove_zero_area_spurs};
+pub use pipeline::{optimize_until_stable, run_pipeline, run_pipeline_with_progress,
+                   run_pipeline_with_stats, PipelineConfig};
+#[cfg(feature = "debug")]
+pub use pipeline::run_pipeline_with_dumps;
+pub use progress::{Pass, PassProgress};
+pub use error::Error;
+pub use epsilon::Epsilon;
+pub use snap::snap_to_grid;
+pub use resample::resample;
+pub use smooth::smooth_chaikin;
+pub use plan::{plan_travel, plan_travel_avoiding, plan_travel_between, Move, Plan};
+pub use duration::{estimate_duration, PenKinematics};
+pub use endpoint_index::{End, Endpoint, EndpointIndex};
+pub use overstroke::merge_overstrokes;
+pub use dash::dash;
+pub use direction::{orient, Direction};
+pub use validate::{validate, Violation};
+pub use sanitize::{sanitize, SanitizeReport};
+pub use approx::SegmentKey;
+pub use transform::{transform, transform_units};
+pub use soup::stitch_segment_soup;
+pub use components::components;
+pub use jogs::remove_jogs;
+pub use t_junction::snap_to_boundaries;
+pub use text_format::{read_layers, read_segments, write_layers, write_segments, TextLayer};
+pub use input::{batch_from_flat, batch_from_tuples, flat_from_points, points_from_flat, points_from_pairs,
+                points_from_tuples};
+#[cfg(feature = "debug")]
+pub use debug_dump::{debug_dump, debug_dump_tree};
+pub use occlude::occlude;
+pub use self_intersection::split_self_intersections;
+pub use containment::{nesting, order_by_containment};
+pub use boolean::{difference, intersection, union, FillRule};
+pub use split::{split_max_length, split_max_points};
+pub use curve::{connect_obvious_curved, CurvedPathSegment, Span};
+#[cfg(feature = "geo")]
+pub use geo_interop::{from_line_string, from_multi_line_string, from_polygon, to_multi_line_string};
+#[cfg(feature = "contour")]
+pub use contour::{contour, marching_squares};
+#[cfg(feature = "preview")]
+pub use preview::{preview, preview_plan, Image, PreviewOptions};
+#[cfg(feature = "dxf")]
+pub use dxf::{from_dxf, to_dxf};
+#[cfg(feature = "geojson")]
+pub use geojson::{from_geojson, to_geojson};
+#[cfg(feature = "kurbo")]
+pub use kurbo_interop::{from_bez_path, to_bez_path};
+#[cfg(feature = "wasm")]
+pub use wasm::stitch;
+#[cfg(feature = "lyon")]
+pub use lyon_interop::{from_lyon_path, to_lyon_path};
+pub use stats::{drawn_length, Stats};
+pub use travel::{order_travel, order_travel_between, travel_distance};
+pub use layers::{process_layers, LayerId};
 
 type Point<S> = euclid::TypedPoint2D<f32, S>;
 
+/// How many points a path holds before it spills onto the heap, unless a
+/// `PathSegment` is given other inline storage.
+pub const INLINE_POINTS: usize = 2;
+
+/// The default inline storage of `Points` and `PathSegment`.
+pub type PointArray<S> = [Point<S>; INLINE_POINTS];
+
+/// The points of a path, stored inline up to as many as fit in `A`.
+pub type Points<S, A = PointArray<S>> = SmallVec<A>;
+
 /// A single path segment that may be merged with other path segments.
+///
+/// `A` is the array that the first points of `path` are stored in before it
+/// spills onto the heap.  The passes work on the default of two points; a
+/// drawing made of longer polylines can keep its segments in a bigger array,
+/// such as `[TypedPoint2D<f32, S>; 8]`, and move them over with `into_inline`.
 #[derive(PartialEq, Clone)]
-pub struct PathSegment<S> {
+pub struct PathSegment<S, A: Array<Item = Point<S>> = PointArray<S>> {
     /// The path of points
-    pub path: SmallVec<[Point<S>; 2]>,
+    pub path: SmallVec<A>,
     /// True if the end of the path segment is the same as the
     /// beginning of the path segment.
     pub closed: bool,
-    length_2: Cell<Option<f32>>,
-    length: Cell<Option<f32>>,
+    /// The layer (or pen) that the segment is drawn with.  Segments are
+    /// only joined to segments on the same layer, and passes that cut or
+    /// rebuild a segment keep its layer.
+    pub layer: LayerId,
+    length_2: f32,
+    length: f32,
 }
 
-impl<S> ::std::fmt::Debug for PathSegment<S> {
+impl<S, A: Array<Item = Point<S>>> ::std::fmt::Debug for PathSegment<S, A> {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         f.debug_struct("PathSegment")
             .field("path", &self.path)
             .field("closed", &self.closed)
+            .field("layer", &self.layer)
             .finish()
     }
 }
 
 impl<S> PathSegment<S> {
     /// TODO: doc
-    pub fn new<P: Into<SmallVec<[Point<S>; 2]>>>(path: P, epsilon: f32) -> PathSegment<S> {
+    ///
+    /// A relative `epsilon` is resolved against the bounds of `path`.
+    pub fn new<P, E>(path: P, epsilon: E) -> PathSegment<S>
+    where
+        P: Into<Points<S>>,
+        E: Into<Epsilon>,
+    {
+        PathSegment::new_inline(path, epsilon)
+    }
+
+    /// Like `new`, but returns an error instead of panicking on bad input.
+    ///
+    /// Paths with a NaN or infinite coordinate are rejected as well; see
+    /// `sanitize` for cleaning those up.
+    pub fn try_new<P, E>(path: P, epsilon: E) -> Result<PathSegment<S>, Error>
+    where
+        P: Into<Points<S>>,
+        E: Into<Epsilon>,
+    {
+        PathSegment::try_new_inline(path, epsilon)
+    }
+}
+
+impl<S, A: Array<Item = Point<S>>> PathSegment<S, A> {
+    /// Like `new`, but stores the points inline in `A`.
+    pub fn new_inline<P, E>(path: P, epsilon: E) -> PathSegment<S, A>
+    where
+        P: Into<SmallVec<A>>,
+        E: Into<Epsilon>,
+    {
         let mut path = path.into();
 
         assert!(path.len() > 1);
+        let epsilon = epsilon
+            .into()
+            .resolve_with(|| util::compute_bounding_box(path.iter().cloned()));
         let first = path.first().cloned().unwrap();
         let last = path.last().cloned().unwrap();
         let first_pt: Point<S> = Point::new(first.x, first.y);
@@ -68,14 +260,55 @@ impl<S> PathSegment<S> {
             path.pop();
         }
 
+        let (length_2, length) = lengths(&path);
         PathSegment {
             path: path,
             closed: closed,
-            length_2: Cell::new(None),
-            length: Cell::new(None),
+            layer: LayerId::default(),
+            length_2: length_2,
+            length: length,
         }
     }
 
+    /// Like `try_new`, but stores the points inline in `A`.
+    pub fn try_new_inline<P, E>(path: P, epsilon: E) -> Result<PathSegment<S, A>, Error>
+    where
+        P: Into<SmallVec<A>>,
+        E: Into<Epsilon>,
+    {
+        let path = path.into();
+        if path.len() < 2 {
+            return Err(Error::TooFewPoints);
+        }
+        if !path.iter().all(|&p| sanitize::is_finite(p)) {
+            return Err(Error::NonFinite);
+        }
+        Ok(PathSegment::new_inline(path, epsilon))
+    }
+
+    /// Moves the points into the inline storage `B`, keeping everything else.
+    /// `into_inline::<PointArray<S>>()` gives a segment that the passes take.
+    pub fn into_inline<B: Array<Item = Point<S>>>(self) -> PathSegment<S, B> {
+        let path = if self.path.len() <= B::size() {
+            self.path.into_iter().collect()
+        } else {
+            SmallVec::from_vec(self.path.into_vec())
+        };
+        PathSegment {
+            path: path,
+            closed: self.closed,
+            layer: self.layer,
+            length_2: self.length_2,
+            length: self.length,
+        }
+    }
+
+    /// Moves the segment onto `layer`.
+    pub fn with_layer(mut self, layer: LayerId) -> PathSegment<S, A> {
+        self.layer = layer;
+        self
+    }
+
     fn first(&self) -> Point<S> {
         *self.path.first().unwrap()
     }
@@ -86,51 +319,52 @@ impl<S> PathSegment<S> {
 
     /// TODO: document
     pub fn length_2(&self) -> f32 {
-        if let Some(l) = self.length_2.get() {
-            return l;
-        }
-
-        let length_2 = self.path
-            .as_slice()
-            .windows(2)
-            .map(|s| (s[1] - s[0]).square_length())
-            .sum();
-
-        self.length_2.set(Some(length_2));
-
-        return length_2;
+        self.length_2
     }
 
     /// TODO: document
     pub fn length(&self) -> f32 {
-        if let Some(l) = self.length.get() {
-            return l;
-        }
-
-        let length = self.path
-            .as_slice()
-            .windows(2)
-            .map(|s| (s[1] - s[0]).length())
-            .sum();
-        self.length.set(Some(length));
+        self.length
+    }
+}
 
-        return length;
+// Computed up front rather than cached lazily so that `PathSegment` stays
+// `Sync`.
+fn lengths<S>(path: &[Point<S>]) -> (f32, f32) {
+    let mut length_2 = 0.0;
+    let mut length = 0.0;
+    for s in path.windows(2) {
+        let d = s[1] - s[0];
+        length_2 += d.square_length();
+        length += d.length();
     }
+    (length_2, length)
 }
 
-impl<S> IntoIterator for PathSegment<S> {
+impl<S, A: Array<Item = Point<S>>> IntoIterator for PathSegment<S, A> {
     type Item = Point<S>;
-    type IntoIter = smallvec::IntoIter<[Point<S>; 2]>;
+    type IntoIter = smallvec::IntoIter<A>;
     fn into_iter(self) -> Self::IntoIter {
         self.path.into_iter()
     }
 }
 
-impl <S> FromIterator<Point<S>> for PathSegment<S> {
+impl<S, A: Array<Item = Point<S>>> From<PathSegment<S, A>> for SmallVec<A> {
+    fn from(segment: PathSegment<S, A>) -> Self {
+        let mut path = segment.path;
+        if segment.closed {
+            let first = path[0];
+            path.push(first);
+        }
+        path
+    }
+}
+
+impl <S, A: Array<Item = Point<S>>> FromIterator<Point<S>> for PathSegment<S, A> {
     fn from_iter<T>(iter: T) -> Self
     where
         T: IntoIterator<Item = Point<S>> {
-        PathSegment::new(iter.into_iter().collect::<Vec<_>>(), 0.001)
+        PathSegment::new_inline(iter.into_iter().collect::<SmallVec<A>>(), DEFAULT_EPSILON)
     }
 
 }
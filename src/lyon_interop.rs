@@ -0,0 +1,70 @@
+use ::*;
+use util::{compute_bounding_box, subpath};
+use lyon_path::Path;
+use lyon_path::builder::FlatPathBuilder;
+use lyon_path::iterator::PathIterator;
+use lyon_path::FlattenedEvent;
+use lyon_path::math::point;
+
+/// Converts a lyon path into segments, one per sub-path.
+///
+/// Curves are flattened to within `tolerance`.  Sub-paths that end with a
+/// `Close` event are returned as closed segments; any other sub-path is
+/// open, even one that ends where it started.
+pub fn from_lyon_path<S, E: Into<Epsilon>>(path: &Path, tolerance: f32, epsilon: E) -> Vec<PathSegment<S>> {
+    let mut out = vec![];
+    let mut current: Vec<Point<S>> = vec![];
+
+    for event in path.path_iter().flattened(tolerance) {
+        match event {
+            FlattenedEvent::MoveTo(p) => {
+                finish(&mut current, false, &mut out);
+                current.push(Point::new(p.x, p.y));
+            }
+            FlattenedEvent::LineTo(p) => {
+                current.push(Point::new(p.x, p.y));
+            }
+            FlattenedEvent::Close => {
+                finish(&mut current, true, &mut out);
+            }
+        }
+    }
+    finish(&mut current, false, &mut out);
+
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| compute_bounding_box(out.iter().flat_map(|p| p.0.iter().cloned())));
+    return out.into_iter().map(|(p, closed)| subpath(p, closed, epsilon)).collect();
+
+    fn finish<S>(current: &mut Vec<Point<S>>, closed: bool, out: &mut Vec<(Vec<Point<S>>, bool)>) {
+        if current.len() > 1 {
+            out.push((current.clone(), closed));
+        }
+        current.clear();
+    }
+}
+
+/// Converts segments into a single lyon path with one sub-path per segment.
+pub fn to_lyon_path<'a, S: 'a, I>(segments: I) -> Path
+where
+    I: IntoIterator<Item = &'a PathSegment<S>>,
+{
+    let mut builder = Path::builder();
+    for segment in segments {
+        let first = segment.first();
+        builder.move_to(point(first.x, first.y));
+        for p in &segment.path[1..] {
+            builder.line_to(point(p.x, p.y));
+        }
+        if segment.closed {
+            builder.close();
+        }
+    }
+    builder.build()
+}
+
+impl<'a, S> From<&'a PathSegment<S>> for Path {
+    fn from(segment: &'a PathSegment<S>) -> Path {
+        to_lyon_path(Some(segment))
+    }
+}
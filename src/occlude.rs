@@ -0,0 +1,142 @@
+use ::*;
+use util::{compute_bounding_box, contains_point};
+use std::cmp::Ordering;
+use std::mem;
+use sanitize::{is_finite, sanitize_segments};
+
+type Rect<S> = euclid::TypedRect<f32, S>;
+
+/// Removes the parts of segments that are hidden behind any of the closed
+/// `occluders`.  Open occluders are ignored, as are ones with a NaN or
+/// infinite point.
+///
+/// Segments that are partially hidden are broken into open pieces, which
+/// can be joined back up by the stitching passes.
+pub fn occlude<S: 'static, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    occluders: &[PathSegment<S>],
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+    let polygons: Vec<&PathSegment<S>> = occluders
+        .iter()
+        .filter(|o| o.closed && o.path.len() > 2 && o.path.iter().all(|&p| is_finite(p)))
+        .collect();
+    if polygons.is_empty() || segments.is_empty() {
+        return segments;
+    }
+
+    let bounds = compute_bounding_box(
+        segments
+            .iter()
+            .chain(polygons.iter().cloned())
+            .flat_map(|s| s.path.iter().cloned()),
+    );
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+
+    let mut edges = QuadTree::default(bounds);
+    let mut areas = QuadTree::default(bounds);
+    for (i, polygon) in polygons.iter().enumerate() {
+        areas.insert_with_box(i, compute_bounding_box(polygon.path.iter().cloned()));
+        let path = &polygon.path;
+        for j in 0..path.len() {
+            let (a, b) = (path[j], path[(j + 1) % path.len()]);
+            edges.insert_with_box((a, b), Rect::from_points(&[a, b]));
+        }
+    }
+
+    let is_hidden = |point: Point<S>| {
+        areas
+            .query(point.aabb())
+            .into_iter()
+            .any(|(&i, _, _)| contains_point(&polygons[i].path, point))
+    };
+
+    let mut out = vec![];
+    for segment in segments {
+        let closed = segment.closed;
+        let layer = segment.layer;
+        let points: Points<S> = segment.into();
+        let mut runs: Vec<Vec<Point<S>>> = vec![];
+        let mut current = vec![];
+
+        for w in points.windows(2) {
+            let (p, q) = (w[0], w[1]);
+            let at = |t: f32| if t == 0.0 {
+                p
+            } else if t == 1.0 {
+                q
+            } else {
+                p + (q - p) * t
+            };
+
+            let mut ts = vec![0.0, 1.0];
+            for (&(a, b), _, _) in edges.query(Rect::from_points(&[p, q])) {
+                if let Some(t) = crossing(p, q, a, b) {
+                    ts.push(t);
+                }
+            }
+            ts.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            for pair in ts.windows(2) {
+                let (t0, t1) = (pair[0], pair[1]);
+                if t1 <= t0 {
+                    continue;
+                }
+
+                if is_hidden(at((t0 + t1) / 2.0)) {
+                    if !current.is_empty() {
+                        runs.push(mem::replace(&mut current, vec![]));
+                    }
+                } else {
+                    if current.is_empty() {
+                        current.push(at(t0));
+                    }
+                    current.push(at(t1));
+                }
+            }
+        }
+        if !current.is_empty() {
+            runs.push(current);
+        }
+
+        // A closed segment whose start is visible would otherwise be split
+        // in two at its starting point.
+        if closed && runs.len() > 1 && runs[0][0] == points[0]
+            && runs.last().unwrap().last() == points.last()
+        {
+            let first = runs.remove(0);
+            runs.last_mut().unwrap().extend_from_slice(&first[1..]);
+        }
+
+        out.extend(
+            runs.into_iter()
+                .filter(|run| run.len() > 1)
+                .map(|run| PathSegment::new(run, epsilon).with_layer(layer)),
+        );
+    }
+
+    out
+}
+
+/// Where `p -> q` crosses `a -> b`, as a fraction of the way along `p -> q`.
+fn crossing<S>(p: Point<S>, q: Point<S>, a: Point<S>, b: Point<S>) -> Option<f32> {
+    let r = q - p;
+    let s = b - a;
+    let denom = r.cross(s);
+    if denom == 0.0 {
+        return None;
+    }
+
+    let t = (a - p).cross(s) / denom;
+    let u = (a - p).cross(r) / denom;
+    if t > 0.0 && t < 1.0 && u >= 0.0 && u <= 1.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
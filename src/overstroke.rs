@@ -0,0 +1,139 @@
+use ::*;
+use resample::resample_intervals;
+use stats::raw_length;
+use util::compute_bounding_box;
+use sanitize::sanitize_segments;
+
+/// Merges open strokes that trace nearly the same line into a single
+/// centerline.
+///
+/// Two strokes are merged when their Hausdorff distance is at most
+/// `threshold`, measured between points spaced `threshold / 2` apart along
+/// each stroke.  The centerline is the average of the strokes after they
+/// have been resampled to the same number of points.  Closed segments are
+/// left alone.
+pub fn merge_overstrokes<S: 'static, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    threshold: f32,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    assert!(threshold > 0.0);
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+
+    let (mut out, open): (Vec<_>, Vec<_>) = segments.into_iter().partition(|s| s.closed);
+    if open.is_empty() {
+        return out;
+    }
+
+    let boxes: Vec<_> = open
+        .iter()
+        .map(|s| compute_bounding_box(s.path.iter().cloned()).inflate(threshold, threshold))
+        .collect();
+    let bounds = boxes.iter().skip(1).fold(boxes[0], |a, b| a.union(b));
+    let mut tree = QuadTree::default(bounds);
+    let mut ids = Vec::with_capacity(open.len());
+    for (i, b) in boxes.iter().enumerate() {
+        ids.push(tree.insert_with_box(i, *b).unwrap());
+    }
+
+    let strokes: Vec<Stroke<S>> = open
+        .iter()
+        .map(|s| Stroke::new(s.path.to_vec(), threshold / 2.0))
+        .collect();
+    let mut merged = vec![false; open.len()];
+    let mut open: Vec<Option<PathSegment<S>>> = open.into_iter().map(Some).collect();
+
+    for i in 0..open.len() {
+        if merged[i] {
+            continue;
+        }
+        tree.remove(ids[i]);
+
+        let mut centerline = Stroke::new(strokes[i].points.clone(), threshold / 2.0);
+        let mut weight = 1.0;
+        let mut candidates: Vec<usize> = tree.query(boxes[i]).into_iter().map(|(&j, _, _)| j).collect();
+        candidates.sort();
+        for j in candidates {
+            if merged[j] || layer_of(&open[j]) != layer_of(&open[i])
+                || hausdorff(&centerline.samples, &strokes[j].samples) > threshold
+            {
+                continue;
+            }
+            centerline = centerline.average(&strokes[j], weight, threshold / 2.0);
+            weight += 1.0;
+            merged[j] = true;
+            tree.remove(ids[j]);
+        }
+
+        if weight == 1.0 {
+            out.push(open[i].take().unwrap());
+        } else {
+            let layer = layer_of(&open[i]).unwrap();
+            out.push(PathSegment::new(centerline.points, epsilon).with_layer(layer));
+        }
+    }
+
+    return out;
+
+    fn layer_of<S>(segment: &Option<PathSegment<S>>) -> Option<LayerId> {
+        segment.as_ref().map(|s| s.layer)
+    }
+}
+
+struct Stroke<S> {
+    points: Vec<Point<S>>,
+    length: f32,
+    /// Points spaced at most `spacing` apart, used for measuring distance.
+    samples: Points<S>,
+}
+
+impl<S> Stroke<S> {
+    fn new(points: Vec<Point<S>>, spacing: f32) -> Stroke<S> {
+        let length = raw_length(&points);
+        let intervals = ((length / spacing).ceil() as usize).max(1);
+        Stroke {
+            samples: resample_intervals(&points, length, intervals),
+            points: points,
+            length: length,
+        }
+    }
+
+    /// The weighted average of `self` and `other`, with `self` counting for
+    /// `weight` strokes.
+    fn average(&self, other: &Stroke<S>, weight: f32, spacing: f32) -> Stroke<S> {
+        let count = self.points.len().max(other.points.len()).max(2) - 1;
+        let a = resample_intervals(&self.points, self.length, count);
+        let mut b = resample_intervals(&other.points, other.length, count);
+
+        // Run `other` the same way as `self`.
+        let (a0, a1) = (a[0], a[count]);
+        let straight = (b[0] - a0).length() + (b[count] - a1).length();
+        let reversed = (b[count] - a0).length() + (b[0] - a1).length();
+        if reversed < straight {
+            b.reverse();
+        }
+
+        let points = a.iter()
+            .zip(b.iter())
+            .map(|(&p, &q)| p + (q - p) / (weight + 1.0))
+            .collect();
+        Stroke::new(points, spacing)
+    }
+}
+
+/// The symmetric discrete Hausdorff distance between two point sets.
+fn hausdorff<S>(a: &[Point<S>], b: &[Point<S>]) -> f32 {
+    directed(a, b).max(directed(b, a))
+}
+
+fn directed<S>(a: &[Point<S>], b: &[Point<S>]) -> f32 {
+    a.iter()
+        .map(|&p| {
+            b.iter()
+                .map(|&q| (q - p).square_length())
+                .fold(std::f32::INFINITY, f32::min)
+        })
+        .fold(0.0, f32::max)
+        .sqrt()
+}
@@ -0,0 +1,407 @@
+use ::*;
+use connect_obvious::connect_obvious_in_place_counted;
+use graph_stitch::graph_stitch_in_place_counted;
+use stats::{drawn_length, raw_length};
+use progress::ignore_progress;
+use util::compute_bounding_box;
+use validate::violations;
+use std::cell::Cell;
+use std::ops::ControlFlow;
+
+/// Settings shared by the passes that make up the standard pipeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PipelineConfig {
+    /// Points closer than this are considered to be the same point.
+    pub epsilon: Epsilon,
+    /// Only join the end of a segment to the start of another.
+    pub only_starts: bool,
+    /// Join segments even when there is more than one candidate.
+    pub allow_ambiguous: bool,
+    /// Follow `connect_obvious` with `prune_open_in_place`, which drops open
+    /// segments that can't be part of a closed loop.
+    pub prune: bool,
+    /// Follow `connect_obvious` with `graph_stitch`.  Note that
+    /// `graph_stitch` only keeps segments that end up in a closed loop.
+    pub graph_stitch: bool,
+    /// Cut the there-and-back spurs out of the stitched paths with
+    /// `remove_zero_area_spurs`.
+    pub remove_spurs: bool,
+    /// Drop the short paths in crowded parts of the output with
+    /// `prune_by_density`.
+    pub density_prune: Option<DensityPrune>,
+    /// Make every open output path run in this direction.
+    pub direction: Option<Direction>,
+    /// When `direction` is set, also cut paths where they turn back on
+    /// themselves.
+    pub split_to_direction: bool,
+    /// Check the output with `validate` and panic if it finds a problem.
+    ///
+    /// Only the checks that the enabled passes promise to uphold are made;
+    /// for instance, nothing is said about dropped points when `prune` or
+    /// `graph_stitch` is on.
+    pub debug_validate: bool,
+    /// The spatial index that the passes look up endpoints in.
+    pub index: IndexBackend,
+    /// The order in which `connect_obvious` grows chains from segments.
+    pub seed_order: SeedOrder,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> PipelineConfig {
+        PipelineConfig {
+            epsilon: Epsilon::default(),
+            only_starts: false,
+            allow_ambiguous: false,
+            prune: false,
+            graph_stitch: false,
+            remove_spurs: false,
+            density_prune: None,
+            direction: None,
+            split_to_direction: false,
+            debug_validate: false,
+            index: IndexBackend::default(),
+            seed_order: SeedOrder::default(),
+        }
+    }
+}
+
+/// Runs `connect_obvious` (and optionally `prune` and `graph_stitch`) over
+/// the segments.
+pub fn run_pipeline<P, I, S: 'static>(segments: I, config: &PipelineConfig) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    run_pipeline_impl(segments, config, &mut ignore_progress, &mut ignore_passes).0
+}
+
+/// The epsilon that every pass of a run uses, resolved once against the
+/// bounds of the whole input so that a relative epsilon can't drift as
+/// passes drop segments.
+pub(crate) fn resolve_epsilon<S>(input: &[Points<S>], config: &PipelineConfig) -> f32 {
+    config
+        .epsilon
+        .resolve_with(|| compute_bounding_box(input.iter().flat_map(|s| s.iter().cloned())))
+}
+
+/// Panics if `out` breaks an invariant that the passes enabled in `config`
+/// should have kept.
+pub(crate) fn debug_validate<S: 'static>(
+    input: &[Points<S>],
+    out: &[PathSegment<S>],
+    epsilon: f32,
+    config: &PipelineConfig,
+) {
+    let drops_segments = config.prune || config.graph_stitch || config.remove_spurs || config.density_prune.is_some();
+    let joins_everything = config.allow_ambiguous && !config.only_starts && config.direction.is_none();
+
+    // `connect_obvious` throws away segments that are too short to matter.
+    let kept = input
+        .iter()
+        .filter(|s| s.len() > 1 && s.windows(2).map(|w| (w[1] - w[0]).square_length()).sum::<f32>() >= epsilon)
+        .map(|s| &s[..]);
+
+    let problems: Vec<_> = violations(kept, out, epsilon)
+        .into_iter()
+        .filter(|v| match *v {
+            Violation::Uncovered { .. } => !drops_segments,
+            Violation::Unjoined { .. } => joins_everything,
+            Violation::InconsistentClosed { .. } | Violation::NonFinite { .. } => true,
+        })
+        .collect();
+    assert!(problems.is_empty(), "the pipeline broke its invariants: {:?}", problems);
+}
+
+/// What a pass left behind, as handed to the hook that `run_passes` and
+/// `finish` call after every pass they run.
+#[cfg_attr(not(feature = "debug"), allow(dead_code))]
+pub(crate) enum PassOutput<'a, S: 'a> {
+    /// The segments are still in the index.
+    Indexed(&'a DualQuadTree<S>),
+    /// The segments have been taken out of the index, along with the epsilon
+    /// of the run.
+    Paths(&'a [PathSegment<S>], f32),
+}
+
+pub(crate) fn ignore_passes<S>(_: &'static str, _: PassOutput<S>) {}
+
+/// The passes that every way of running a `PipelineConfig` shares, run over
+/// the segments in `dual_qt` and leaving the result in it.  `on_pass` is
+/// called with the name of each pass once it is done.
+///
+/// Returns the number of joins and ambiguities, and whether `on_progress`
+/// cancelled the run.  A cancelled run skips the passes it hadn't started.
+pub(crate) fn run_passes<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    config: &PipelineConfig,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+    on_pass: &mut FnMut(&'static str, PassOutput<S>),
+) -> (usize, usize, bool) {
+    let cancelled = Cell::new(false);
+    let mut tracked = |p: PassProgress| {
+        let flow = on_progress(p);
+        if let ControlFlow::Break(()) = flow {
+            cancelled.set(true);
+        }
+        flow
+    };
+
+    let (mut joins, ambiguities) = connect_obvious_in_place_counted(
+        dual_qt,
+        config.seed_order,
+        config.only_starts,
+        config.allow_ambiguous,
+        &mut tracked,
+    );
+    on_pass("connect_obvious", PassOutput::Indexed(dual_qt));
+    if config.prune && !cancelled.get() {
+        prune_open_in_place(dual_qt, config.only_starts);
+        on_pass("prune", PassOutput::Indexed(dual_qt));
+    }
+    if config.graph_stitch && !cancelled.get() {
+        joins += graph_stitch_in_place_counted(dual_qt, &mut tracked);
+        on_pass("graph_stitch", PassOutput::Indexed(dual_qt));
+    }
+    (joins, ambiguities, cancelled.get())
+}
+
+/// The passes that run over the stitched paths once they are out of the
+/// index.  `epsilon` is the one the run already resolved, so that a relative
+/// epsilon isn't measured again against the bounds of the joined output.
+pub(crate) fn finish<S>(
+    mut segments: Vec<PathSegment<S>>,
+    epsilon: f32,
+    config: &PipelineConfig,
+    on_pass: &mut FnMut(&'static str, PassOutput<S>),
+) -> Vec<PathSegment<S>> {
+    if config.remove_spurs {
+        segments = remove_zero_area_spurs(segments, epsilon);
+        on_pass("remove_zero_area_spurs", PassOutput::Paths(&segments, epsilon));
+    }
+    if let Some(d) = config.density_prune {
+        segments = prune_by_density(segments, d.min_length, d.radius, d.crowd);
+        on_pass("prune_by_density", PassOutput::Paths(&segments, epsilon));
+    }
+    if let Some(direction) = config.direction {
+        segments = orient(segments, direction, config.split_to_direction);
+        on_pass("orient", PassOutput::Paths(&segments, epsilon));
+    }
+    segments
+}
+
+/// Like `run_pipeline`, but also reports what each pass did.
+pub fn run_pipeline_with_stats<P, I, S: 'static>(
+    segments: I,
+    config: &PipelineConfig,
+) -> (Vec<PathSegment<S>>, Stats)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    run_pipeline_impl(segments, config, &mut ignore_progress, &mut ignore_passes)
+}
+
+/// Like `run_pipeline`, but calls `progress` as the passes make headway.
+///
+/// Returning `ControlFlow::Break` from `progress` cancels the pipeline.  The
+/// segments that were already joined are returned along with the ones that
+/// the cancelled pass hadn't gotten to yet.
+pub fn run_pipeline_with_progress<P, I, S: 'static, F>(
+    segments: I,
+    config: &PipelineConfig,
+    mut progress: F,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+    F: FnMut(PassProgress) -> ControlFlow<()>,
+{
+    run_pipeline_impl(segments, config, &mut progress, &mut ignore_passes).0
+}
+
+/// Like `run_pipeline`, but calls `dump` with the name of each pass and an
+/// SVG rendering of the segments it left behind, as `debug_dump_tree` draws
+/// them.  The first call is for the input, named `"input"`.
+#[cfg(feature = "debug")]
+pub fn run_pipeline_with_dumps<P, I, S: 'static, F>(
+    segments: I,
+    config: &PipelineConfig,
+    mut dump: F,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+    F: FnMut(&str, String),
+{
+    let mut on_pass = |name: &'static str, output: PassOutput<S>| {
+        let svg = match output {
+            PassOutput::Indexed(dual_qt) => debug_dump_tree(dual_qt),
+            PassOutput::Paths(paths, epsilon) => debug_dump::debug_dump_paths(paths, epsilon, config.index),
+        };
+        dump(name, svg);
+    };
+    run_pipeline_impl(segments, config, &mut ignore_progress, &mut on_pass).0
+}
+
+fn run_pipeline_impl<P, I, S: 'static>(
+    segments: I,
+    config: &PipelineConfig,
+    on_progress: &mut FnMut(PassProgress) -> ControlFlow<()>,
+    on_pass: &mut FnMut(&'static str, PassOutput<S>),
+) -> (Vec<PathSegment<S>>, Stats)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let segments: Vec<Points<S>> = segments.into_iter().map(Into::into).collect();
+    let mut stats = Stats {
+        segments_in: segments.len(),
+        length_before: segments.iter().map(|s| raw_length(s)).sum(),
+        ..Stats::default()
+    };
+    let points_in: usize = segments.iter().map(|s| s.len()).sum();
+    let epsilon = resolve_epsilon(&segments, config);
+    let input = if config.debug_validate {
+        Some(segments.clone())
+    } else {
+        None
+    };
+
+    let (segments, aabb, _) = util::prepare(segments, Epsilon::Absolute(epsilon));
+    let mut dual_qt = DualQuadTree::new(aabb, epsilon, config.index);
+    for segment in segments {
+        dual_qt.insert(segment);
+    }
+    on_pass("input", PassOutput::Indexed(&dual_qt));
+    let (joins, ambiguities, cancelled) = run_passes(&mut dual_qt, config, on_progress, on_pass);
+    stats.joins = joins;
+    stats.ambiguities = ambiguities;
+
+    let out: Vec<_> = dual_qt.into_iter().collect();
+    let out = if cancelled {
+        let config = PipelineConfig {
+            remove_spurs: false,
+            density_prune: None,
+            ..*config
+        };
+        finish(out, epsilon, &config, on_pass)
+    } else {
+        finish(out, epsilon, config, on_pass)
+    };
+    // A cancelled run leaves work undone on purpose.
+    if let (Some(input), false) = (input, cancelled) {
+        debug_validate(&input, &out, epsilon, config);
+    }
+
+    let points_out: usize = out.iter().map(|s| s.path.len()).sum();
+    stats.segments_out = out.len();
+    let closings = out.iter().filter(|s| s.closed).count();
+    stats.points_dropped = points_in.saturating_sub(points_out + stats.joins + closings);
+    stats.length_after = out.iter().map(drawn_length).sum();
+    stats.pen_up_travel = out.first().map_or(0.0, |s| travel_distance(&out, s.first()));
+    if cancelled {
+        debug_event!("pipeline: cancelled");
+    }
+    debug_event!("pipeline: done, {:?}", stats);
+
+    (out, stats)
+}
+
+/// Like `run_pipeline`, but over the segments of several layers at once.
+/// Every segment comes out on the layer it went in on, and segments on
+/// different layers are never joined.
+pub(crate) fn run_pipeline_layered<P, S: 'static>(
+    layers: Vec<(LayerId, Vec<P>)>,
+    config: &PipelineConfig,
+) -> Vec<PathSegment<S>>
+where
+    P: Into<Points<S>>,
+{
+    let layers: Vec<(LayerId, Vec<Points<S>>)> = layers
+        .into_iter()
+        .map(|(layer, segments)| (layer, segments.into_iter().map(Into::into).collect()))
+        .collect();
+    let epsilon = config.epsilon.resolve_with(|| {
+        compute_bounding_box(layers.iter().flat_map(|l| l.1.iter()).flat_map(|s| s.iter().cloned()))
+    });
+
+    let mut bounds = None;
+    let mut segments = vec![];
+    for &(layer, ref input) in &layers {
+        let (kept, aabb, _) = util::prepare(input.iter().cloned(), Epsilon::Absolute(epsilon));
+        if kept.is_empty() {
+            continue;
+        }
+        bounds = Some(match bounds {
+            Some(bounds) => aabb.union(&bounds),
+            None => aabb,
+        });
+        segments.extend(kept.into_iter().map(|s| s.with_layer(layer)));
+    }
+    let bounds = match bounds {
+        Some(bounds) => bounds,
+        None => return vec![],
+    };
+
+    let mut dual_qt = DualQuadTree::new(bounds, epsilon, config.index);
+    for segment in segments {
+        dual_qt.insert(segment);
+    }
+    run_passes(&mut dual_qt, config, &mut ignore_progress, &mut ignore_passes);
+    let out = finish(dual_qt.into_iter().collect(), epsilon, config, &mut ignore_passes);
+
+    if config.debug_validate {
+        for &(layer, ref input) in &layers {
+            let on_layer: Vec<_> = out.iter().filter(|s| s.layer == layer).cloned().collect();
+            debug_validate(input, &on_layer, epsilon, config);
+        }
+    }
+    out
+}
+
+/// Runs the passes picked out by `config` over and over until they stop
+/// changing the number of segments and their total length, or until they
+/// have run `max_iters` times.
+///
+/// The spatial index is built once and reused by every iteration.
+pub fn optimize_until_stable<P, I, S: 'static>(
+    segments: I,
+    config: &PipelineConfig,
+    max_iters: usize,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let segments: Vec<Points<S>> = segments.into_iter().map(Into::into).collect();
+    let input = if config.debug_validate {
+        Some(segments.clone())
+    } else {
+        None
+    };
+
+    let epsilon = resolve_epsilon(&segments, config);
+    let mut dual_qt = DualQuadTree::from_segments_with_index(segments, epsilon, config.index);
+    let mut before = measure(&dual_qt);
+
+    for _ in 0..max_iters {
+        run_passes(&mut dual_qt, config, &mut ignore_progress, &mut ignore_passes);
+
+        // Summing in a different order can change the length slightly.
+        let after = measure(&dual_qt);
+        if after.0 == before.0 && (after.1 - before.1).abs() <= 1.0e-5 * before.1.max(1.0) {
+            break;
+        }
+        before = after;
+    }
+
+    let out = finish(dual_qt.into_iter().collect(), epsilon, config, &mut ignore_passes);
+    if let Some(input) = input {
+        debug_validate(&input, &out, epsilon, config);
+    }
+    out
+}
+
+fn measure<S: 'static>(dual_qt: &DualQuadTree<S>) -> (usize, f32) {
+    (dual_qt.len(), dual_qt.iter().map(|(_, s)| drawn_length(s)).sum())
+}
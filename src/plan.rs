@@ -0,0 +1,131 @@
+use ::*;
+use keep_out::Router;
+use stats::{drawn_length, raw_length};
+use sanitize::{is_finite, sanitize_segments};
+use travel::{apply_order, exit_point, finite_start, travel_order, travel_order_between};
+
+/// A single step of a `Plan`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Move<S> {
+    /// Draw the segment, starting at its first point.  Closed segments end
+    /// back at their first point.
+    PenDown(PathSegment<S>),
+    /// Move from the first point to the last without drawing, passing
+    /// through the points in between.
+    PenUp(Vec<Point<S>>),
+}
+
+/// An ordered list of pen moves, ready to be handed to an emitter or a
+/// simulator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan<S> {
+    /// The moves, in the order that they should be made.
+    pub moves: Vec<Move<S>>,
+}
+
+impl<S> Plan<S> {
+    /// Builds a plan that draws the segments in the order and direction that
+    /// they are given in, starting with the pen at `start`.
+    pub fn from_ordered(segments: Vec<PathSegment<S>>, start: Point<S>) -> Plan<S> {
+        Plan::build(segments, start, |from, to| vec![from, to])
+    }
+
+    /// Like `from_ordered`, but pen-up moves are routed around the closed
+    /// segments in `keep_out`.
+    ///
+    /// Moves that start or end inside of a keep-out region go in a straight
+    /// line.
+    pub fn from_ordered_avoiding(
+        segments: Vec<PathSegment<S>>,
+        start: Point<S>,
+        keep_out: &[PathSegment<S>],
+    ) -> Plan<S> {
+        let router = Router::new(keep_out);
+        Plan::build(segments, start, |from, to| router.route(from, to))
+    }
+
+    fn build<F>(segments: Vec<PathSegment<S>>, start: Point<S>, mut route: F) -> Plan<S>
+    where
+        F: FnMut(Point<S>, Point<S>) -> Vec<Point<S>>,
+    {
+        let mut moves = Vec::with_capacity(segments.len() * 2);
+        let mut position = start;
+        for segment in segments {
+            if segment.first() != position {
+                moves.push(Move::PenUp(route(position, segment.first())));
+            }
+            position = exit_point(&segment);
+            moves.push(Move::PenDown(segment));
+        }
+        Plan { moves: moves }
+    }
+
+    /// The total distance travelled with the pen up.
+    pub fn pen_up_distance(&self) -> f32 {
+        self.moves
+            .iter()
+            .map(|m| match *m {
+                Move::PenUp(ref points) => raw_length(points),
+                Move::PenDown(_) => 0.0,
+            })
+            .sum()
+    }
+
+    /// The total distance drawn, including the closing edges of closed
+    /// segments.
+    pub fn pen_down_distance(&self) -> f32 {
+        self.moves
+            .iter()
+            .map(|m| match *m {
+                Move::PenDown(ref segment) => drawn_length(segment),
+                Move::PenUp(..) => 0.0,
+            })
+            .sum()
+    }
+}
+
+/// Orders the segments like `order_travel` and returns the result as a
+/// `Plan`.
+pub fn plan_travel<S: 'static>(segments: Vec<PathSegment<S>>, start: Point<S>) -> Plan<S> {
+    let segments = sanitize_segments(segments);
+    let start = finite_start(&segments, start);
+    let order = travel_order(&segments, start);
+    Plan::from_ordered(apply_order(segments, &order), start)
+}
+
+/// Like `plan_travel`, but ends with a pen-up move to `end` when there is
+/// one, and picks the order with that move in mind.  See
+/// `order_travel_between`.
+pub fn plan_travel_between<S: 'static>(segments: Vec<PathSegment<S>>, start: Point<S>, end: Option<Point<S>>) -> Plan<S> {
+    let segments = sanitize_segments(segments);
+    let start = finite_start(&segments, start);
+    let end = end.filter(|&end| is_finite(end));
+    let order = travel_order_between(&segments, start, end);
+    let mut plan = Plan::from_ordered(apply_order(segments, &order), start);
+    if let Some(end) = end {
+        let position = plan.moves.iter().rev().filter_map(|m| match *m {
+            Move::PenDown(ref segment) => Some(exit_point(segment)),
+            Move::PenUp(..) => None,
+        }).next().unwrap_or(start);
+        if position != end {
+            plan.moves.push(Move::PenUp(vec![position, end]));
+        }
+    }
+    plan
+}
+
+/// Like `plan_travel`, but pen-up moves are routed around the closed
+/// segments in `keep_out`.
+///
+/// The order is still chosen by straight-line distance.
+pub fn plan_travel_avoiding<S: 'static>(
+    segments: Vec<PathSegment<S>>,
+    start: Point<S>,
+    keep_out: &[PathSegment<S>],
+) -> Plan<S> {
+    let segments = sanitize_segments(segments);
+    let start = finite_start(&segments, start);
+    let keep_out = sanitize_segments(keep_out.to_vec());
+    let order = travel_order(&segments, start);
+    Plan::from_ordered_avoiding(apply_order(segments, &order), start, &keep_out)
+}
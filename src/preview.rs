@@ -0,0 +1,175 @@
+use ::*;
+use util::compute_bounding_box;
+use std::io;
+
+/// How `preview` and `preview_plan` draw.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreviewOptions {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// Pixels left empty around the drawing.
+    pub margin: u32,
+    /// The color of the paper.
+    pub background: [u8; 4],
+    /// The color of lines drawn with the pen down.
+    pub pen_down: [u8; 4],
+    /// The color of pen-up moves, or `None` to leave them out.
+    pub pen_up: Option<[u8; 4]>,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> PreviewOptions {
+        PreviewOptions {
+            width: 512,
+            height: 512,
+            margin: 8,
+            background: [255, 255, 255, 255],
+            pen_down: [0, 0, 0, 255],
+            pen_up: Some([255, 0, 0, 255]),
+        }
+    }
+}
+
+/// An RGBA image, one byte per channel, row by row from the top left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    /// The width in pixels.
+    pub width: u32,
+    /// The height in pixels.
+    pub height: u32,
+    /// `width * height * 4` bytes.
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32, color: [u8; 4]) -> Image {
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for _ in 0..width as usize * height as usize {
+            rgba.extend_from_slice(&color);
+        }
+        Image {
+            width: width,
+            height: height,
+            rgba: rgba,
+        }
+    }
+
+    /// The color of the pixel at `(x, y)`.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.rgba[i], self.rgba[i + 1], self.rgba[i + 2], self.rgba[i + 3]]
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: [u8; 4]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.rgba[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// Bresenham's line between two pixels, both ends included.
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: [u8; 4]) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+        loop {
+            self.set(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * error;
+            if e2 >= dy {
+                error += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Writes the image as a PNG.
+    pub fn write_png<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        writer
+            .write_image_data(&self.rgba)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// Draws the segments, scaled to fit the image.
+pub fn preview<S>(segments: &[PathSegment<S>], options: &PreviewOptions) -> Image {
+    let strokes: Vec<_> = segments.iter().map(|s| (stroke(s), options.pen_down)).collect();
+    render(&strokes, options)
+}
+
+/// Draws the moves of `plan`, scaled to fit the image.  Pen-up moves are
+/// drawn underneath in `options.pen_up`.
+pub fn preview_plan<S>(plan: &Plan<S>, options: &PreviewOptions) -> Image {
+    let mut strokes = vec![];
+    if let Some(color) = options.pen_up {
+        for m in &plan.moves {
+            if let Move::PenUp(ref points) = *m {
+                strokes.push((points.clone(), color));
+            }
+        }
+    }
+    for m in &plan.moves {
+        if let Move::PenDown(ref segment) = *m {
+            strokes.push((stroke(segment), options.pen_down));
+        }
+    }
+    render(&strokes, options)
+}
+
+/// The points that the pen passes through while drawing `segment`.
+fn stroke<S>(segment: &PathSegment<S>) -> Vec<Point<S>> {
+    let mut points = segment.path.to_vec();
+    if segment.closed {
+        points.push(segment.first());
+    }
+    points
+}
+
+fn render<S>(strokes: &[(Vec<Point<S>>, [u8; 4])], options: &PreviewOptions) -> Image {
+    let mut image = Image::new(options.width, options.height, options.background);
+    let bounds = compute_bounding_box(strokes.iter().flat_map(|s| s.0.iter().cloned()));
+    if !(bounds.size.width >= 0.0 && bounds.size.height >= 0.0) {
+        return image;
+    }
+
+    // Keep the aspect ratio, and center the drawing in the space left over.
+    let room_x = (options.width as f32 - 2.0 * options.margin as f32 - 1.0).max(0.0);
+    let room_y = (options.height as f32 - 2.0 * options.margin as f32 - 1.0).max(0.0);
+    let scale = (room_x / bounds.size.width).min(room_y / bounds.size.height);
+    let scale = if scale.is_finite() { scale } else { 1.0 };
+    let offset_x = options.margin as f32 + (room_x - bounds.size.width * scale) / 2.0;
+    let offset_y = options.margin as f32 + (room_y - bounds.size.height * scale) / 2.0;
+    let to_pixel = |p: Point<S>| {
+        (
+            ((p.x - bounds.origin.x) * scale + offset_x).round() as i64,
+            ((p.y - bounds.origin.y) * scale + offset_y).round() as i64,
+        )
+    };
+
+    for &(ref points, color) in strokes {
+        let points: Vec<_> = points.iter().filter(|&&p| ::sanitize::is_finite(p)).collect();
+        if points.len() == 1 {
+            let (x, y) = to_pixel(*points[0]);
+            image.set(x, y, color);
+        }
+        for w in points.windows(2) {
+            image.line(to_pixel(*w[0]), to_pixel(*w[1]), color);
+        }
+    }
+    image
+}
@@ -0,0 +1,25 @@
+use std::ops::ControlFlow;
+
+/// The pass that a `PassProgress` report comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pass {
+    /// `connect_obvious`
+    ConnectObvious,
+    /// `graph_stitch`
+    GraphStitch,
+}
+
+/// A progress report from a running pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PassProgress {
+    /// The pass that is running.
+    pub pass: Pass,
+    /// How many segments the pass has dealt with so far.
+    pub done: usize,
+    /// How many segments the pass started with.
+    pub total: usize,
+}
+
+pub(crate) fn ignore_progress(_: PassProgress) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
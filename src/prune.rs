@@ -1,28 +1,102 @@
 use ::*;
 
 /// Remoes all line segments that can't possibly be part of a cycle.
-pub fn prune<P, I, S: 'static>(segments: I, epsilon: f32, only_starts: bool) -> Vec<PathSegment<S>>
+///
+/// Closed segments are looked at like any other, so one that touches nothing
+/// else is removed.  See `prune_open_in_place` for keeping them.
+pub fn prune<P, I, S: 'static, E: Into<Epsilon>>(segments: I, epsilon: E, only_starts: bool) -> Vec<PathSegment<S>>
 where
     I: IntoIterator<Item = P>,
-    P: Into<smallvec::SmallVec<[Point<S>; 2]>>,
+    P: Into<Points<S>>,
 {
-    let mut dual_qt = util::populate(segments, epsilon);
+    prune_with_index(segments, epsilon, IndexBackend::default(), only_starts)
+}
+
+/// Like `prune`, but also returns the segments that were removed, as
+/// `(kept, removed)`.
+///
+/// Segments too short to matter are dropped before pruning starts, and come
+/// first in `removed`.
+pub fn prune_with_removed<P, I, S: 'static, E: Into<Epsilon>>(
+    segments: I,
+    epsilon: E,
+    only_starts: bool,
+) -> (Vec<PathSegment<S>>, Vec<PathSegment<S>>)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let (segments, mut removed, aabb, epsilon) = util::prepare_keeping_short(segments, epsilon.into());
+    let mut dual_qt = DualQuadTree::new(aabb, epsilon, IndexBackend::default());
+    for segment in segments {
+        dual_qt.insert(segment);
+    }
+    removed.extend(prune_removing(&mut dual_qt, only_starts, false));
+    (dual_qt.into_iter().collect(), removed)
+}
+
+/// Like `prune`, but keeps the endpoints in `index`.
+pub(crate) fn prune_with_index<P, I, S: 'static, E: Into<Epsilon>>(
+    segments: I,
+    epsilon: E,
+    index: IndexBackend,
+    only_starts: bool,
+) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let mut dual_qt = DualQuadTree::from_segments_with_index(segments, epsilon, index);
+    prune_removing(&mut dual_qt, only_starts, false);
+    dual_qt.into_iter().collect()
+}
+
+/// Like `prune`, but removes segments from `dual_qt` directly.
+pub fn prune_in_place<S: 'static>(dual_qt: &mut DualQuadTree<S>, only_starts: bool) {
+    prune_removing(dual_qt, only_starts, false);
+}
 
+/// Like `prune_in_place`, but closed segments are always kept, even when
+/// nothing else touches them.  This is what the pipeline runs after
+/// `connect_obvious`, which closes the loops it finds.
+pub fn prune_open_in_place<S: 'static>(dual_qt: &mut DualQuadTree<S>, only_starts: bool) {
+    prune_removing(dual_qt, only_starts, true);
+}
+
+fn prune_removing<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    only_starts: bool,
+    keep_closed: bool,
+) -> Vec<PathSegment<S>> {
+    let epsilon = dual_qt.epsilon();
+    let mut removed = vec![];
+    let before = dual_qt.len();
+    debug_event!("prune: start, {} segments", before);
     loop {
-        let made_progress = prune_one_iter(&mut dual_qt, epsilon, only_starts);
+        let made_progress = prune_one_iter(dual_qt, epsilon, only_starts, keep_closed, &mut removed);
         if !made_progress {
             break;
         }
     }
-
-    dual_qt.into_iter().collect()
+    debug_event!("prune: done, removed {} of {} segments", before - dual_qt.len(), before);
+    removed
 }
 
-fn prune_one_iter<S: 'static>(dual_qt: &mut DualQuadTree<S>, epsilon: f32, only_starts: bool) -> bool {
+fn prune_one_iter<S: 'static>(
+    dual_qt: &mut DualQuadTree<S>,
+    epsilon: f32,
+    only_starts: bool,
+    keep_closed: bool,
+    removed: &mut Vec<PathSegment<S>>,
+) -> bool {
     let mut made_progress = false;
     let mut to_remove = vec![];
 
     for (id, path) in dual_qt.iter() {
+        // Already a cycle of its own.
+        if keep_closed && path.closed {
+            continue;
+        }
         let (start, end) = (path.first(), path.last());
 
         let a = dual_qt.has_forward_neighbor(id, start, epsilon);
@@ -33,12 +107,13 @@ fn prune_one_iter<S: 'static>(dual_qt: &mut DualQuadTree<S>, epsilon: f32, only_
 
         let should_be_kept = (a || (!only_starts && b())) && (c || (!only_starts && d()));
         if !should_be_kept {
-            to_remove.push(id);
+            to_remove.push((id, start, end));
         }
     }
 
-    for id in to_remove {
-        dual_qt.remove(id);
+    for (id, start, end) in to_remove {
+        trace_event!("prune {:?} from {:?} to {:?}", id, start, end);
+        removed.extend(dual_qt.remove(id));
         made_progress = true;
     }
 
@@ -0,0 +1,76 @@
+use ::*;
+use stats::drawn_length;
+use sanitize::is_finite;
+
+/// Redistributes the points of `segment` so that they are evenly spaced
+/// along the path, as close to `spacing` apart as possible.
+///
+/// The endpoints of open segments are kept, as is the first point of closed
+/// segments.  The shape is only followed as closely as the new spacing
+/// allows, so corners between new points are cut.  Points with a NaN or
+/// infinite coordinate are skipped.
+pub fn resample<S>(mut segment: PathSegment<S>, spacing: f32) -> PathSegment<S> {
+    assert!(spacing > 0.0);
+    if !segment.path.iter().all(|&p| is_finite(p)) {
+        segment.path.retain(|p| is_finite(*p));
+        segment.update_lengths();
+    }
+
+    let total = drawn_length(&segment);
+    if total == 0.0 {
+        return segment;
+    }
+    let (closed, layer) = (segment.closed, segment.layer);
+    let points: Points<S> = segment.into();
+
+    let min_intervals = if closed { 3 } else { 1 };
+    let intervals = ((total / spacing).round() as usize).max(min_intervals);
+    let mut out = resample_intervals(&points, total, intervals);
+    if closed {
+        out.pop();
+    }
+
+    // Resampled points are never on top of each other, so closedness is
+    // carried over rather than rediscovered.
+    let mut resampled = PathSegment::new(out, 0.0).with_layer(layer);
+    resampled.closed = closed;
+    resampled
+}
+
+/// Splits the polyline `points` of length `total` into `intervals` pieces
+/// of equal length, returning the `intervals + 1` points between them.
+pub(crate) fn resample_intervals<S>(points: &[Point<S>], total: f32, intervals: usize) -> Points<S> {
+    let step = total / intervals as f32;
+
+    let mut out: Points<S> = SmallVec::with_capacity(intervals + 1);
+    out.push(points[0]);
+
+    // `walked` is the distance along the path to the start of the edge
+    // that is currently being walked.
+    let mut walked = 0.0;
+    let mut edges = points.windows(2);
+    let mut edge = edges.next();
+    for i in 1..intervals {
+        let target = step * i as f32;
+        while let Some(e) = edge {
+            let length = (e[1] - e[0]).length();
+            if walked + length >= target {
+                break;
+            }
+            walked += length;
+            edge = edges.next();
+        }
+
+        match edge {
+            Some(e) => {
+                let length = (e[1] - e[0]).length();
+                let t = if length > 0.0 { (target - walked) / length } else { 0.0 };
+                out.push(e[0] + (e[1] - e[0]) * t);
+            }
+            None => break,
+        }
+    }
+
+    out.push(*points.last().unwrap());
+    out
+}
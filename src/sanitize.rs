@@ -0,0 +1,109 @@
+use ::*;
+
+/// What `sanitize` had to do to its input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// Number of points with a NaN or infinite coordinate that were removed.
+    pub points_dropped: usize,
+    /// Number of polylines that had to be cut in two or more pieces.
+    pub polylines_split: usize,
+    /// Number of polylines that had fewer than two points left and were
+    /// dropped entirely.
+    pub polylines_dropped: usize,
+}
+
+/// Removes points with a NaN or infinite coordinate.
+///
+/// Polylines are cut where a point is removed rather than having the points
+/// on either side joined, since nothing is known about how the line got
+/// from one to the other.  Pieces with fewer than two points are dropped.
+///
+/// Every pass does this to its input on its own, so that a single bad point
+/// can't upset the spatial index or the geometry.
+pub fn sanitize<P, I, S>(segments: I) -> (Vec<Points<S>>, SanitizeReport)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let mut report = SanitizeReport::default();
+    let mut out = vec![];
+    for segment in segments {
+        let points = segment.into();
+        if points.iter().all(|&p| is_finite(p)) {
+            if points.len() > 1 {
+                out.push(points);
+            } else {
+                report.polylines_dropped += 1;
+            }
+            continue;
+        }
+
+        let pieces_before = out.len();
+        let mut current: Points<S> = SmallVec::new();
+        for p in points {
+            if is_finite(p) {
+                current.push(p);
+                continue;
+            }
+            report.points_dropped += 1;
+            if current.len() > 1 {
+                out.push(current);
+            }
+            current = SmallVec::new();
+        }
+        if current.len() > 1 {
+            out.push(current);
+        }
+
+        match out.len() - pieces_before {
+            0 => report.polylines_dropped += 1,
+            1 => {}
+            _ => report.polylines_split += 1,
+        }
+    }
+    (out, report)
+}
+
+/// Like `sanitize`, but for segments that have already been built.  The
+/// pieces of a segment that had to be cut are open.  Segments without a bad
+/// point are passed through untouched, and so is the whole list when none of
+/// them has one.
+pub(crate) fn sanitize_segments<S>(segments: Vec<PathSegment<S>>) -> Vec<PathSegment<S>> {
+    if segments.iter().all(|s| s.path.iter().all(|&p| is_finite(p))) {
+        return segments;
+    }
+
+    let mut out = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if segment.path.iter().all(|&p| is_finite(p)) {
+            out.push(segment);
+            continue;
+        }
+        let layer = segment.layer;
+        let mut points = segment.path.into_vec();
+        if segment.closed {
+            // Start just after a bad point, so that the loop is only cut
+            // where it has to be and not at its seam as well.
+            let bad = points.iter().position(|&p| !is_finite(p)).unwrap();
+            points.rotate_left(bad + 1);
+        }
+        let (pieces, _) = sanitize(Some(points));
+        out.extend(pieces.into_iter().map(|path| {
+            let mut piece = PathSegment {
+                path: path,
+                closed: false,
+                layer: layer,
+                length_2: 0.0,
+                length: 0.0,
+            };
+            piece.update_lengths();
+            piece
+        }));
+    }
+    out
+}
+
+/// True if neither coordinate is NaN or infinite.
+pub(crate) fn is_finite<S>(p: Point<S>) -> bool {
+    p.x.is_finite() && p.y.is_finite()
+}
@@ -0,0 +1,153 @@
+use ::*;
+use stats::raw_length;
+
+// Distances along a segment are measured along the line that the pen draws,
+// so for closed segments they include the closing edge.
+
+impl<S> PathSegment<S> {
+    /// Recomputes `length` and `length_2`.  Call this after changing `path`
+    /// directly.
+    pub fn update_lengths(&mut self) {
+        let (length_2, length) = lengths(&self.path);
+        self.length_2 = length_2;
+        self.length = length;
+    }
+
+    /// Makes the segment run the other way.  Closed segments keep their
+    /// first point.
+    pub fn reverse(&mut self) {
+        self.path.reverse();
+        if self.closed {
+            self.path.rotate_right(1);
+        }
+        self.update_lengths();
+    }
+
+    /// Cuts the segment at the point at `index`, which ends up at the end of
+    /// the first piece and the start of the second.  Both pieces are open.
+    ///
+    /// Closed segments are cut open at their first point as well, so for
+    /// them `index` may also be `path.len() - 1`.  Panics if either piece
+    /// would have fewer than two points.
+    pub fn split_at_index(&self, index: usize) -> (PathSegment<S>, PathSegment<S>) {
+        let points = self.drawn_points();
+        assert!(index > 0 && index < points.len() - 1);
+        (
+            self.open(points[..index + 1].iter().cloned().collect()),
+            self.open(points[index..].iter().cloned().collect()),
+        )
+    }
+
+    /// Cuts the segment `length` along it, adding a point there if there
+    /// isn't one already.  Both pieces are open.
+    ///
+    /// Panics unless `length` is strictly between zero and the length of the
+    /// segment.
+    pub fn split_at_length(&self, length: f32) -> (PathSegment<S>, PathSegment<S>) {
+        let points = self.drawn_points();
+        assert!(length > 0.0 && length < raw_length(&points));
+        let (edge, at) = locate(&points, length);
+
+        let mut first: Points<S> = points[..edge + 1].iter().cloned().collect();
+        first.push(at);
+        let rest = if at == points[edge + 1] { edge + 2 } else { edge + 1 };
+        let mut second: Points<S> = SmallVec::with_capacity(points.len() - rest + 1);
+        second.push(at);
+        second.extend(points[rest..].iter().cloned());
+
+        (self.open(first), self.open(second))
+    }
+
+    /// Appends `other` to the end of this segment.  If `other` starts within
+    /// `epsilon` of where this segment ends, the two points are merged.
+    ///
+    /// Closed segments are opened at their first point first.  The result
+    /// is closed if its ends are within `epsilon` of each other.  A relative
+    /// `epsilon` is resolved against the bounds of both segments.
+    pub fn concat<E: Into<Epsilon>>(&self, other: &PathSegment<S>, epsilon: E) -> PathSegment<S> {
+        let mut points = self.drawn_points();
+        let tail = other.drawn_points();
+        let epsilon = epsilon.into().resolve_with(|| {
+            util::compute_bounding_box(points.iter().chain(tail.iter()).cloned())
+        });
+
+        let skip = if (tail[0] - *points.last().unwrap()).length() <= epsilon { 1 } else { 0 };
+        points.extend(tail[skip..].iter().cloned());
+        PathSegment::new(points, epsilon).with_layer(self.layer)
+    }
+
+    /// The open piece of the segment between `start_length` and
+    /// `end_length` along it.  Both are clamped to the segment.
+    ///
+    /// Panics unless `start_length` is less than `end_length`.
+    pub fn subsegment(&self, start_length: f32, end_length: f32) -> PathSegment<S> {
+        assert!(start_length < end_length);
+        let points = self.drawn_points();
+        let total = raw_length(&points);
+        let start_length = start_length.max(0.0).min(total);
+        let end_length = end_length.max(0.0).min(total);
+
+        let (start_edge, start) = locate(&points, start_length);
+        let (end_edge, end) = locate(&points, end_length);
+
+        let mut out: Points<S> = SmallVec::new();
+        out.push(start);
+        for &p in &points[start_edge + 1..end_edge + 1] {
+            if *out.last().unwrap() != p {
+                out.push(p);
+            }
+        }
+        if *out.last().unwrap() != end || out.len() == 1 {
+            out.push(end);
+        }
+        self.open(out)
+    }
+
+    /// The point `length` along the segment, clamped to the segment.
+    pub fn point_at_length(&self, length: f32) -> Point<S> {
+        let points = self.drawn_points();
+        let total = raw_length(&points);
+        locate(&points, length.max(0.0).min(total)).1
+    }
+
+    /// The points that the pen visits, including the return to the start
+    /// of closed segments.
+    fn drawn_points(&self) -> Points<S> {
+        let mut points = self.path.clone();
+        if self.closed {
+            let first = points[0];
+            points.push(first);
+        }
+        points
+    }
+
+    /// An open segment on this segment's layer that keeps every point of
+    /// `path`.
+    fn open(&self, path: Points<S>) -> PathSegment<S> {
+        let (length_2, length) = lengths(&path);
+        PathSegment {
+            path: path,
+            closed: false,
+            layer: self.layer,
+            length_2: length_2,
+            length: length,
+        }
+    }
+}
+
+/// Finds the point `length` along `points`, returning it along with the
+/// index of the edge that it lies on.  `length` must be at most the length
+/// of `points`.
+fn locate<S>(points: &[Point<S>], length: f32) -> (usize, Point<S>) {
+    let mut walked = 0.0;
+    for (i, w) in points.windows(2).enumerate() {
+        let edge = (w[1] - w[0]).length();
+        if walked + edge >= length && edge > 0.0 {
+            let t = ((length - walked) / edge).max(0.0).min(1.0);
+            return (i, w[0] + (w[1] - w[0]) * t);
+        }
+        walked += edge;
+    }
+    let last = points.len() - 1;
+    (last.saturating_sub(1), points[last])
+}
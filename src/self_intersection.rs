@@ -0,0 +1,96 @@
+use ::*;
+use util::{compute_bounding_box, crossing};
+use sanitize::sanitize_segments;
+
+type Rect<S> = euclid::TypedRect<f32, S>;
+
+/// Splits a path that crosses itself into simple pieces.
+///
+/// Every loop that the path makes between two visits to a crossing is
+/// returned as a closed segment.  If `extract_loops` is true, the rest of
+/// the path is kept in one piece with the loops cut out of it; otherwise
+/// the rest of the path is also split at every crossing.
+///
+/// A path with a NaN or infinite point is first cut there, as `sanitize`
+/// would, and each piece is split on its own.
+pub fn split_self_intersections<S: 'static, E: Into<Epsilon>>(
+    segment: PathSegment<S>,
+    extract_loops: bool,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    let epsilon = epsilon.into().resolve_with(|| compute_bounding_box(segment.path.iter().cloned()));
+    sanitize_segments(vec![segment])
+        .into_iter()
+        .flat_map(|piece| split_one(piece, extract_loops, epsilon))
+        .collect()
+}
+
+fn split_one<S: 'static>(segment: PathSegment<S>, extract_loops: bool, epsilon: f32) -> Vec<PathSegment<S>> {
+    let layer = segment.layer;
+    let points: Points<S> = segment.into();
+    let mut points = points.into_vec();
+    let mut out = vec![];
+
+    while let Some((i, j, x)) = first_crossing(&points) {
+        let mut l00p = Vec::with_capacity(j - i + 2);
+        l00p.push(x);
+        l00p.extend_from_slice(&points[i + 1..j + 1]);
+        l00p.push(x);
+        out.push(PathSegment::new(l00p, epsilon).with_layer(layer));
+
+        let mut rest = Vec::with_capacity(points.len());
+        if extract_loops {
+            rest.extend_from_slice(&points[..i + 1]);
+        } else {
+            let mut head = points[..i + 1].to_vec();
+            head.push(x);
+            if head.len() > 1 {
+                out.push(PathSegment::new(head, epsilon).with_layer(layer));
+            }
+        }
+        rest.push(x);
+        rest.extend_from_slice(&points[j + 1..]);
+        points = rest;
+    }
+
+    if points.len() > 1 {
+        out.push(PathSegment::new(points, epsilon).with_layer(layer));
+    }
+    out
+}
+
+/// Finds the first edge `j` that crosses an earlier edge `i`, preferring the
+/// latest such `i` so that the loop between them is simple.
+fn first_crossing<S: 'static>(points: &[Point<S>]) -> Option<(usize, usize, Point<S>)> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    let bounds = compute_bounding_box(points.iter().cloned());
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+    let mut tree = QuadTree::default(bounds);
+
+    for j in 0..points.len() - 1 {
+        let (b0, b1) = (points[j], points[j + 1]);
+        let rect = Rect::from_points(&[b0, b1]);
+
+        let hit = tree.query(rect)
+            .into_iter()
+            .map(|(&i, _, _)| i)
+            .filter(|&i| i + 1 < j)
+            .filter_map(|i| crossing(points[i], points[i + 1], b0, b1).map(|(t, _)| (i, t)))
+            .max_by_key(|&(i, _)| i);
+
+        if let Some((i, t)) = hit {
+            let x = points[i] + (points[i + 1] - points[i]) * t;
+            return Some((i, j, x));
+        }
+
+        tree.insert_with_box(j, rect);
+    }
+
+    None
+}
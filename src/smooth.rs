@@ -0,0 +1,48 @@
+use ::*;
+use sanitize::sanitize_segments;
+
+/// Rounds off corners with `iterations` rounds of Chaikin subdivision.
+///
+/// Every round replaces each edge with points a quarter and three quarters
+/// of the way along it.  With `pin_endpoints`, open segments keep their
+/// first and last points so that they still meet whatever they were
+/// stitched to; otherwise their ends pull in a little each round.
+pub fn smooth_chaikin<S>(segments: Vec<PathSegment<S>>, iterations: usize, pin_endpoints: bool) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    segments
+        .into_iter()
+        .map(|segment| {
+            let closed = segment.closed;
+            let mut points: Vec<Point<S>> = segment.path.to_vec();
+            for _ in 0..iterations {
+                points = chaikin(&points, closed, pin_endpoints);
+            }
+
+            let mut smoothed = PathSegment::new(points, 0.0).with_layer(segment.layer);
+            smoothed.closed = closed;
+            smoothed
+        })
+        .collect()
+}
+
+fn chaikin<S>(points: &[Point<S>], closed: bool, pin_endpoints: bool) -> Vec<Point<S>> {
+    let n = points.len();
+    if n < 3 && !(closed && n == 2) {
+        return points.to_vec();
+    }
+
+    let edges = if closed { n } else { n - 1 };
+    let mut out = Vec::with_capacity(edges * 2 + 2);
+    if !closed && pin_endpoints {
+        out.push(points[0]);
+    }
+    for i in 0..edges {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        out.push(a + (b - a) * 0.25);
+        out.push(a + (b - a) * 0.75);
+    }
+    if !closed && pin_endpoints {
+        out.push(points[n - 1]);
+    }
+    out
+}
@@ -0,0 +1,36 @@
+use ::*;
+use sanitize::sanitize_segments;
+
+/// Rounds every point to the nearest multiple of `grid` and merges points
+/// that end up on top of each other.
+///
+/// Endpoints that only differed by a little end up identical, so running
+/// this before stitching helps with data that has slightly inconsistent
+/// coordinates.  Segments that collapse to a single point are dropped, and
+/// segments whose ends meet after snapping become closed.
+pub fn snap_to_grid<S>(segments: Vec<PathSegment<S>>, grid: f32) -> Vec<PathSegment<S>> {
+    assert!(grid > 0.0);
+    let segments = sanitize_segments(segments);
+
+    let mut out = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let (closed, layer) = (segment.closed, segment.layer);
+        let points: Points<S> = segment.into();
+
+        let mut snapped: Points<S> = SmallVec::new();
+        for p in points {
+            let p = Point::new((p.x / grid).round() * grid, (p.y / grid).round() * grid);
+            if snapped.last() != Some(&p) {
+                snapped.push(p);
+            }
+        }
+
+        if snapped.len() < 2 || (closed && snapped.len() < 3) {
+            continue;
+        }
+        // Snapped points are at least `grid` apart, so half of it only
+        // merges points that are identical.
+        out.push(PathSegment::new(snapped, grid / 2.0).with_layer(layer));
+    }
+    out
+}
@@ -0,0 +1,113 @@
+use ::*;
+use fnv::FnvHashMap as HashMap;
+use sanitize::is_finite;
+use util::{compute_bounding_box, UnionFind};
+
+/// Joins a soup of two-point segments, such as the output of marching
+/// squares, into polylines.
+///
+/// Works like `connect_obvious` with `only_starts` and `allow_ambiguous`
+/// both off: segments are joined, in either direction, wherever exactly two
+/// of their ends meet.  Because every input is a single edge, the ends can
+/// be matched with a hash grid instead of quadtrees, which is a lot faster.
+/// Pairs with a non-finite coordinate or with ends within `epsilon` of each
+/// other are dropped.  A relative `epsilon` is resolved against the bounds
+/// of the pairs.
+///
+/// Unlike `connect_obvious`, ends are grouped transitively: a run of ends
+/// that are each within `epsilon` of the next counts as one meeting point,
+/// even when the ends of the run are further apart.  For input whose ends
+/// either coincide or are well apart, as marching squares gives, the two
+/// agree.
+pub fn stitch_segment_soup<S, I, E>(pairs: I, epsilon: E) -> Vec<PathSegment<S>>
+where
+    I: IntoIterator<Item = (Point<S>, Point<S>)>,
+    E: Into<Epsilon>,
+{
+    let pairs: Vec<(Point<S>, Point<S>)> = pairs
+        .into_iter()
+        .filter(|&(a, b)| is_finite(a) && is_finite(b))
+        .collect();
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| compute_bounding_box(pairs.iter().flat_map(|&(a, b)| vec![a, b])));
+    let within = |a: Point<S>, b: Point<S>| (a - b).square_length() <= epsilon * epsilon;
+    let edges: Vec<(Point<S>, Point<S>)> = pairs.into_iter().filter(|&(a, b)| !within(a, b)).collect();
+
+    // Endpoint `2 * e` is the start of edge `e` and `2 * e + 1` its end.
+    let endpoint = |i: usize| if i % 2 == 0 { edges[i / 2].0 } else { edges[i / 2].1 };
+    let cell_size = if epsilon > 0.0 { epsilon } else { 1.0 };
+    let cell_of = |p: Point<S>| ((p.x / cell_size).floor() as i64, (p.y / cell_size).floor() as i64);
+
+    // Merge every pair of endpoints within epsilon of each other.
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::default();
+    let mut sets = UnionFind::new(edges.len() * 2);
+    for i in 0..edges.len() * 2 {
+        let p = endpoint(i);
+        let (cx, cy) = cell_of(p);
+        for x in cx - 1..cx + 2 {
+            for y in cy - 1..cy + 2 {
+                if let Some(bucket) = grid.get(&(x, y)) {
+                    for &j in bucket {
+                        if within(p, endpoint(j)) {
+                            sets.union(i, j);
+                        }
+                    }
+                }
+            }
+        }
+        grid.entry((cx, cy)).or_insert_with(Vec::new).push(i);
+    }
+
+    // The endpoints incident to each merged node.
+    let mut incident: HashMap<usize, Vec<usize>> = HashMap::default();
+    for i in 0..edges.len() * 2 {
+        incident.entry(sets.find(i)).or_insert_with(Vec::new).push(i);
+    }
+    let node_of: Vec<usize> = (0..edges.len() * 2).map(|i| sets.find(i)).collect();
+    let degree = |i: usize| incident[&node_of[i]].len();
+    // The other endpoint at a node where exactly two meet.
+    let partner = |i: usize| {
+        let at = &incident[&node_of[i]];
+        if at[0] == i { at[1] } else { at[0] }
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut out = vec![];
+
+    // Walks from endpoint `start` along its edge and on through every node
+    // where only two ends meet.
+    let walk = |start: usize, used: &mut Vec<bool>| {
+        let mut points: Vec<Point<S>> = vec![endpoint(start)];
+        let mut at = start;
+        loop {
+            used[at / 2] = true;
+            let far = at ^ 1;
+            points.push(endpoint(far));
+            if degree(far) != 2 {
+                break;
+            }
+            let next = partner(far);
+            if used[next / 2] {
+                break;
+            }
+            at = next;
+        }
+        PathSegment::new(points, epsilon)
+    };
+
+    // Open chains start at nodes where something other than two ends meet.
+    for i in 0..edges.len() * 2 {
+        if !used[i / 2] && degree(i) != 2 {
+            out.push(walk(i, &mut used));
+        }
+    }
+    // Everything left is part of a loop.
+    for e in 0..edges.len() {
+        if !used[e] {
+            out.push(walk(2 * e, &mut used));
+        }
+    }
+
+    out
+}
@@ -0,0 +1,302 @@
+use ::*;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
+
+/// The spatial index that a `DualQuadTree` keeps its endpoints in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndexBackend {
+    /// A quadtree over the bounds of the input.  Copes well with endpoints
+    /// that are clustered in a few places.
+    QuadTree {
+        /// How many times a node may be split.
+        max_depth: usize,
+        /// How many items a node holds before it is split.
+        max_children: usize,
+    },
+    /// A flat hash grid.  Much faster than a quadtree when the endpoints
+    /// are spread evenly, as they are in traced rasters.
+    Grid {
+        /// The width of a cell, as a multiple of epsilon.
+        cell_size: f32,
+    },
+}
+
+impl Default for IndexBackend {
+    /// A quadtree whose nodes split once they hold 16 items, at most 8
+    /// levels deep.
+    fn default() -> IndexBackend {
+        IndexBackend::QuadTree {
+            max_depth: 8,
+            max_children: 16,
+        }
+    }
+}
+
+/// Identifies an item in an `Index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum IndexId {
+    Tree(ItemId),
+    Grid(usize),
+}
+
+/// Either of the backends, behind the part of the `QuadTree` interface
+/// that `DualQuadTree` uses.
+pub(crate) enum Index<T, S> {
+    Tree(QuadTree<T, S>),
+    Grid(Grid<T, S>),
+}
+
+impl<T, S: 'static> Index<T, S> {
+    pub(crate) fn new(backend: IndexBackend, bounds: euclid::TypedRect<f32, S>, epsilon: f32) -> Index<T, S> {
+        match backend {
+            IndexBackend::QuadTree { max_depth, max_children } => Index::Tree(QuadTree::new(
+                bounds,
+                true,
+                (max_children / 4).max(1),
+                max_children.max(1),
+                max_depth,
+            )),
+            IndexBackend::Grid { cell_size } => {
+                let mut size = cell_size * epsilon;
+                if !(size.is_finite() && size > 0.0) {
+                    // Fall back to a grid of about 64 by 64 cells.
+                    size = bounds.size.width.max(bounds.size.height) / 64.0;
+                }
+                if !(size.is_finite() && size > 0.0) {
+                    size = 1.0;
+                }
+                Index::Grid(Grid::new(size))
+            }
+        }
+    }
+
+    /// Empties the index.  A grid keeps its buffers; a quadtree is built
+    /// again for the new bounds.
+    pub(crate) fn reset(&mut self, backend: IndexBackend, bounds: euclid::TypedRect<f32, S>, epsilon: f32) {
+        if let Index::Grid(ref mut grid) = *self {
+            if let Index::Grid(fresh) = Index::<T, S>::new(backend, bounds, epsilon) {
+                grid.clear(fresh.cell_size);
+                return;
+            }
+        }
+        *self = Index::new(backend, bounds, epsilon);
+    }
+
+    pub(crate) fn insert(&mut self, item: T, rect: euclid::TypedRect<f32, S>) -> Option<IndexId> {
+        match *self {
+            Index::Tree(ref mut tree) => tree.insert_with_box(item, rect).map(IndexId::Tree),
+            Index::Grid(ref mut grid) => Some(IndexId::Grid(grid.insert(item, rect))),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: IndexId) {
+        match (self, id) {
+            (&mut Index::Tree(ref mut tree), IndexId::Tree(id)) => {
+                tree.remove(id);
+            }
+            (&mut Index::Grid(ref mut grid), IndexId::Grid(id)) => grid.remove(id),
+            _ => unreachable!("an id from one backend was used with another"),
+        }
+    }
+
+    /// The items whose boxes touch `rect`, along with those boxes.
+    pub(crate) fn query(&self, rect: euclid::TypedRect<f32, S>) -> Vec<(&T, euclid::TypedRect<f32, S>)> {
+        match *self {
+            Index::Tree(ref tree) => tree.query(rect).into_iter().map(|(t, r, _)| (t, r)).collect(),
+            Index::Grid(ref grid) => grid.query(rect),
+        }
+    }
+
+    /// Calls `f` with the bounds of every node of the tree, or of every
+    /// occupied cell of the grid.
+    pub(crate) fn inspect<F: FnMut(euclid::TypedRect<f32, S>)>(&self, mut f: F) {
+        match *self {
+            Index::Tree(ref tree) => tree.inspect(|rect, _, _| f(rect)),
+            Index::Grid(ref grid) => grid.inspect(f),
+        }
+    }
+}
+
+type Cell = (i64, i64);
+
+/// Items whose box covers more cells than this are kept in a list of their
+/// own instead of in every cell.
+const MAX_CELLS_PER_ITEM: i64 = 64;
+
+/// A hash grid of square cells.  An item is stored in every cell its box
+/// touches.
+pub(crate) struct Grid<T, S> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<usize>>,
+    items: Vec<Option<(T, euclid::TypedRect<f32, S>)>>,
+    free: Vec<usize>,
+    /// The items that cover too many cells to be stored in each of them.
+    wide: Vec<usize>,
+    /// The lowest and highest cell that has held an item since the grid was
+    /// last cleared.  Queries never look outside of it.
+    extent: Option<(Cell, Cell)>,
+}
+
+impl<T, S> Grid<T, S> {
+    fn new(cell_size: f32) -> Grid<T, S> {
+        Grid {
+            cell_size: cell_size,
+            cells: HashMap::default(),
+            items: vec![],
+            free: vec![],
+            wide: vec![],
+            extent: None,
+        }
+    }
+
+    fn clear(&mut self, cell_size: f32) {
+        self.cell_size = cell_size;
+        self.cells.clear();
+        self.items.clear();
+        self.free.clear();
+        self.wide.clear();
+        self.extent = None;
+    }
+
+    /// The lowest and highest cell that `rect` touches.  Coordinates too
+    /// large for a cell index are clamped to the largest one.
+    fn cell_range(&self, rect: &euclid::TypedRect<f32, S>) -> (Cell, Cell) {
+        let cell = |x: f32, y: f32| ((x / self.cell_size).floor() as i64, (y / self.cell_size).floor() as i64);
+        (
+            cell(rect.origin.x, rect.origin.y),
+            cell(rect.max_x(), rect.max_y()),
+        )
+    }
+
+    fn insert(&mut self, item: T, rect: euclid::TypedRect<f32, S>) -> usize {
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.items[id] = Some((item, rect));
+                id
+            }
+            None => {
+                self.items.push(Some((item, rect)));
+                self.items.len() - 1
+            }
+        };
+
+        let (low, high) = self.cell_range(&rect);
+        if cell_count(low, high) > MAX_CELLS_PER_ITEM {
+            self.wide.push(id);
+            return id;
+        }
+
+        self.extent = Some(match self.extent {
+            Some((l, h)) => ((l.0.min(low.0), l.1.min(low.1)), (h.0.max(high.0), h.1.max(high.1))),
+            None => (low, high),
+        });
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                self.cells.entry((x, y)).or_insert_with(Vec::new).push(id);
+            }
+        }
+        id
+    }
+
+    fn remove(&mut self, id: usize) {
+        let rect = match self.items[id].take() {
+            Some((_, rect)) => rect,
+            None => return,
+        };
+        self.free.push(id);
+
+        let (low, high) = self.cell_range(&rect);
+        if cell_count(low, high) > MAX_CELLS_PER_ITEM {
+            self.wide.retain(|&other| other != id);
+            return;
+        }
+
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                let now_empty = match self.cells.get_mut(&(x, y)) {
+                    Some(bucket) => {
+                        bucket.retain(|&other| other != id);
+                        bucket.is_empty()
+                    }
+                    None => false,
+                };
+                if now_empty {
+                    self.cells.remove(&(x, y));
+                }
+            }
+        }
+    }
+
+    /// Looks at the cells that `rect` touches, or at every occupied cell
+    /// when there are fewer of those, so a wide query costs no more than
+    /// going through all of the items.
+    fn query(&self, rect: euclid::TypedRect<f32, S>) -> Vec<(&T, euclid::TypedRect<f32, S>)> {
+        // Items that span several cells would otherwise be found more than
+        // once.
+        let mut seen = HashSet::default();
+        let mut out = vec![];
+        self.collect(&self.wide, &rect, &mut seen, &mut out);
+
+        let (low, high) = self.cell_range(&rect);
+        let (low, high) = match self.extent {
+            Some((l, h)) => ((low.0.max(l.0), low.1.max(l.1)), (high.0.min(h.0), high.1.min(h.1))),
+            None => return out,
+        };
+        if cell_count(low, high) > self.cells.len() as i64 {
+            for (&(x, y), bucket) in &self.cells {
+                if low.0 <= x && x <= high.0 && low.1 <= y && y <= high.1 {
+                    self.collect(bucket, &rect, &mut seen, &mut out);
+                }
+            }
+        } else {
+            for x in low.0..=high.0 {
+                for y in low.1..=high.1 {
+                    if let Some(bucket) = self.cells.get(&(x, y)) {
+                        self.collect(bucket, &rect, &mut seen, &mut out);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Pushes the items in `ids` whose boxes touch `rect` onto `out`,
+    /// skipping those in `seen`.
+    fn collect<'a>(
+        &'a self,
+        ids: &[usize],
+        rect: &euclid::TypedRect<f32, S>,
+        seen: &mut HashSet<usize>,
+        out: &mut Vec<(&'a T, euclid::TypedRect<f32, S>)>,
+    ) {
+        for &id in ids {
+            let (ref item, item_rect) = *self.items[id].as_ref().unwrap();
+            let touches = item_rect.origin.x <= rect.max_x()
+                && rect.origin.x <= item_rect.max_x()
+                && item_rect.origin.y <= rect.max_y()
+                && rect.origin.y <= item_rect.max_y();
+            if touches && seen.insert(id) {
+                out.push((item, item_rect));
+            }
+        }
+    }
+
+    fn inspect<F: FnMut(euclid::TypedRect<f32, S>)>(&self, mut f: F) {
+        for &(x, y) in self.cells.keys() {
+            f(euclid::TypedRect::new(
+                euclid::point2(x as f32 * self.cell_size, y as f32 * self.cell_size),
+                euclid::size2(self.cell_size, self.cell_size),
+            ));
+        }
+    }
+}
+
+/// How many cells there are from `low` to `high`, saturating rather than
+/// overflowing.
+fn cell_count(low: Cell, high: Cell) -> i64 {
+    if high.0 < low.0 || high.1 < low.1 {
+        return 0;
+    }
+    let width = high.0.saturating_sub(low.0).saturating_add(1);
+    let height = high.1.saturating_sub(low.1).saturating_add(1);
+    width.saturating_mul(height)
+}
@@ -0,0 +1,61 @@
+use ::*;
+use std::mem;
+use sanitize::sanitize_segments;
+
+/// Splits segments into pieces no longer than `max_length`, cutting only at
+/// existing vertices.  A single edge that is longer than `max_length` ends
+/// up in a piece of its own.
+///
+/// Closed segments that need to be split are cut into open pieces.
+pub fn split_max_length<S, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    max_length: f32,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+    split_where(segments, epsilon, |_, length| length > max_length)
+}
+
+/// Splits segments into pieces that have at most `max_points` points.
+///
+/// Closed segments that need to be split are cut into open pieces.
+pub fn split_max_points<S, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    max_points: usize,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    assert!(max_points > 1);
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+    split_where(segments, epsilon, |points, _| points > max_points)
+}
+
+/// Cuts a piece whenever adding the next point would make `too_long`
+/// return true for the piece's point count and length.
+fn split_where<S, F>(segments: Vec<PathSegment<S>>, epsilon: f32, mut too_long: F) -> Vec<PathSegment<S>>
+where
+    F: FnMut(usize, f32) -> bool,
+{
+    let mut out = vec![];
+    for segment in segments {
+        let layer = segment.layer;
+        let points: Points<S> = segment.into();
+        let mut piece = vec![points[0]];
+        let mut length = 0.0;
+
+        for &p in &points[1..] {
+            let last = *piece.last().unwrap();
+            let edge = (p - last).length();
+            if piece.len() > 1 && too_long(piece.len() + 1, length + edge) {
+                out.push(PathSegment::new(mem::replace(&mut piece, vec![last]), epsilon).with_layer(layer));
+                length = 0.0;
+            }
+            piece.push(p);
+            length += edge;
+        }
+
+        out.push(PathSegment::new(piece, epsilon).with_layer(layer));
+    }
+    out
+}
@@ -0,0 +1,40 @@
+use ::*;
+
+/// Counters describing what a run of the pipeline did to its input.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Number of segments given to the pipeline.
+    pub segments_in: usize,
+    /// Number of segments produced by the pipeline.
+    pub segments_out: usize,
+    /// Number of times two segments were joined end to end.
+    pub joins: usize,
+    /// Number of input points that are missing from the output.  Where two
+    /// segments were joined, or where a loop closes, the two points that met
+    /// are kept as one and neither counts as dropped.
+    pub points_dropped: usize,
+    /// Total length of the input segments.
+    pub length_before: f32,
+    /// Total length of the output segments, including closing edges.
+    pub length_after: f32,
+    /// Number of points where more than one join was possible.
+    pub ambiguities: usize,
+    /// Pen-up travel when drawing the output in order, starting where the
+    /// first segment starts.
+    pub pen_up_travel: f32,
+}
+
+/// The length drawn by the pen for `segment`, including the closing edge
+/// of closed segments.
+pub fn drawn_length<S>(segment: &PathSegment<S>) -> f32 {
+    let closing = if segment.closed {
+        (segment.first() - segment.last()).length()
+    } else {
+        0.0
+    };
+    segment.length() + closing
+}
+
+pub(crate) fn raw_length<S>(points: &[Point<S>]) -> f32 {
+    points.windows(2).map(|s| (s[1] - s[0]).length()).sum()
+}
@@ -0,0 +1,233 @@
+use ::*;
+use util::{centered_with_radius, compute_bounding_box};
+use std::cmp::Ordering;
+use sanitize::sanitize_segments;
+
+type Rect<S> = euclid::TypedRect<f32, S>;
+
+/// Where a loose end meets the body of a segment.
+struct Foot<S> {
+    edge: usize,
+    t: f32,
+    point: Point<S>,
+}
+
+/// Extends loose ends that stop short of the body of another segment, so
+/// that they meet it in a T-junction.
+///
+/// An end of an open segment that isn't already joined to another end is
+/// moved or extended to the nearest point on any edge within `max_gap` of
+/// it.  The segment it lands on is split there when it is open; a closed
+/// segment stays closed and gains a corner at that point instead.  Feet that
+/// fall within `epsilon` of an existing corner use that corner, and ends
+/// that would land on the end of an open segment are left to the stitching
+/// passes.
+pub fn snap_to_boundaries<S: 'static, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    max_gap: f32,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+    if segments.is_empty() || !(max_gap > 0.0) {
+        return segments;
+    }
+
+    let bounds = compute_bounding_box(segments.iter().flat_map(|s| s.path.iter().cloned()));
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0) + max_gap,
+        1.0f32.max(bounds.size.height / 10.0) + max_gap,
+    );
+    let mut edges = QuadTree::default(bounds);
+    let mut ends = QuadTree::default(bounds);
+    for (i, segment) in segments.iter().enumerate() {
+        for j in 0..edge_count(segment) {
+            let (a, b) = edge(segment, j);
+            edges.insert_with_box((i, j), Rect::from_points(&[a, b]));
+        }
+        if !segment.closed {
+            for &p in &[segment.first(), segment.last()] {
+                ends.insert_with_box(i, p.aabb());
+            }
+        }
+    }
+
+    // Work out every snap against the original geometry before changing
+    // anything.
+    let mut extend_start: Vec<Option<Point<S>>> = vec![None; segments.len()];
+    let mut extend_end: Vec<Option<Point<S>>> = vec![None; segments.len()];
+    let mut cuts: Vec<Vec<Foot<S>>> = segments.iter().map(|_| vec![]).collect();
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.closed {
+            continue;
+        }
+        for &at_end in &[false, true] {
+            let point = if at_end { segment.last() } else { segment.first() };
+            let joined = ends
+                .query(centered_with_radius(point, epsilon))
+                .into_iter()
+                .filter(|&(&other, rect, _)| other != i && (rect.origin - point).length() <= epsilon)
+                .next()
+                .is_some();
+            if joined {
+                continue;
+            }
+
+            let skip = own_edges_near_end(segment, at_end, max_gap);
+            let nearest = edges
+                .query(centered_with_radius(point, max_gap))
+                .into_iter()
+                .filter(|&(&(j, k), _, _)| j != i || !skip(k))
+                .map(|(&(j, k), _, _)| {
+                    let (a, b) = edge(&segments[j], k);
+                    let (t, foot) = project(point, a, b);
+                    (j, k, t, foot, (foot - point).length())
+                })
+                .filter(|&(j, _, _, foot, distance)| distance <= max_gap && !on_loose_end(&segments[j], foot, epsilon))
+                .min_by(|a, b| a.4.partial_cmp(&b.4).unwrap_or(Ordering::Equal));
+
+            if let Some((j, k, t, foot, _)) = nearest {
+                if at_end {
+                    extend_end[i] = Some(foot);
+                } else {
+                    extend_start[i] = Some(foot);
+                }
+                cuts[j].push(Foot { edge: k, t: t, point: foot });
+            }
+        }
+    }
+
+    let mut out = vec![];
+    for (i, segment) in segments.into_iter().enumerate() {
+        let (closed, layer) = (segment.closed, segment.layer);
+        let mut pieces = cut(segment, &mut cuts[i], epsilon);
+        if let Some(foot) = extend_start[i] {
+            extend(&mut pieces[0], foot, false, epsilon);
+        }
+        if let Some(foot) = extend_end[i] {
+            extend(pieces.last_mut().unwrap(), foot, true, epsilon);
+        }
+        for piece in pieces {
+            let mut piece = PathSegment::new(piece, 0.0).with_layer(layer);
+            piece.closed = closed;
+            out.push(piece);
+        }
+    }
+    out
+}
+
+fn edge_count<S>(segment: &PathSegment<S>) -> usize {
+    if segment.closed {
+        segment.path.len()
+    } else {
+        segment.path.len() - 1
+    }
+}
+
+fn edge<S>(segment: &PathSegment<S>, j: usize) -> (Point<S>, Point<S>) {
+    let path = &segment.path;
+    (path[j], path[(j + 1) % path.len()])
+}
+
+/// Ends meeting ends are left to the stitching passes.
+fn on_loose_end<S>(segment: &PathSegment<S>, point: Point<S>, epsilon: f32) -> bool {
+    !segment.closed
+        && ((segment.first() - point).length() <= epsilon || (segment.last() - point).length() <= epsilon)
+}
+
+/// The edges of `segment` that run from the end up to the first corner
+/// more than `max_gap` away from it.  An end is always close to these, so
+/// snapping to them would only fold the segment back onto itself.
+fn own_edges_near_end<S>(segment: &PathSegment<S>, at_end: bool, max_gap: f32) -> impl Fn(usize) -> bool {
+    let path = &segment.path;
+    let n = path.len();
+    let point = if at_end { path[n - 1] } else { path[0] };
+    let mut count = 1;
+    while count < n - 1 {
+        let corner = if at_end { path[n - 1 - count] } else { path[count] };
+        if (corner - point).length() > max_gap {
+            break;
+        }
+        count += 1;
+    }
+    move |k| if at_end { k >= n - 1 - count } else { k < count }
+}
+
+/// The closest point to `p` on the edge from `a` to `b`, and how far along
+/// the edge it is.
+fn project<S>(p: Point<S>, a: Point<S>, b: Point<S>) -> (f32, Point<S>) {
+    let ab = b - a;
+    let length_2 = ab.square_length();
+    let t = if length_2 > 0.0 {
+        ((p - a).dot(ab) / length_2).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+    (t, a + ab * t)
+}
+
+/// Adds the feet as corners and, for open segments, splits there.
+fn cut<S>(segment: PathSegment<S>, feet: &mut Vec<Foot<S>>, epsilon: f32) -> Vec<Vec<Point<S>>> {
+    let closed = segment.closed;
+    let path: Vec<Point<S>> = segment.path.to_vec();
+    if feet.is_empty() {
+        return vec![path];
+    }
+    feet.sort_by(|a, b| (a.edge, a.t).partial_cmp(&(b.edge, b.t)).unwrap_or(Ordering::Equal));
+
+    // Feet right next to a corner use the corner, the rest become new
+    // corners of their own.
+    let n = path.len();
+    let mut on_corners = vec![];
+    let mut points = vec![];
+    let mut corner_at = Vec::with_capacity(n);
+    let mut at = vec![];
+    let mut feet = feet.iter().peekable();
+    for j in 0..n {
+        points.push(path[j]);
+        corner_at.push(points.len() - 1);
+        while let Some(foot) = feet.peek().filter(|f| f.edge == j).map(|f| f.point) {
+            feet.next();
+            let next = (j + 1) % n;
+            if (foot - path[j]).length() <= epsilon {
+                on_corners.push(j);
+            } else if (foot - path[next]).length() <= epsilon {
+                on_corners.push(next);
+            } else if (foot - *points.last().unwrap()).length() <= epsilon {
+                at.push(points.len() - 1);
+            } else {
+                points.push(foot);
+                at.push(points.len() - 1);
+            }
+        }
+    }
+    at.extend(on_corners.into_iter().map(|j| corner_at[j]));
+    at.sort();
+    at.dedup();
+
+    if closed {
+        return vec![points];
+    }
+
+    let mut pieces = vec![];
+    let mut from = 0;
+    for k in at {
+        if k > from && k < points.len() - 1 {
+            pieces.push(points[from..k + 1].to_vec());
+            from = k;
+        }
+    }
+    pieces.push(points[from..].to_vec());
+    pieces
+}
+
+fn extend<S>(points: &mut Vec<Point<S>>, foot: Point<S>, at_end: bool, epsilon: f32) {
+    let index = if at_end { points.len() - 1 } else { 0 };
+    if (points[index] - foot).length() <= epsilon {
+        points[index] = foot;
+    } else if at_end {
+        points.push(foot);
+    } else {
+        points.insert(0, foot);
+    }
+}
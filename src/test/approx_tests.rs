@@ -0,0 +1,79 @@
+use euclid::{UnknownUnit, point2};
+use std::collections::HashSet;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn triangle(start: usize, reversed: bool, noise: f32) -> PathSegment {
+    let mut points = vec![point2(0.0, 0.0), point2(1.0 + noise, 0.0), point2(0.0, 1.0)];
+    if reversed {
+        points.reverse();
+    }
+    points.rotate_left(start);
+    let first = points[0];
+    points.push(first);
+    PathSegment::new(points, EPSILON)
+}
+
+#[test]
+fn closed_segments_ignore_rotation_and_direction() {
+    let a = triangle(0, false, 0.0);
+    for &reversed in &[false, true] {
+        for start in 0..3 {
+            let b = triangle(start, reversed, 0.0001);
+            assert!(a.approx_eq(&b, EPSILON));
+            assert!(!a.approx_eq(&b, 0.00001));
+        }
+    }
+}
+
+#[test]
+fn open_segments_ignore_direction_only() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(1.0, 1.0), point2(1.0, 0.0), point2(0.0, 0.0)], EPSILON);
+    let c = PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 0.0)], EPSILON);
+    assert!(a.approx_eq(&b, EPSILON));
+    assert!(!a.approx_eq(&c, EPSILON));
+}
+
+#[test]
+fn keys_dedupe_equivalent_segments() {
+    let mut keys = HashSet::new();
+    for &reversed in &[false, true] {
+        for start in 0..3 {
+            keys.insert(triangle(start, reversed, 0.0001).key(0.01));
+        }
+    }
+    assert_eq!(keys.len(), 1);
+
+    keys.insert(triangle(0, false, 0.5).key(0.01));
+    assert_eq!(keys.len(), 2);
+}
+
+/// A zigzag loop that visits the same few rows over and over, so that many
+/// rotations start out alike.
+fn zigzag(len: usize, start: usize, reversed: bool) -> PathSegment {
+    let mut points: Vec<_> = (0..len)
+        .map(|i| point2(i as f32, [0.0, 1.0, 0.0, 2.0][i % 4]))
+        .chain((0..len).rev().map(|i| point2(i as f32, -1.0)))
+        .collect();
+    if reversed {
+        points.reverse();
+    }
+    points.rotate_left(start);
+    let mut segment = PathSegment::new(points, EPSILON);
+    segment.closed = true;
+    segment
+}
+
+#[test]
+fn keys_of_long_loops_ignore_rotation_and_direction() {
+    let len = 20_000;
+    let key = zigzag(len, 0, false).key(0.01);
+    for &reversed in &[false, true] {
+        for &start in &[1, 7, len, 2 * len - 1] {
+            assert_eq!(zigzag(len, start, reversed).key(0.01), key);
+        }
+    }
+}
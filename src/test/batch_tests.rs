@@ -0,0 +1,98 @@
+use ::{run_pipeline, BatchProcessor, IndexBackend, Move, PipelineConfig};
+use euclid::{UnknownUnit, point2};
+
+type Point = ::Point<UnknownUnit>;
+
+fn drawings() -> Vec<Vec<Vec<Point>>> {
+    vec![
+        vec![
+            vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+            vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+            vec![point2(2.0, 0.0), point2(3.0, 0.0)],
+        ],
+        vec![
+            vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+            vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+            vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+            vec![point2(50.0, 50.0), point2(60.0, 50.0)],
+        ],
+        vec![],
+        vec![
+            vec![point2(0.0, 0.0), point2(2.0, 0.0)],
+            vec![point2(2.0, 0.0), point2(2.0, 2.0)],
+            vec![point2(2.0, 2.0), point2(0.0, 2.0)],
+            vec![point2(0.0, 2.0), point2(0.0, 0.0)],
+            vec![point2(2.0, 0.0), point2(0.0, 2.0)],
+            vec![point2(0.0, 2.0), point2(2.0, 0.0)],
+            vec![point2(2.0, 2.0), point2(5.0, 5.0)],
+        ],
+        vec![
+            vec![point2(-100.0, -100.0), point2(-90.0, -100.0)],
+            vec![point2(-80.0, -100.0), point2(-90.0, -100.0)],
+        ],
+    ]
+}
+
+fn summary(segments: &[::PathSegment<UnknownUnit>]) -> (usize, usize, f32) {
+    let closed = segments.iter().filter(|s| s.closed).count();
+    let length = segments.iter().map(|s| s.length()).sum();
+    (segments.len(), closed, length)
+}
+
+fn matches_run_pipeline_with(config: PipelineConfig) {
+    for &index in &[IndexBackend::default(), IndexBackend::Grid { cell_size: 4.0 }] {
+        let config = PipelineConfig {
+            index: index,
+            debug_validate: true,
+            ..config
+        };
+        let mut batch = BatchProcessor::new(config);
+        let out = batch.process_all(drawings());
+        assert_eq!(out.len(), drawings().len());
+        for (drawing, out) in drawings().into_iter().zip(out) {
+            let expected = run_pipeline(drawing, &config);
+            let (got, want) = (summary(&out), summary(&expected));
+            assert_eq!((got.0, got.1), (want.0, want.1));
+            assert!((got.2 - want.2).abs() < 1.0e-4);
+        }
+    }
+}
+
+#[test]
+fn matches_run_pipeline() {
+    matches_run_pipeline_with(PipelineConfig::default());
+}
+
+#[test]
+fn matches_run_pipeline_with_every_pass() {
+    matches_run_pipeline_with(PipelineConfig {
+        prune: true,
+        graph_stitch: true,
+        remove_spurs: true,
+        ..PipelineConfig::default()
+    });
+}
+
+#[test]
+fn nothing_carries_over_between_drawings() {
+    let mut batch = BatchProcessor::new(PipelineConfig::default());
+    let first = batch.process(vec![vec![point2(0.0, 0.0), point2(1.0, 0.0)]]);
+    let second: Vec<Vec<Point>> = vec![vec![point2(1.0, 0.0), point2(2.0, 0.0)]];
+    let second = batch.process(second);
+    assert_eq!(first.len(), 1);
+    assert_eq!(second.len(), 1);
+    assert_eq!(&second[0].path[..], &[point2(1.0, 0.0), point2(2.0, 0.0)][..]);
+}
+
+#[test]
+fn plans_end_at_the_travel_end() {
+    let end = point2(10.0, 10.0);
+    let mut batch = BatchProcessor::new(PipelineConfig::default()).with_travel(point2(0.0, 0.0), Some(end));
+    for drawing in drawings().into_iter().filter(|d| !d.is_empty()) {
+        let plan = batch.plan(drawing);
+        match plan.moves.last() {
+            Some(&Move::PenUp(ref points)) => assert_eq!(*points.last().unwrap(), end),
+            other => panic!("expected a final pen-up move, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use ::{difference, intersection, union, FillRule};
+use util::area;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn square(x: f32, y: f32, size: f32) -> PathSegment {
+    PathSegment::new(
+        vec![
+            point2(x, y),
+            point2(x + size, y),
+            point2(x + size, y + size),
+            point2(x, y + size),
+            point2(x, y),
+        ],
+        EPSILON,
+    )
+}
+
+fn total_area(segments: &[PathSegment]) -> f32 {
+    segments.iter().map(|s| area(&s.path).abs() / 2.0).sum()
+}
+
+/// Holes count against the total.
+fn signed_area(segments: &[PathSegment]) -> f32 {
+    segments.iter().map(|s| area(&s.path) / 2.0).sum()
+}
+
+#[test]
+fn overlapping_squares() {
+    let a = [square(0.0, 0.0, 2.0)];
+    let b = [square(1.0, 1.0, 2.0)];
+
+    let u = union(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(u.len(), 1);
+    assert!(u[0].closed);
+    assert!((total_area(&u) - 7.0).abs() < 1.0e-4);
+
+    let i = intersection(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(i.len(), 1);
+    assert!((total_area(&i) - 1.0).abs() < 1.0e-4);
+
+    let d = difference(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(d.len(), 1);
+    assert!((total_area(&d) - 3.0).abs() < 1.0e-4);
+}
+
+#[test]
+fn disjoint_squares() {
+    let a = [square(0.0, 0.0, 1.0)];
+    let b = [square(5.0, 5.0, 1.0)];
+
+    assert_eq!(union(&a, &b, FillRule::NonZero, EPSILON).len(), 2);
+    assert!(intersection(&a, &b, FillRule::NonZero, EPSILON).is_empty());
+    assert_eq!(difference(&a, &b, FillRule::NonZero, EPSILON), a.to_vec());
+}
+
+#[test]
+fn nested_squares() {
+    let outer = [square(0.0, 0.0, 4.0)];
+    let inner = [square(1.0, 1.0, 1.0)];
+
+    assert_eq!(union(&outer, &inner, FillRule::NonZero, EPSILON), outer.to_vec());
+    assert_eq!(intersection(&outer, &inner, FillRule::NonZero, EPSILON), inner.to_vec());
+    // The inner square becomes a hole.
+    assert_eq!(difference(&outer, &inner, FillRule::NonZero, EPSILON).len(), 2);
+    assert!(difference(&inner, &outer, FillRule::NonZero, EPSILON).is_empty());
+}
+
+#[test]
+fn holes_survive_a_union() {
+    let outer = [square(0.0, 0.0, 4.0)];
+    let inner = [square(1.0, 1.0, 1.0)];
+    let holed = difference(&outer, &inner, FillRule::NonZero, EPSILON);
+    assert!((signed_area(&holed) - 15.0).abs() < 1.0e-4);
+
+    for &fill in &[FillRule::NonZero, FillRule::EvenOdd] {
+        let u = union(&holed, &[square(10.0, 10.0, 1.0)], fill, EPSILON);
+        assert_eq!(u.len(), 3);
+        assert!((signed_area(&u) - 16.0).abs() < 1.0e-4);
+
+        let u = union(&holed, &holed, fill, EPSILON);
+        assert_eq!(u.len(), 2);
+        assert!((signed_area(&u) - 15.0).abs() < 1.0e-4);
+    }
+}
+
+#[test]
+fn fill_rule_decides_nested_rings() {
+    // Both squares run the same way.
+    let nested = [square(0.0, 0.0, 4.0), square(1.0, 1.0, 1.0)];
+
+    let even_odd = union(&nested, &[], FillRule::EvenOdd, EPSILON);
+    assert_eq!(even_odd.len(), 2);
+    assert!((signed_area(&even_odd) - 15.0).abs() < 1.0e-4);
+
+    let non_zero = union(&nested, &[], FillRule::NonZero, EPSILON);
+    assert_eq!(non_zero, vec![nested[0].clone()]);
+}
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> PathSegment {
+    PathSegment::new(
+        vec![point2(x0, y0), point2(x1, y0), point2(x1, y1), point2(x0, y1), point2(x0, y0)],
+        EPSILON,
+    )
+}
+
+#[test]
+fn squares_sharing_a_corner() {
+    let a = [square(0.0, 0.0, 1.0)];
+    let b = [square(1.0, 1.0, 1.0)];
+
+    assert!((signed_area(&union(&a, &b, FillRule::NonZero, EPSILON)) - 2.0).abs() < 1.0e-4);
+    assert!(intersection(&a, &b, FillRule::NonZero, EPSILON).is_empty());
+    assert!((signed_area(&difference(&a, &b, FillRule::NonZero, EPSILON)) - 1.0).abs() < 1.0e-4);
+}
+
+#[test]
+fn corners_on_an_edge_cut_it() {
+    // The corners of `b` at (2, 0) and (2, 1) lie on the right edge of `a`.
+    let a = [square(0.0, 0.0, 2.0)];
+    let b = [rect(2.0, 0.0, 4.0, 1.0)];
+
+    let u = union(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(u.len(), 1);
+    assert!((signed_area(&u) - 6.0).abs() < 1.0e-4);
+    assert!(intersection(&a, &b, FillRule::NonZero, EPSILON).is_empty());
+    assert!((signed_area(&difference(&a, &b, FillRule::NonZero, EPSILON)) - 4.0).abs() < 1.0e-4);
+
+    // A corner in the middle of an edge, with nothing else touching.
+    let notch = [PathSegment::new(
+        vec![point2(2.0, 1.0), point2(3.0, 0.5), point2(3.0, 1.5), point2(2.0, 1.0)],
+        EPSILON,
+    )];
+    let u = union(&a, &notch, FillRule::NonZero, EPSILON);
+    assert!((signed_area(&u) - 4.5).abs() < 1.0e-4);
+}
+
+#[test]
+fn overlapping_edges() {
+    // The top and bottom edges of the two overlap from x = 1 to x = 2.
+    let a = [square(0.0, 0.0, 2.0)];
+    let b = [square(1.0, 0.0, 2.0)];
+
+    let u = union(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(u.len(), 1);
+    assert!((signed_area(&u) - 6.0).abs() < 1.0e-4);
+
+    let i = intersection(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(i.len(), 1);
+    assert!((signed_area(&i) - 2.0).abs() < 1.0e-4);
+
+    let d = difference(&a, &b, FillRule::NonZero, EPSILON);
+    assert_eq!(d.len(), 1);
+    assert!((signed_area(&d) - 2.0).abs() < 1.0e-4);
+
+    let same = union(&a, &a, FillRule::NonZero, EPSILON);
+    assert_eq!(same.len(), 1);
+    assert!((signed_area(&same) - 4.0).abs() < 1.0e-4);
+}
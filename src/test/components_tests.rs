@@ -0,0 +1,32 @@
+use ::components;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn touching_segments_are_grouped() {
+    let segments = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(5.0, 5.0), point2(6.0, 5.0)], EPSILON),
+        PathSegment::new(vec![point2(2.0, 0.0), point2(1.0, 1.0)], EPSILON),
+        PathSegment::new(vec![point2(1.0, 1.0), point2(1.0, 0.0005)], EPSILON),
+        PathSegment::new(
+            vec![point2(5.0, 5.0), point2(5.0, 6.0), point2(4.0, 6.0), point2(5.0, 5.0)],
+            EPSILON,
+        ),
+    ];
+
+    let groups = components(segments, EPSILON);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].len(), 3);
+    assert_eq!(groups[0][1].first(), point2(2.0, 0.0));
+    assert_eq!(groups[1].len(), 2);
+    assert!(groups[1][1].closed);
+}
+
+#[test]
+fn no_segments_no_groups() {
+    assert!(components(Vec::<PathSegment>::new(), EPSILON).is_empty());
+}
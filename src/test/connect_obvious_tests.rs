@@ -1,5 +1,5 @@
 use super::util::*;
-use ::connect_obvious;
+use ::{connect_obvious, connect_obvious_ordered, SeedOrder};
 use permutohedron::heap_recursive as permute;
 use euclid::{UnknownUnit, point2};
 
@@ -211,5 +211,33 @@ fn doesnt_continue_on_ambiguities() {
     run(problem);
 }
 
+/// A tiny spur and a long stroke both continue a long stroke; whichever
+/// seed goes first decides which of them it is joined to.
+fn spur() -> Vec<Vec<Point>> {
+    vec![
+        vec![point2(10.0005, 0.0), point2(10.0, 0.5)],
+        vec![point2(10.0, 0.0), point2(20.0, 0.0)],
+        vec![point2(0.0, 0.0), point2(10.0, 0.0)],
+    ]
+}
+
+fn longest(output: &[PathSegment]) -> f32 {
+    output.iter().map(|s| s.length()).fold(0.0, f32::max)
+}
+
+#[test]
+fn longest_first_seeds_from_the_backbone() {
+    let output = connect_obvious_ordered(spur(), EPSILON, false, true, SeedOrder::LongestFirst);
+    assert_eq!(output.len(), 2);
+    assert!((longest(&output) - 20.0).abs() < 0.01);
+}
+
+#[test]
+fn input_order_seeds_from_the_first_segment() {
+    let output = connect_obvious_ordered(spur(), EPSILON, false, true, SeedOrder::InputOrder);
+    assert_eq!(output.len(), 2);
+    assert!((longest(&output) - 10.5).abs() < 0.01);
+}
+
 // TODO: make a double-diamond <><> shape and assert that there aren't any closed paths
 // Wait, are diamond shapes inherantly bad?  I think it's totally fine.
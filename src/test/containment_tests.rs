@@ -0,0 +1,59 @@
+use ::{nesting, order_by_containment};
+use util::area;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn square(x: f32, y: f32, size: f32) -> PathSegment {
+    PathSegment::new(
+        vec![
+            point2(x, y),
+            point2(x + size, y),
+            point2(x + size, y + size),
+            point2(x, y + size),
+            point2(x, y),
+        ],
+        EPSILON,
+    )
+}
+
+fn reversed(mut segment: PathSegment) -> PathSegment {
+    segment.path.reverse();
+    segment
+}
+
+#[test]
+fn nesting_finds_the_smallest_container() {
+    let segments = vec![
+        square(0.0, 0.0, 10.0),
+        square(1.0, 1.0, 8.0),
+        square(2.0, 2.0, 1.0),
+        square(20.0, 20.0, 1.0),
+        PathSegment::new(vec![point2(3.0, 3.0), point2(4.0, 4.0)], EPSILON),
+    ];
+    assert_eq!(nesting(&segments), vec![None, Some(0), Some(1), None, None]);
+}
+
+#[test]
+fn inner_loops_come_first_with_alternating_winding() {
+    let line = PathSegment::new(vec![point2(30.0, 30.0), point2(40.0, 40.0)], EPSILON);
+    let segments = vec![
+        line.clone(),
+        reversed(square(0.0, 0.0, 10.0)),
+        square(1.0, 1.0, 8.0),
+        square(2.0, 2.0, 1.0),
+    ];
+
+    let out = order_by_containment(segments);
+    assert_eq!(out.len(), 4);
+    assert_eq!(out[3], line);
+
+    // depth 2, 1, 0
+    assert!(area(&out[0].path) > 0.0);
+    assert!(area(&out[1].path) < 0.0);
+    assert!(area(&out[2].path) > 0.0);
+    assert!(out[0].length() < out[1].length());
+    assert!(out[1].length() < out[2].length());
+}
@@ -0,0 +1,62 @@
+use ::{contour, marching_squares, Epsilon};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+#[test]
+fn a_peak_gives_one_loop() {
+    let field = [
+        0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0,
+    ];
+    let soup: Vec<(Point, Point)> = marching_squares(&field, 3, 3, 0.5);
+    assert_eq!(soup.len(), 4);
+
+    let out: Vec<PathSegment> = contour(&field, 3, 3, 0.5, 0.001);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+    assert_eq!(out[0].path.len(), 4);
+    for p in &out[0].path {
+        assert_eq!((p.x - 1.0).abs() + (p.y - 1.0).abs(), 0.5);
+    }
+}
+
+#[test]
+fn crossings_are_interpolated() {
+    let field = [
+        0.0, 4.0,
+        0.0, 4.0,
+    ];
+    let soup: Vec<(Point, Point)> = marching_squares(&field, 2, 2, 1.0);
+    assert_eq!(soup, vec![(point2(0.25, 0.0), point2(0.25, 1.0))]);
+}
+
+#[test]
+fn saddles_follow_the_center() {
+    let field = [
+        1.0, 0.0,
+        0.0, 1.0,
+    ];
+    let joined: Vec<(Point, Point)> = marching_squares(&field, 2, 2, 0.25);
+    let split: Vec<(Point, Point)> = marching_squares(&field, 2, 2, 0.75);
+    assert_eq!(joined.len(), 2);
+    assert_eq!(split.len(), 2);
+    // With the center inside, the outside corners are cut off.
+    assert!(joined.contains(&(point2(0.75, 0.0), point2(1.0, 0.25))));
+    // Otherwise the inside corners are.
+    assert!(split.contains(&(point2(0.0, 0.25), point2(0.25, 0.0))));
+}
+
+#[test]
+fn takes_a_relative_epsilon() {
+    let field = [
+        0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0,
+    ];
+    let out: Vec<PathSegment> = contour(&field, 3, 3, 0.5, Epsilon::RelativeToBounds(0.001));
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+}
@@ -0,0 +1,86 @@
+use ::{connect_obvious_with_cost, graph_stitch_with_cost};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+/// Prefers continuing in a straight line, and refuses to turn corners.
+fn straightest(a: &PathSegment, end: Point, b: &PathSegment, start: Point) -> f32 {
+    let before = end - if a.last() == end { a.path[a.path.len() - 2] } else { a.path[1] };
+    let after = if b.first() == start { b.path[1] } else { b.path[b.path.len() - 2] } - start;
+    let cos = before.dot(after) / (before.length() * after.length());
+    if cos < 0.9 {
+        ::std::f32::INFINITY
+    } else {
+        -cos
+    }
+}
+
+#[test]
+fn costs_steer_joins() {
+    // Three segments meet at (1, 0); the straight continuation should win.
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+    ];
+    let out = connect_obvious_with_cost(segments, EPSILON, false, straightest);
+    assert_eq!(out.len(), 2);
+    let long = out.iter().find(|s| s.path.len() == 3).unwrap();
+    assert!(long.path.iter().all(|p| p.y == 0.0));
+}
+
+#[test]
+fn infinite_cost_forbids_joins() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+    ];
+    let out = connect_obvious_with_cost(segments, EPSILON, false, |_, _, _, _| ::std::f32::INFINITY);
+    assert_eq!(out.len(), 2);
+}
+
+#[test]
+fn graph_stitch_respects_forbidden_joins() {
+    let square = || {
+        vec![
+            PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+            PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON),
+            PathSegment::new(vec![point2(1.0, 1.0), point2(0.0, 1.0)], EPSILON),
+            PathSegment::new(vec![point2(0.0, 1.0), point2(0.0, 0.0)], EPSILON),
+        ]
+    };
+
+    let out = graph_stitch_with_cost(square(), EPSILON, |_, _, _, _| 0.0);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+
+    let out = graph_stitch_with_cost(square(), EPSILON, |_, end: Point, _, _| {
+        if end == point2(1.0, 1.0) {
+            ::std::f32::INFINITY
+        } else {
+            0.0
+        }
+    });
+    assert!(out.is_empty());
+}
+
+#[test]
+fn graph_stitch_with_cost_uses_the_given_epsilon() {
+    // A triangle whose corners are 0.01 apart.
+    let triangle = || {
+        vec![
+            PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON),
+            PathSegment::new(vec![point2(10.0, 0.01), point2(5.0, 5.0)], EPSILON),
+            PathSegment::new(vec![point2(5.0, 5.01), point2(0.0, 0.01)], EPSILON),
+        ]
+    };
+
+    assert!(graph_stitch_with_cost(triangle(), EPSILON, |_, _, _, _| 0.0).is_empty());
+
+    let out = graph_stitch_with_cost(triangle(), 0.05, |_, _, _, _| 0.0);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+}
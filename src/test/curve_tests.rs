@@ -0,0 +1,106 @@
+use ::{connect_obvious_curved, CurvedPathSegment, Span};
+use euclid::{UnknownUnit, point2};
+use std::f32::consts::PI;
+
+type CurvedPathSegment2 = CurvedPathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn half_circle(from_x: f32, to_x: f32) -> CurvedPathSegment2 {
+    CurvedPathSegment::new(
+        point2(from_x, 0.0),
+        vec![
+            Span::Arc {
+                center: point2(0.0, 0.0),
+                to: point2(to_x, 0.0),
+                clockwise: false,
+            },
+        ],
+        EPSILON,
+    )
+}
+
+#[test]
+fn arc_length_is_exact() {
+    let arc = half_circle(1.0, -1.0);
+    assert!((arc.length() - PI).abs() < 1.0e-5);
+}
+
+#[test]
+fn cubic_length_of_a_straight_line() {
+    let cubic: CurvedPathSegment2 = CurvedPathSegment::new(
+        point2(0.0, 0.0),
+        vec![
+            Span::Cubic {
+                ctrl1: point2(1.0, 0.0),
+                ctrl2: point2(2.0, 0.0),
+                to: point2(3.0, 0.0),
+            },
+        ],
+        EPSILON,
+    );
+    assert!((cubic.length() - 3.0).abs() < 1.0e-4);
+}
+
+#[test]
+fn reversing_twice_is_identity() {
+    let arc = half_circle(1.0, -1.0);
+    let reversed = arc.reversed();
+    assert_eq!(reversed.first(), point2(-1.0, 0.0));
+    assert_eq!(reversed.reversed(), arc);
+}
+
+#[test]
+fn flattening_stays_on_the_circle() {
+    let arc = half_circle(1.0, -1.0);
+    let flat = arc.flatten(0.01, EPSILON);
+    assert!(flat.path.len() > 3);
+    for p in &flat.path {
+        assert!((p.to_vector().length() - 1.0).abs() < 1.0e-4);
+    }
+}
+
+#[test]
+fn flattening_with_no_tolerance_finishes() {
+    let cubic: CurvedPathSegment2 = CurvedPathSegment::new(
+        point2(0.0, 0.0),
+        vec![
+            Span::Cubic {
+                ctrl1: point2(0.0, 10.0),
+                ctrl2: point2(10.0, 10.0),
+                to: point2(10.0, 0.0),
+            },
+        ],
+        EPSILON,
+    );
+    for &tolerance in &[0.0, -1.0, ::std::f32::NAN] {
+        let flat = cubic.flatten(tolerance, EPSILON);
+        assert!(flat.path.len() > 3 && flat.path.len() < 10_000);
+        assert_eq!(*flat.path.last().unwrap(), point2(10.0, 0.0));
+
+        let flat = half_circle(1.0, -1.0).flatten(tolerance, EPSILON);
+        assert!(flat.path.len() > 3 && flat.path.len() < 10_000);
+    }
+}
+
+#[test]
+fn two_halves_make_a_closed_circle() {
+    let top = half_circle(1.0, -1.0);
+    // Ends where `top` ends, so it has to be reversed to join.
+    let bottom = CurvedPathSegment::new(
+        point2(1.0, 0.0),
+        vec![
+            Span::Arc {
+                center: point2(0.0, 0.0),
+                to: point2(-1.0, 0.0),
+                clockwise: true,
+            },
+        ],
+        EPSILON,
+    );
+
+    let out = connect_obvious_curved(vec![top, bottom], EPSILON);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+    assert!((out[0].length() - 2.0 * PI).abs() < 1.0e-4);
+}
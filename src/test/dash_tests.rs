@@ -0,0 +1,63 @@
+use ::dash;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn line() -> PathSegment {
+    PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON)
+}
+
+fn spans(segments: &[PathSegment]) -> Vec<(f32, f32)> {
+    segments
+        .iter()
+        .map(|s| (s.path[0].x, s.path[s.path.len() - 1].x))
+        .collect()
+}
+
+#[test]
+fn simple_pattern() {
+    let out = dash(vec![line()], &[2.0, 2.0], 0.0);
+    assert_eq!(spans(&out), vec![(0.0, 2.0), (4.0, 6.0), (8.0, 10.0)]);
+}
+
+#[test]
+fn phase_shifts_the_pattern() {
+    let out = dash(vec![line()], &[2.0, 2.0], 3.0);
+    assert_eq!(spans(&out), vec![(1.0, 3.0), (5.0, 7.0), (9.0, 10.0)]);
+}
+
+#[test]
+fn odd_patterns_repeat() {
+    let out = dash(vec![line()], &[3.0], 0.0);
+    assert_eq!(spans(&out), vec![(0.0, 3.0), (6.0, 9.0)]);
+}
+
+#[test]
+fn dashes_follow_corners() {
+    let corner = PathSegment::new(vec![point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 2.0)], EPSILON);
+    let out = dash(vec![corner], &[3.0, 1.0], 0.0);
+    assert_eq!(out.len(), 1);
+    assert_eq!(
+        out[0].path.to_vec(),
+        vec![point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 1.0)]
+    );
+}
+
+#[test]
+fn closed_segments_include_the_closing_edge() {
+    let square = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.0),
+            point2(1.0, 1.0),
+            point2(0.0, 1.0),
+            point2(0.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let out = dash(vec![square], &[0.5, 0.5], 0.0);
+    assert_eq!(out.len(), 4);
+    assert_eq!(out[3].path.to_vec(), vec![point2(0.0, 1.0), point2(0.0, 0.5)]);
+}
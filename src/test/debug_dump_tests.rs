@@ -0,0 +1,66 @@
+use ::{debug_dump, run_pipeline_with_dumps, Direction, PipelineConfig};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn renders_segments_and_endpoints() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+    ];
+    let svg = debug_dump(segments, EPSILON);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<polyline").count(), 2);
+    assert_eq!(svg.matches(r#"fill="green""#).count(), 2);
+    assert_eq!(svg.matches(r#"fill="red""#).count(), 2);
+    assert!(svg.contains("<rect"));
+}
+
+#[test]
+fn ambiguous_endpoints_are_marked() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+    ];
+    let svg = debug_dump(segments, EPSILON);
+    assert!(svg.contains(r#"stroke="orange""#));
+    // All three endpoints at (1, 0) get a ring.
+    let rings = svg
+        .lines()
+        .filter(|l| l.contains(r#"cx="1" cy="0""#) && !l.contains("fill"))
+        .count();
+    assert_eq!(rings, 3);
+}
+
+#[test]
+fn pipeline_dumps_after_every_pass() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 1.0)],
+        vec![point2(0.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(5.0, 5.0), point2(6.0, 5.0)],
+    ];
+    let config = PipelineConfig {
+        prune: true,
+        direction: Some(Direction::IncreasingX),
+        ..PipelineConfig::default()
+    };
+    let mut dumps = vec![];
+    let out = run_pipeline_with_dumps(segments, &config, |name, svg| dumps.push((name.to_string(), svg)));
+
+    let names: Vec<_> = dumps.iter().map(|d| &d.0[..]).collect();
+    assert_eq!(names, vec!["input", "connect_obvious", "prune", "orient"]);
+    assert_eq!(dumps[0].1.matches("<polyline").count(), 5);
+    // The square is joined into a loop, and only the loop survives `prune`.
+    assert_eq!(dumps[1].1.matches("<polygon").count(), 1);
+    assert_eq!(dumps[1].1.matches("<polyline").count(), 1);
+    assert_eq!(dumps[2].1.matches("<polyline").count(), 0);
+    assert_eq!(out.len(), 1);
+    assert_eq!(dumps[3].1.matches("<polygon").count(), 1);
+}
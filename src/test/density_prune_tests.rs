@@ -0,0 +1,67 @@
+use ::{prune_by_density, run_pipeline, DensityPrune, PipelineConfig};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn dash(x: f32, y: f32, length: f32) -> PathSegment {
+    PathSegment::new(vec![point2(x, y), point2(x + length, y)], EPSILON)
+}
+
+/// Ten rows of short hatching around the origin, plus a long stroke through
+/// it, and a short tick far away from everything.
+fn drawing() -> Vec<PathSegment> {
+    let mut segments = vec![];
+    for row in 0..10 {
+        segments.push(dash(0.0, row as f32 * 0.2, 0.3));
+    }
+    segments.push(dash(-1.0, 1.0, 5.0));
+    segments.push(dash(100.0, 100.0, 0.3));
+    segments
+}
+
+#[test]
+fn short_segments_survive_only_where_it_is_sparse() {
+    let out = prune_by_density(drawing(), 1.0, 2.0, 4);
+    assert_eq!(out.len(), 2);
+    assert_eq!(out[0], dash(-1.0, 1.0, 5.0));
+    assert_eq!(out[1], dash(100.0, 100.0, 0.3));
+}
+
+#[test]
+fn a_low_min_length_keeps_everything() {
+    let out = prune_by_density(drawing(), 0.2, 2.0, 4);
+    assert_eq!(out, drawing());
+}
+
+#[test]
+fn partial_crowds_scale_the_threshold() {
+    // Each dash has one neighbor, so needs half of `min_length`.
+    let pair = vec![dash(0.0, 0.0, 0.4), dash(0.0, 0.1, 0.6)];
+    let out = prune_by_density(pair, 1.0, 1.0, 2);
+    assert_eq!(out, vec![dash(0.0, 0.1, 0.6)]);
+}
+
+#[test]
+fn a_crowd_of_zero_is_always_crowded() {
+    let out = prune_by_density(drawing(), 1.0, 2.0, 0);
+    assert_eq!(out, vec![dash(-1.0, 1.0, 5.0)]);
+}
+
+#[test]
+fn runs_as_part_of_the_pipeline() {
+    let input: Vec<_> = drawing().into_iter().map(|s| s.path.to_vec()).collect();
+    let config = PipelineConfig {
+        density_prune: Some(DensityPrune {
+            min_length: 1.0,
+            radius: 2.0,
+            crowd: 4,
+        }),
+        debug_validate: true,
+        ..PipelineConfig::default()
+    };
+    let mut out = run_pipeline(input, &config);
+    out.sort_by_key(|s| s.path[0].x as i32);
+    assert_eq!(out, vec![dash(-1.0, 1.0, 5.0), dash(100.0, 100.0, 0.3)]);
+}
@@ -0,0 +1,69 @@
+use ::{orient, run_pipeline, Direction, PipelineConfig};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn segments_are_reversed_to_match() {
+    let a = PathSegment::new(vec![point2(0.0, 5.0), point2(1.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 5.0)], EPSILON);
+    let out = orient(vec![a, b], Direction::IncreasingY, false);
+    assert!(out.iter().all(|s| s.path[0].y == 0.0));
+
+    let out = orient(out, Direction::DecreasingY, false);
+    assert!(out.iter().all(|s| s.path[0].y == 5.0));
+}
+
+#[test]
+fn zigzags_are_split() {
+    let zigzag = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 2.0), point2(2.0, 1.0), point2(3.0, 3.0)],
+        EPSILON,
+    );
+    let out = orient(vec![zigzag], Direction::IncreasingY, true);
+    assert_eq!(out.len(), 3);
+    for segment in &out {
+        for w in segment.path.windows(2) {
+            assert!(w[1].y >= w[0].y);
+        }
+    }
+}
+
+#[test]
+fn flat_edges_do_not_split() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(1.0, 2.0)],
+        EPSILON,
+    );
+    assert_eq!(orient(vec![segment], Direction::IncreasingY, true).len(), 1);
+}
+
+#[test]
+fn dominant_direction_follows_the_drawing() {
+    let segments = vec![
+        PathSegment::new(vec![point2(10.0, 0.0), point2(0.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(0.0, 2.0), point2(10.0, 2.0)], EPSILON),
+        PathSegment::new(vec![point2(5.0, 5.0), point2(5.0, 4.0)], EPSILON),
+    ];
+    let out = orient(segments, Direction::Dominant, false);
+    assert_eq!(out[0].path[0], point2(0.0, 0.0));
+    assert_eq!(out[1].path[0], point2(0.0, 2.0));
+}
+
+#[test]
+fn pipeline_applies_direction() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(3.0, 0.0), point2(2.0, 0.0)],
+        vec![point2(2.0, 0.0), point2(1.0, 0.0)],
+    ];
+    let config = PipelineConfig {
+        direction: Some(Direction::IncreasingX),
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(input, &config);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path[0], point2(1.0, 0.0));
+}
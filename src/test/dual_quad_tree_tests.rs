@@ -0,0 +1,97 @@
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+use util::populate;
+use {Epsilon, LayerId};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.01;
+
+#[test]
+fn nearest_start_wins() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(1.004, 0.0), point2(2.0, 1.0)],
+        vec![point2(1.001, 0.0), point2(2.0, -1.0)],
+    ];
+    let (mut dual_qt, epsilon) = populate(segments, Epsilon::Absolute(EPSILON));
+    let found = dual_qt.query_forward(point2(1.0, 0.0), LayerId(0), epsilon, false, true).unwrap();
+    assert_eq!(found.path[0], point2(1.001, 0.0));
+    assert_eq!(dual_qt.len(), 1);
+}
+
+#[test]
+fn nearest_end_beats_start() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(1.004, 0.0), point2(2.0, 1.0)],
+        vec![point2(2.0, -1.0), point2(1.001, 0.0)],
+    ];
+    let (mut dual_qt, epsilon) = populate(segments, Epsilon::Absolute(EPSILON));
+    let found = dual_qt.query_forward(point2(1.0, 0.0), LayerId(0), epsilon, false, true).unwrap();
+    // Ends are reversed so that they continue the chain.
+    assert_eq!(found.path[0], point2(1.001, 0.0));
+    assert_eq!(found.path[1], point2(2.0, -1.0));
+}
+
+#[test]
+fn ambiguity_is_still_reported() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(1.004, 0.0), point2(2.0, 1.0)],
+        vec![point2(1.001, 0.0), point2(2.0, -1.0)],
+    ];
+    let (mut dual_qt, epsilon) = populate(segments, Epsilon::Absolute(EPSILON));
+    assert!(dual_qt.query_forward(point2(3.0, 3.0), LayerId(0), epsilon, false, false).is_none());
+    assert_eq!(dual_qt.ambiguity_count(), 1);
+}
+
+#[test]
+fn passes_share_one_tree() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(5.0, 5.0), point2(6.0, 5.0)],
+    ];
+    let mut dual_qt = ::DualQuadTree::from_segments(segments, EPSILON);
+    assert_eq!(dual_qt.epsilon(), EPSILON);
+    assert_eq!(dual_qt.len(), 4);
+
+    assert_eq!(::connect_obvious_in_place(&mut dual_qt, false, false), 2);
+    assert_eq!(dual_qt.len(), 2);
+
+    ::prune_open_in_place(&mut dual_qt, false);
+    ::graph_stitch_in_place(&mut dual_qt);
+    let out: Vec<_> = dual_qt.into_iter().collect();
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+}
+
+#[test]
+fn graph_stitch_in_place_uses_the_tree_epsilon_and_keeps_closed_segments() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(10.0, 0.0)],
+        vec![point2(10.0, 0.005), point2(5.0, 5.0)],
+        vec![point2(5.0, 5.005), point2(0.0, 0.005)],
+        vec![point2(20.0, 0.0), point2(21.0, 0.0), point2(21.0, 1.0), point2(20.0, 0.0)],
+    ];
+    let mut dual_qt = ::DualQuadTree::from_segments(segments, EPSILON);
+    assert_eq!(dual_qt.len(), 4);
+
+    ::graph_stitch_in_place(&mut dual_qt);
+    let out: Vec<_> = dual_qt.into_iter().collect();
+    assert_eq!(out.len(), 2);
+    assert!(out.iter().all(|s| s.closed));
+    assert!(out.iter().any(|s| s.path[..].contains(&point2(5.0, 5.0))));
+}
+
+#[test]
+fn join_queries_are_counted() {
+    let segments: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(5.0, 5.0), point2(6.0, 5.0)],
+    ];
+    let (mut dual_qt, epsilon) = populate(segments, Epsilon::Absolute(EPSILON));
+    assert_eq!(dual_qt.query_counts(), (0, 0));
+    assert!(dual_qt.query_forward(point2(3.0, 3.0), LayerId(0), epsilon, false, false).is_none());
+    assert_eq!(dual_qt.query_counts(), (2, 0));
+    assert!(dual_qt.query_forward(point2(5.0, 5.0), LayerId(0), epsilon, false, false).is_some());
+    assert_eq!(dual_qt.query_counts(), (4, 1));
+}
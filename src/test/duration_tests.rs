@@ -0,0 +1,108 @@
+use ::{estimate_duration, PenKinematics, Plan};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn kinematics() -> PenKinematics {
+    PenKinematics {
+        draw_speed: 10.0,
+        travel_speed: 20.0,
+        accel: 100.0,
+        pen_lift_time: 0.5,
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1.0e-4
+}
+
+#[test]
+fn long_line_reaches_top_speed() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(100.0, 0.0)], EPSILON);
+    let plan = Plan::from_ordered(vec![line], point2(0.0, 0.0));
+    // 0.1s to speed up and slow down, 0.5 units each, 99 units at speed.
+    assert!(close(estimate_duration(&plan, &kinematics()), 0.2 + 9.9 + 0.5));
+}
+
+#[test]
+fn short_line_never_reaches_top_speed() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(0.25, 0.0)], EPSILON);
+    let plan = Plan::from_ordered(vec![line], point2(0.0, 0.0));
+    // Peaks at 5 units/s halfway along.
+    assert!(close(estimate_duration(&plan, &kinematics()), 0.1 + 0.5));
+}
+
+#[test]
+fn straight_vertices_cost_nothing() {
+    let one = PathSegment::new(vec![point2(0.0, 0.0), point2(100.0, 0.0)], EPSILON);
+    let many = PathSegment::new(
+        (0..101).map(|i| point2(i as f32, 0.0)).collect::<Vec<_>>(),
+        EPSILON,
+    );
+    let k = kinematics();
+    let a = estimate_duration(&Plan::from_ordered(vec![one], point2(0.0, 0.0)), &k);
+    let b = estimate_duration(&Plan::from_ordered(vec![many], point2(0.0, 0.0)), &k);
+    assert!((a - b).abs() < 1.0e-3);
+}
+
+#[test]
+fn sharp_corners_are_slower() {
+    let straight = PathSegment::new(vec![point2(0.0, 0.0), point2(20.0, 0.0)], EPSILON);
+    let corner = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(10.0, 0.0), point2(10.0, 10.0)],
+        EPSILON,
+    );
+    let k = kinematics();
+    let a = estimate_duration(&Plan::from_ordered(vec![straight], point2(0.0, 0.0)), &k);
+    let b = estimate_duration(&Plan::from_ordered(vec![corner], point2(0.0, 0.0)), &k);
+    assert!(b > a);
+}
+
+#[test]
+fn pen_up_moves_use_travel_speed() {
+    let line = PathSegment::new(vec![point2(100.0, 0.0), point2(100.0, 0.25)], EPSILON);
+    let plan = Plan::from_ordered(vec![line], point2(0.0, 0.0));
+    // 0.4s ramping, 96 units at 20 units/s, then the short stroke.
+    assert!(close(estimate_duration(&plan, &kinematics()), 0.4 + 4.8 + 0.1 + 0.5));
+}
+
+#[test]
+fn zero_accel_is_instant() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON);
+    let plan = Plan::from_ordered(vec![line], point2(-2.0, 0.0));
+    for &accel in &[0.0, -1.0] {
+        let kinematics = PenKinematics {
+            accel: accel,
+            ..kinematics()
+        };
+        // 2 units of travel at 20 units/s and 2 of drawing at 10.
+        assert!(close(estimate_duration(&plan, &kinematics), 0.1 + 0.2 + 0.5));
+    }
+}
+
+#[test]
+fn speeds_that_cant_be_reached_take_forever() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON);
+    let travelling = Plan::from_ordered(vec![line.clone()], point2(-2.0, 0.0));
+    let in_place = Plan::from_ordered(vec![line], point2(0.0, 0.0));
+    for &speed in &[0.0, -1.0, ::std::f32::NAN] {
+        for &accel in &[100.0, 0.0] {
+            let slow_drawing = PenKinematics {
+                draw_speed: speed,
+                accel: accel,
+                ..kinematics()
+            };
+            assert_eq!(estimate_duration(&in_place, &slow_drawing), ::std::f32::INFINITY);
+
+            let slow_travel = PenKinematics {
+                travel_speed: speed,
+                accel: accel,
+                ..kinematics()
+            };
+            assert_eq!(estimate_duration(&travelling, &slow_travel), ::std::f32::INFINITY);
+            assert!(estimate_duration(&in_place, &slow_travel).is_finite());
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use ::{from_dxf, to_dxf, Error};
+use super::util::*;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn reads_every_kind_of_line() {
+    let dxf = "0\nSECTION\n2\nBLOCKS\n0\nLINE\n8\nhidden\n10\n9\n20\n9\n11\n8\n21\n8\n0\nENDSEC\n\
+               0\nSECTION\n2\nENTITIES\n\
+               0\nLINE\n8\nwalls\n10\n0.0\n20\n0.0\n11\n1.0\n21\n0.0\n\
+               0\nLWPOLYLINE\n8\nroads\n90\n3\n70\n1\n10\n0\n20\n0\n10\n1\n20\n0\n10\n1\n20\n1\n\
+               0\nPOLYLINE\n8\nwalls\n66\n1\n70\n0\n\
+               0\nVERTEX\n8\nwalls\n10\n1\n20\n0\n\
+               0\nVERTEX\n8\nwalls\n10\n1\n20\n1\n\
+               0\nSEQEND\n\
+               0\nENDSEC\n0\nEOF\n";
+
+    let layers: Vec<(String, Vec<PathSegment>)> = from_dxf(dxf, EPSILON).unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].0, "walls");
+    assert_same(
+        &layers[0].1,
+        &[
+            PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+            PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON),
+        ],
+        false,
+    ).unwrap();
+    assert_eq!(layers[1].0, "roads");
+    assert_eq!(layers[1].1.len(), 1);
+    assert!(layers[1].1[0].closed);
+}
+
+#[test]
+fn round_trip_keeps_layers() {
+    let input = vec![
+        ("a", vec![PathSegment::new(vec![point2(0.0, 0.0), point2(1.5, 0.0)], EPSILON)]),
+        (
+            "b",
+            vec![PathSegment::new(
+                vec![point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(0.0, 0.0)],
+                EPSILON,
+            )],
+        ),
+    ];
+
+    let output: Vec<(String, Vec<PathSegment>)> = from_dxf(&to_dxf(&input), EPSILON).unwrap();
+    assert_eq!(output.len(), 2);
+    for (out, &(name, ref segments)) in output.iter().zip(&input) {
+        assert_eq!(out.0, name);
+        assert_same(&out.1, segments, false).unwrap();
+    }
+}
+
+#[test]
+fn malformed_input_is_an_error() {
+    let result: Result<Vec<(String, Vec<PathSegment>)>, Error> =
+        from_dxf("0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nx\n", EPSILON);
+    assert_eq!(result.err(), Some(Error::Malformed));
+}
+
+#[test]
+fn writes_r12_that_another_reader_accepts() {
+    use dxf_reader::entities::EntityType;
+    use dxf_reader::enums::AcadVersion;
+    use dxf_reader::Drawing;
+
+    let input = vec![
+        ("walls", vec![PathSegment::new(vec![point2(0.0, 0.0), point2(1.5, 0.0), point2(1.5, 2.0)], EPSILON)]),
+        (
+            "roads",
+            vec![PathSegment::new(
+                vec![point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(0.0, 0.0)],
+                EPSILON,
+            )],
+        ),
+    ];
+    let text = to_dxf(&input);
+
+    let drawing = Drawing::load(&mut text.as_bytes()).unwrap();
+    assert_eq!(drawing.header.version, AcadVersion::R12);
+    let layers: Vec<&str> = drawing.layers().map(|l| &l.name[..]).collect();
+    assert!(layers.contains(&"walls") && layers.contains(&"roads"));
+
+    let polylines: Vec<_> = drawing
+        .entities()
+        .filter_map(|e| match e.specific {
+            EntityType::Polyline(ref p) => Some((&e.common.layer[..], p)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(polylines.len(), 2);
+    let (layer, walls) = polylines[0];
+    assert_eq!(layer, "walls");
+    assert_eq!(walls.flags & 1, 0);
+    let points: Vec<(f64, f64)> = walls.vertices().map(|v| (v.location.x, v.location.y)).collect();
+    assert_eq!(points, vec![(0.0, 0.0), (1.5, 0.0), (1.5, 2.0)]);
+    let (layer, roads) = polylines[1];
+    assert_eq!(layer, "roads");
+    assert_eq!(roads.flags & 1, 1);
+    assert_eq!(roads.vertices().count(), 3);
+}
@@ -0,0 +1,52 @@
+use ::{End, EndpointIndex};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn segments() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(1.0, 1.0), point2(5.0, 5.0)], EPSILON),
+        PathSegment::new(vec![point2(20.0, 20.0), point2(30.0, 20.0)], EPSILON),
+    ]
+}
+
+#[test]
+fn empty_index() {
+    let index = EndpointIndex::<UnknownUnit>::new(&[]);
+    assert!(index.is_empty());
+    assert!(index.nearest_endpoint(point2(0.0, 0.0), 3).is_empty());
+}
+
+#[test]
+fn within_radius_is_sorted() {
+    let index = EndpointIndex::new(&segments());
+    assert_eq!(index.len(), 6);
+
+    let hits = index.endpoints_within(point2(0.5, 0.5), 1.0);
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|h| h.end == End::Start));
+
+    let hits = index.endpoints_within(point2(0.9, 0.9), 1.5);
+    assert_eq!((hits[0].segment, hits[1].segment), (1, 0));
+}
+
+#[test]
+fn radius_is_a_circle() {
+    let index = EndpointIndex::new(&segments());
+    // (1, 1) is inside the query box but outside of the circle.
+    assert!(index.endpoints_within(point2(0.0, 0.0), 1.2).len() == 1);
+}
+
+#[test]
+fn nearest_k() {
+    let index = EndpointIndex::new(&segments());
+    let hits = index.nearest_endpoint(point2(29.0, 20.0), 2);
+    assert_eq!(hits.len(), 2);
+    assert_eq!((hits[0].segment, hits[0].end), (2, End::End));
+    assert_eq!((hits[1].segment, hits[1].end), (2, End::Start));
+
+    assert_eq!(index.nearest_endpoint(point2(0.0, 0.0), 10).len(), 6);
+}
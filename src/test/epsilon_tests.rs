@@ -0,0 +1,48 @@
+use euclid::{UnknownUnit, point2, vec2};
+use ::{Epsilon, PathSegment};
+
+type Rect = ::euclid::TypedRect<f32, UnknownUnit>;
+
+#[test]
+fn absolute_ignores_bounds() {
+    let bounds = Rect::new(point2(0.0, 0.0), ::euclid::size2(300.0, 400.0));
+    assert_eq!(Epsilon::Absolute(0.5).resolve(&bounds), 0.5);
+    assert_eq!(Epsilon::from(0.5f32), Epsilon::Absolute(0.5));
+}
+
+#[test]
+fn relative_scales_with_diagonal() {
+    let bounds = Rect::new(point2(0.0, 0.0), ::euclid::size2(300.0, 400.0));
+    assert_eq!(Epsilon::RelativeToBounds(0.01).resolve(&bounds), 5.0);
+}
+
+#[test]
+fn relative_epsilon_closes_large_paths() {
+    let path = vec![
+        point2(0.0, 0.0),
+        point2(1000.0, 0.0),
+        point2(1000.0, 1000.0),
+        point2(0.5, 0.5),
+    ];
+    let absolute: PathSegment<UnknownUnit> = PathSegment::new(path.clone(), 0.001);
+    assert!(!absolute.closed);
+
+    let relative: PathSegment<UnknownUnit> = PathSegment::new(path, Epsilon::RelativeToBounds(0.001));
+    assert!(relative.closed);
+    assert_eq!(relative.path.len(), 3);
+}
+
+#[test]
+fn relative_scales_connect_obvious() {
+    let scale = vec2(1.0e4, 1.0e4);
+    let segments = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0) + scale],
+        vec![point2(1.0, 0.0) + scale + vec2(0.5, 0.0), point2(2.0, 2.0)],
+    ];
+    let joined: Vec<PathSegment<UnknownUnit>> =
+        ::connect_obvious(segments.clone(), Epsilon::RelativeToBounds(0.001), false, false);
+    assert_eq!(joined.len(), 1);
+
+    let separate: Vec<PathSegment<UnknownUnit>> = ::connect_obvious(segments, 0.001, false, false);
+    assert_eq!(separate.len(), 2);
+}
@@ -0,0 +1,90 @@
+use ffi::*;
+
+fn config() -> LineStitchConfig {
+    LineStitchConfig {
+        epsilon: 0.001,
+        epsilon_is_relative: false,
+        only_starts: false,
+        allow_ambiguous: false,
+        prune: false,
+        graph_stitch: false,
+    }
+}
+
+#[test]
+fn joins_polylines_across_the_boundary() {
+    let a = [0.0f32, 0.0, 1.0, 0.0];
+    let b = [1.0f32, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0];
+
+    unsafe {
+        let segments = line_stitch_segments_new();
+        line_stitch_segments_push(segments, a.as_ptr(), 2);
+        line_stitch_segments_push(segments, b.as_ptr(), 4);
+        let paths = line_stitch_run(segments, &config());
+        line_stitch_segments_free(segments);
+
+        assert_eq!(line_stitch_paths_len(paths), 1);
+        assert!(line_stitch_paths_closed(paths, 0));
+        let mut count = 0;
+        let points = line_stitch_paths_points(paths, 0, &mut count);
+        assert_eq!(count, 4);
+        assert_eq!(::std::slice::from_raw_parts(points, 2), &[0.0, 0.0]);
+
+        line_stitch_paths_free(paths);
+    }
+}
+
+#[test]
+fn short_polylines_are_ignored() {
+    let a = [0.0f32, 0.0];
+
+    unsafe {
+        let segments = line_stitch_segments_new();
+        line_stitch_segments_push(segments, a.as_ptr(), 1);
+        let paths = line_stitch_run(segments, &config());
+        assert_eq!(line_stitch_paths_len(paths), 0);
+        line_stitch_paths_free(paths);
+        line_stitch_segments_free(segments);
+    }
+}
+
+#[test]
+fn out_of_range_and_null_are_harmless() {
+    let a = [0.0f32, 0.0, 1.0, 0.0];
+
+    unsafe {
+        let segments = line_stitch_segments_new();
+        line_stitch_segments_push(segments, a.as_ptr(), 2);
+        line_stitch_segments_push(segments, ::std::ptr::null(), 2);
+        line_stitch_segments_push(::std::ptr::null_mut(), a.as_ptr(), 2);
+        let paths = line_stitch_run(segments, &config());
+        assert_eq!(line_stitch_paths_len(paths), 1);
+
+        let mut count = 7;
+        assert!(line_stitch_paths_points(paths, 1, &mut count).is_null());
+        assert_eq!(count, 0);
+        assert!(!line_stitch_paths_closed(paths, 1));
+
+        assert!(line_stitch_run(::std::ptr::null(), &config()).is_null());
+        assert!(line_stitch_run(segments, ::std::ptr::null()).is_null());
+        assert_eq!(line_stitch_paths_len(::std::ptr::null()), 0);
+
+        line_stitch_paths_free(paths);
+        line_stitch_segments_free(segments);
+    }
+}
+
+#[test]
+fn header_declares_every_function() {
+    let header = include_str!("../../include/line_stitch.h");
+    let source = include_str!("../ffi.rs");
+    let exported: Vec<_> = source
+        .split("extern \"C\" fn ")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('(').unwrap()])
+        .collect();
+    assert_eq!(exported.len(), 8);
+    for name in exported {
+        assert!(header.contains(&format!("{}(", name)), "{} is missing from the header", name);
+    }
+}
@@ -0,0 +1,50 @@
+use ::{from_polygon, Error};
+use euclid::{UnknownUnit, point2};
+use geo_types::{Coordinate, LineString, Polygon};
+use std::convert::TryFrom;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn coord(x: f32, y: f32) -> Coordinate<f32> {
+    Coordinate { x: x, y: y }
+}
+
+#[test]
+fn closed_segment_becomes_closed_line_string() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(0.0, 1.0), point2(0.0, 0.0)],
+        EPSILON,
+    );
+    let line: LineString<f32> = segment.clone().into();
+    assert_eq!(line.0.len(), 4);
+    assert_eq!(line.0[0], line.0[3]);
+
+    assert_eq!(PathSegment::try_from(line).unwrap(), segment);
+}
+
+#[test]
+fn short_line_string_is_an_error() {
+    let line = LineString(vec![coord(0.0, 0.0)]);
+    assert_eq!(PathSegment::try_from(line), Err(Error::TooFewPoints));
+}
+
+#[test]
+fn open_segment_is_not_a_polygon() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON);
+    assert_eq!(Polygon::<f32>::try_from(segment).err(), Some(Error::NotClosed));
+}
+
+#[test]
+fn polygon_rings_become_closed_segments() {
+    let polygon = Polygon::new(
+        LineString(vec![coord(0.0, 0.0), coord(4.0, 0.0), coord(4.0, 4.0), coord(0.0, 0.0)]),
+        vec![
+            LineString(vec![coord(1.0, 1.0), coord(2.0, 1.0), coord(2.0, 2.0), coord(1.0, 1.0)]),
+        ],
+    );
+    let segments: Vec<PathSegment> = from_polygon(&polygon, EPSILON).unwrap();
+    assert_eq!(segments.len(), 2);
+    assert!(segments.iter().all(|s| s.closed));
+}
@@ -0,0 +1,63 @@
+use ::{from_geojson, to_geojson, Error};
+use super::util::*;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn reads_lines_and_polygons() {
+    let json = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "properties": {}, "geometry": {
+                "type": "MultiLineString",
+                "coordinates": [[[0, 0], [1, 0]], [[1, 0], [1, 1]]]
+            } },
+            { "type": "Feature", "properties": {}, "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[5, 5], [6, 5], [6, 6], [5, 5]]]
+            } },
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [9, 9] } }
+        ]
+    }"#;
+
+    let segments: Vec<PathSegment> = from_geojson(json, EPSILON, |x, y| point2(x as f32 * 2.0, y as f32)).unwrap();
+    let expected = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(2.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(2.0, 0.0), point2(2.0, 1.0)], EPSILON),
+        PathSegment::new(
+            vec![point2(10.0, 5.0), point2(12.0, 5.0), point2(12.0, 6.0), point2(10.0, 5.0)],
+            EPSILON,
+        ),
+    ];
+    assert_same(&segments, &expected, false).unwrap();
+}
+
+#[test]
+fn round_trip() {
+    let input = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+        PathSegment::new(
+            vec![point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(0.0, 0.0)],
+            EPSILON,
+        ),
+    ];
+
+    for &polygons in &[false, true] {
+        let json = to_geojson(&input, polygons, |p| (p.x as f64, p.y as f64));
+        let output: Vec<PathSegment> = from_geojson(&json, EPSILON, |x, y| point2(x as f32, y as f32)).unwrap();
+        assert_same(&output, &input, false).unwrap();
+    }
+}
+
+#[test]
+fn malformed_input_is_an_error() {
+    let result: Result<Vec<PathSegment>, Error> = from_geojson(
+        r#"{ "type": "LineString", "coordinates": [[0, "a"]] }"#,
+        EPSILON,
+        |x, y| point2(x as f32, y as f32),
+    );
+    assert_eq!(result.err(), Some(Error::Malformed));
+}
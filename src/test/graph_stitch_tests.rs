@@ -1,4 +1,4 @@
-use graph_stitch;
+use {graph_stitch, graph_stitch_with_epsilon};
 use super::util::*;
 use permutohedron::heap_recursive as permute;
 use euclid::{UnknownUnit, point2};
@@ -109,3 +109,77 @@ fn two_segments_that_are_close_to_each_other() {
         ..default_problem()
     });
 }
+
+#[test]
+fn long_ring_does_not_overflow_the_stack() {
+    let count = 100_000;
+    let at = |i: usize| {
+        let angle = (i % count) as f32 / count as f32 * 2.0 * ::std::f32::consts::PI;
+        point2(angle.cos() * count as f32, angle.sin() * count as f32)
+    };
+    let input: Vec<_> = (0..count)
+        .map(|i| PathSegment::new(vec![at(i), at(i + 1)], EPSILON))
+        .collect();
+
+    let output = graph_stitch(input);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+    assert_eq!(output[0].path.len(), count);
+}
+
+#[test]
+fn joins_ends_within_the_given_epsilon() {
+    // A triangle whose corners are 0.01 apart.
+    let input = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(10.0, 0.01), point2(5.0, 5.0)], EPSILON),
+        PathSegment::new(vec![point2(5.0, 5.01), point2(0.0, 0.01)], EPSILON),
+    ];
+
+    assert!(graph_stitch(input.clone()).is_empty());
+
+    let output = graph_stitch_with_epsilon(input, 0.05);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+    assert_eq!(output[0].path.len(), 3);
+}
+
+#[test]
+fn densely_branched_lattice_finishes() {
+    // Every corner can be reached along many paths of different lengths.
+    let side = 30;
+    let at = |x: usize, y: usize| point2(x as f32, y as f32);
+    let mut input = vec![];
+    for x in 0..side {
+        for y in 0..side {
+            input.push(PathSegment::new(vec![at(x, y), at(x + 1, y)], EPSILON));
+            input.push(PathSegment::new(vec![at(x, y), at(x, y + 1)], EPSILON));
+            input.push(PathSegment::new(vec![at(x, y), at(x + 1, y + 1)], EPSILON));
+        }
+    }
+    input.push(PathSegment::new(vec![at(side, side), point2(-1.0, side as f32 + 1.0), at(0, 0)], EPSILON));
+
+    let output = graph_stitch(input);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+}
+
+#[test]
+fn loops_through_a_junction_with_many_branches_are_found() {
+    // Six branches run from `a` to `j`.  The ones explored first are the
+    // shortest overall, so the longest loop is only found on the sixth walk
+    // through the segment that leaves `j`.
+    let (z, a, j) = (point2(5.0, -10.0), point2(0.0, 0.0), point2(10.0, 0.0));
+    let mut input = vec![PathSegment::new(vec![z, a], EPSILON)];
+    for i in 1..7 {
+        let middle = point2(5.0, 6.0 - i as f32);
+        input.push(PathSegment::new(vec![a, middle], EPSILON));
+        input.push(PathSegment::new(vec![middle, point2(5.0, 20.0 * i as f32), j], EPSILON));
+    }
+    input.push(PathSegment::new(vec![j, z], EPSILON));
+
+    let output = graph_stitch(input);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+    assert!(output[0].path.contains(&point2(5.0, 120.0)));
+}
@@ -0,0 +1,41 @@
+use ::{batch_from_flat, batch_from_tuples, connect_obvious, flat_from_points, points_from_flat, points_from_pairs,
+       points_from_tuples};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+type Points = ::Points<UnknownUnit>;
+
+#[test]
+fn every_shape_gives_the_same_points() {
+    let tuples: Points = points_from_tuples(vec![(0.0, 1.0), (2.0, 3.0)]);
+    let pairs: Points = points_from_pairs(&[[0.0, 1.0], [2.0, 3.0]]);
+    let flat: Points = points_from_flat(&[0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(&tuples[..], &[point2(0.0, 1.0), point2(2.0, 3.0)]);
+    assert_eq!(tuples, pairs);
+    assert_eq!(tuples, flat);
+}
+
+#[test]
+fn segments_collect_from_tuples() {
+    let segment: PathSegment = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 0.0)].into_iter().collect();
+    assert!(segment.closed);
+    let segment: PathSegment = vec![[0.0, 0.0], [1.0, 0.0]].into_iter().collect();
+    assert!(!segment.closed);
+}
+
+#[test]
+fn batches_feed_the_passes() {
+    let batch: Vec<Points> = batch_from_tuples(vec![vec![(0.0, 0.0), (1.0, 0.0)], vec![(1.0, 0.0), (1.0, 1.0)]]);
+    assert_eq!(connect_obvious(batch, 0.001, true, false).len(), 1);
+
+    let batch: Vec<Points> = batch_from_flat(&[0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 2.0, 2.0], &[2, 3]);
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[1].len(), 3);
+}
+
+#[test]
+fn flat_coordinates_round_trip() {
+    let coords = [0.0, 1.0, 2.0, 3.0, -4.0, 5.5];
+    let points: Points = points_from_flat(&coords);
+    assert_eq!(flat_from_points(&points), coords.to_vec());
+}
@@ -0,0 +1,94 @@
+use ::remove_jogs;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn staircases_become_diagonals() {
+    let mut points = vec![point2(0.0, 0.0)];
+    for i in 0..10 {
+        let i = i as f32;
+        points.push(point2(i + 1.0, i));
+        points.push(point2(i + 1.0, i + 1.0));
+    }
+    let stairs = PathSegment::new(points, EPSILON);
+
+    let out = remove_jogs(vec![stairs], 1.0);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.0), point2(10.0, 10.0)]);
+}
+
+#[test]
+fn narrow_zigzags_are_flattened() {
+    let zigzag = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.1),
+            point2(2.0, -0.1),
+            point2(3.0, 0.1),
+            point2(4.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let out = remove_jogs(vec![zigzag], 0.3);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.0), point2(4.0, 0.0)]);
+}
+
+#[test]
+fn corners_wider_than_the_pen_are_kept() {
+    let corner = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 4.0)], EPSILON);
+    assert_eq!(remove_jogs(vec![corner.clone()], 0.5), vec![corner]);
+}
+
+#[test]
+fn closed_paths_stay_closed() {
+    let square = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(2.0, 0.0),
+            point2(4.0, 0.1),
+            point2(4.0, 4.0),
+            point2(0.0, 4.0),
+            point2(0.0, 0.0),
+        ],
+        EPSILON,
+    );
+    assert!(square.closed);
+
+    let out = remove_jogs(vec![square], 0.5);
+    assert!(out[0].closed);
+    assert_eq!(
+        out[0].path.to_vec(),
+        vec![point2(0.0, 0.0), point2(4.0, 0.1), point2(4.0, 4.0), point2(0.0, 4.0)]
+    );
+}
+
+#[test]
+fn zero_pen_width_changes_nothing() {
+    let zigzag = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.1), point2(2.0, 0.0)], EPSILON);
+    assert_eq!(remove_jogs(vec![zigzag.clone()], 0.0), vec![zigzag]);
+}
+
+#[test]
+fn long_wobbly_lines_collapse() {
+    let points: Vec<_> = (0..20_000)
+        .map(|i| point2(i as f32 * 0.01, if i % 2 == 0 { 0.05 } else { -0.05 }))
+        .collect();
+    let line = PathSegment::new(points, EPSILON);
+
+    let out = remove_jogs(vec![line], 0.2);
+    assert_eq!(out[0].path.len(), 2);
+}
+
+#[test]
+fn doubling_back_is_only_dropped_within_the_pen() {
+    let short = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(3.9, 0.0)], EPSILON);
+    assert_eq!(
+        remove_jogs(vec![short], 0.5)[0].path.to_vec(),
+        vec![point2(0.0, 0.0), point2(3.9, 0.0)]
+    );
+
+    let long = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(2.0, 0.0)], EPSILON);
+    assert_eq!(remove_jogs(vec![long.clone()], 0.5), vec![long]);
+}
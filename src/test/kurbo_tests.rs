@@ -0,0 +1,69 @@
+use ::{from_bez_path, to_bez_path};
+use super::util::*;
+use euclid::{UnknownUnit, point2};
+use kurbo::BezPath;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn round_trip_keeps_closed_flag() {
+    let input = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+        PathSegment::new(
+            vec![
+                point2(0.0, 0.0),
+                point2(0.0, 1.0),
+                point2(1.0, 1.0),
+                point2(0.0, 0.0),
+            ],
+            EPSILON,
+        ),
+    ];
+
+    let path = to_bez_path(&input);
+    let output: Vec<PathSegment> = from_bez_path(&path, 0.1, EPSILON);
+    assert_same(&output, &input, false).unwrap();
+}
+
+#[test]
+fn curves_are_flattened() {
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    path.curve_to((0.0, 10.0), (10.0, 10.0), (10.0, 0.0));
+
+    let output: Vec<PathSegment> = from_bez_path(&path, 0.01, EPSILON);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].path.len() > 2);
+    assert_eq!(output[0].last(), point2(10.0, 0.0));
+}
+
+#[test]
+fn close_path_does_not_repeat_the_first_point() {
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    path.line_to((1.0, 0.0));
+    path.line_to((1.0, 1.0));
+    path.line_to((0.0, 0.0));
+    path.close_path();
+
+    let output: Vec<PathSegment> = from_bez_path(&path, 0.1, EPSILON);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+    assert_eq!(&output[0].path[..], &[point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)]);
+}
+
+#[test]
+fn only_close_path_closes_a_sub_path() {
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    path.line_to((1.0, 0.0));
+    path.line_to((1.0, 1.0));
+    path.line_to((0.0, 0.0005));
+
+    let output: Vec<PathSegment> = from_bez_path(&path, 0.1, EPSILON);
+    assert_eq!(output.len(), 1);
+    assert!(!output[0].closed);
+    assert_eq!(output[0].path.len(), 4);
+}
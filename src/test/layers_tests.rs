@@ -0,0 +1,85 @@
+use ::{dash, process_layers, LayerId, PipelineConfig};
+use euclid::{UnknownUnit, point2};
+use std::collections::HashMap;
+
+type Point = ::Point<UnknownUnit>;
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn empty_layers_are_dropped() {
+    let mut layers: HashMap<LayerId, Vec<Vec<Point>>> = HashMap::new();
+    layers.insert(LayerId(0), vec![]);
+    layers.insert(LayerId(1), vec![vec![point2(0.0, 0.0), point2(1.0, 0.0)]]);
+
+    let out = process_layers(layers, &PipelineConfig::default());
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].0, LayerId(1));
+}
+
+#[test]
+fn layers_are_ordered_by_travel() {
+    let mut layers: HashMap<LayerId, Vec<Vec<Point>>> = HashMap::new();
+    layers.insert(LayerId(0), vec![vec![point2(20.0, 0.0), point2(21.0, 0.0)]]);
+    layers.insert(LayerId(1), vec![vec![point2(1.0, 0.0), point2(2.0, 0.0)]]);
+    layers.insert(LayerId(2), vec![vec![point2(10.0, 0.0), point2(11.0, 0.0)]]);
+
+    let out = process_layers(layers, &PipelineConfig::default());
+    let ids: Vec<_> = out.iter().map(|&(id, _)| id).collect();
+    assert_eq!(ids, vec![LayerId(1), LayerId(2), LayerId(0)]);
+}
+
+#[test]
+fn each_layer_is_stitched() {
+    let mut layers: HashMap<LayerId, Vec<Vec<Point>>> = HashMap::new();
+    layers.insert(
+        LayerId(0),
+        vec![
+            vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+            vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+        ],
+    );
+
+    let out = process_layers(layers, &PipelineConfig::default());
+    assert_eq!(out[0].1.len(), 1);
+    assert_eq!(out[0].1[0].path.len(), 3);
+}
+
+#[test]
+fn layers_are_never_joined_to_each_other() {
+    let square = |x: f32| {
+        vec![
+            vec![point2(x, 0.0), point2(x + 1.0, 0.0)],
+            vec![point2(x + 1.0, 0.0), point2(x + 1.0, 1.0)],
+            vec![point2(x + 1.0, 1.0), point2(x, 1.0)],
+            vec![point2(x, 1.0), point2(x, 0.0)],
+        ]
+    };
+    let mut layers: HashMap<LayerId, Vec<Vec<Point>>> = HashMap::new();
+    // The same square on both layers, so every corner has a start from each.
+    layers.insert(LayerId(0), square(0.0));
+    layers.insert(LayerId(1), square(0.0));
+    layers.insert(LayerId(2), vec![vec![point2(1.0, 1.0), point2(3.0, 3.0)]]);
+
+    let mut out = process_layers(layers, &PipelineConfig::default());
+    out.sort_by_key(|&(id, _)| id);
+    assert_eq!(out.len(), 3);
+    for &(id, ref segments) in &out[..2] {
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].closed);
+        assert_eq!(segments[0].layer, id);
+    }
+    assert_eq!(out[2].1[0].layer, LayerId(2));
+}
+
+#[test]
+fn passes_keep_the_layer() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0)], EPSILON).with_layer(LayerId(3));
+    let dashes = dash(vec![segment.clone()], &[1.0, 1.0], 0.0);
+    assert_eq!(dashes.len(), 2);
+    assert!(dashes.iter().all(|d| d.layer == LayerId(3)));
+
+    let (a, b) = segment.split_at_length(1.0);
+    assert_eq!((a.layer, b.layer), (LayerId(3), LayerId(3)));
+}
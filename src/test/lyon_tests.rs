@@ -0,0 +1,59 @@
+use ::{from_lyon_path, to_lyon_path};
+use super::util::*;
+use euclid::{UnknownUnit, point2};
+use lyon_path::Path;
+use lyon_path::builder::FlatPathBuilder;
+use lyon_path::math::point;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn round_trip_keeps_closed_flag() {
+    let input = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+        PathSegment::new(
+            vec![
+                point2(0.0, 0.0),
+                point2(0.0, 1.0),
+                point2(1.0, 1.0),
+                point2(0.0, 0.0),
+            ],
+            EPSILON,
+        ),
+    ];
+
+    let path = to_lyon_path(&input);
+    let output: Vec<PathSegment> = from_lyon_path(&path, 0.1, EPSILON);
+    assert_same(&output, &input, false).unwrap();
+}
+
+#[test]
+fn close_does_not_repeat_the_first_point() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.line_to(point(0.0, 0.0));
+    builder.close();
+
+    let output: Vec<PathSegment> = from_lyon_path(&builder.build(), 0.1, EPSILON);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].closed);
+    assert_eq!(&output[0].path[..], &[point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)]);
+}
+
+#[test]
+fn only_close_closes_a_sub_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.line_to(point(0.0, 0.0005));
+
+    let output: Vec<PathSegment> = from_lyon_path(&builder.build(), 0.1, EPSILON);
+    assert_eq!(output.len(), 1);
+    assert!(!output[0].closed);
+    assert_eq!(output[0].path.len(), 4);
+}
@@ -6,3 +6,62 @@ mod prune_tests;
 mod reg_tests;
 mod zero_area_loop_tests;
 mod graph_stitch_tests;
+mod travel_tests;
+mod layers_tests;
+mod pipeline_tests;
+mod path_segment_tests;
+#[cfg(feature = "lyon")]
+mod lyon_tests;
+#[cfg(feature = "geo")]
+mod geo_tests;
+#[cfg(feature = "kurbo")]
+mod kurbo_tests;
+#[cfg(feature = "geojson")]
+mod geojson_tests;
+#[cfg(feature = "dxf")]
+mod dxf_tests;
+#[cfg(feature = "preview")]
+mod preview_tests;
+#[cfg(feature = "contour")]
+mod contour_tests;
+#[cfg(feature = "ffi")]
+mod ffi_tests;
+mod curve_tests;
+mod split_tests;
+mod occlude_tests;
+mod boolean_tests;
+mod containment_tests;
+mod self_intersection_tests;
+mod epsilon_tests;
+mod snap_tests;
+mod resample_tests;
+mod smooth_tests;
+mod plan_tests;
+mod duration_tests;
+mod endpoint_index_tests;
+mod dual_quad_tree_tests;
+mod spatial_index_tests;
+mod restitch_tests;
+mod overstroke_tests;
+mod dash_tests;
+mod cost_tests;
+mod direction_tests;
+mod validate_tests;
+mod sanitize_tests;
+mod approx_tests;
+mod transform_tests;
+mod input_tests;
+mod soup_tests;
+mod components_tests;
+mod jogs_tests;
+mod t_junction_tests;
+mod text_format_tests;
+mod density_prune_tests;
+mod batch_tests;
+mod python_tests;
+#[cfg(feature = "log")]
+mod trace_tests;
+#[cfg(feature = "debug")]
+mod debug_dump_tests;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm_tests;
@@ -0,0 +1,58 @@
+use ::occlude;
+use super::util::*;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn square(x: f32, y: f32, size: f32) -> PathSegment {
+    PathSegment::new(
+        vec![
+            point2(x, y),
+            point2(x + size, y),
+            point2(x + size, y + size),
+            point2(x, y + size),
+            point2(x, y),
+        ],
+        EPSILON,
+    )
+}
+
+#[test]
+fn no_occluders() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let out = occlude(vec![line.clone()], &[], EPSILON);
+    assert_eq!(out, vec![line]);
+}
+
+#[test]
+fn line_through_a_square_is_cut_in_two() {
+    let line = PathSegment::new(vec![point2(0.0, 1.0), point2(8.0, 1.0)], EPSILON);
+    let out = occlude(vec![line], &[square(4.0, 0.0, 2.0)], EPSILON);
+
+    assert_same(
+        &out,
+        &[
+            PathSegment::new(vec![point2(0.0, 1.0), point2(4.0, 1.0)], EPSILON),
+            PathSegment::new(vec![point2(6.0, 1.0), point2(8.0, 1.0)], EPSILON),
+        ],
+        false,
+    ).unwrap();
+}
+
+#[test]
+fn fully_hidden_segments_are_removed() {
+    let line = PathSegment::new(vec![point2(1.0, 1.0), point2(2.0, 2.0)], EPSILON);
+    let out = occlude(vec![line], &[square(0.0, 0.0, 5.0)], EPSILON);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn partially_hidden_closed_segment_stays_in_one_piece() {
+    let out = occlude(vec![square(0.0, 0.0, 4.0)], &[square(3.0, 3.0, 4.0)], EPSILON);
+    assert_eq!(out.len(), 1);
+    assert!(!out[0].closed);
+    assert_eq!(out[0].path.first(), Some(&point2(3.0, 4.0)));
+    assert_eq!(out[0].path.last(), Some(&point2(4.0, 3.0)));
+}
@@ -0,0 +1,52 @@
+use ::merge_overstrokes;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn parallel_strokes_merge_to_centerline() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(0.0, 0.5), point2(10.0, 0.5)], EPSILON);
+    let out = merge_overstrokes(vec![a, b], 1.0, EPSILON);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.25), point2(10.0, 0.25)]);
+}
+
+#[test]
+fn reversed_strokes_merge() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(10.0, 0.5), point2(0.0, 0.5)], EPSILON);
+    let out = merge_overstrokes(vec![a, b], 1.0, EPSILON);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.25), point2(10.0, 0.25)]);
+}
+
+#[test]
+fn distant_strokes_are_kept() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(0.0, 3.0), point2(10.0, 3.0)], EPSILON);
+    let out = merge_overstrokes(vec![a.clone(), b.clone()], 1.0, EPSILON);
+    assert_eq!(out, vec![a, b]);
+}
+
+#[test]
+fn partial_overlap_is_kept() {
+    // Close along the shared part, but `b` runs on much further.
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(0.0, 0.5), point2(20.0, 0.5)], EPSILON);
+    assert_eq!(merge_overstrokes(vec![a, b], 1.0, EPSILON).len(), 2);
+}
+
+#[test]
+fn three_strokes_average_evenly() {
+    let strokes = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(0.0, 0.3), point2(10.0, 0.3)], EPSILON),
+        PathSegment::new(vec![point2(0.0, 0.6), point2(10.0, 0.6)], EPSILON),
+    ];
+    let out = merge_overstrokes(strokes, 1.0, EPSILON);
+    assert_eq!(out.len(), 1);
+    assert!((out[0].path[0].y - 0.3).abs() < 1.0e-5);
+}
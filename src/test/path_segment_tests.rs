@@ -0,0 +1,123 @@
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn path_segment_is_send_and_sync() {
+    assert_send_sync::<PathSegment>();
+}
+
+#[test]
+fn lengths_are_computed() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(3.0, 0.0), point2(3.0, 4.0)],
+        EPSILON,
+    );
+    assert_eq!(segment.length(), 7.0);
+    assert_eq!(segment.length_2(), 25.0);
+}
+
+#[test]
+fn short_paths_are_stored_inline() {
+    let points: ::Points<UnknownUnit> = (0..::INLINE_POINTS).map(|i| point2(i as f32, 0.0)).collect();
+    let segment = PathSegment::new(points, EPSILON);
+    assert!(!segment.path.spilled());
+}
+
+fn square() -> PathSegment {
+    PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 1.0), point2(0.0, 0.0)],
+        EPSILON,
+    )
+}
+
+#[test]
+fn reverse_keeps_the_start_of_closed_segments() {
+    let mut segment = square();
+    segment.reverse();
+    assert!(segment.closed);
+    assert_eq!(
+        &segment.path[..],
+        &[point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(1.0, 0.0)]
+    );
+    assert_eq!(segment.length(), 3.0);
+
+    let mut open = PathSegment::new(vec![point2(0.0, 0.0), point2(3.0, 0.0), point2(3.0, 4.0)], EPSILON);
+    open.reverse();
+    assert_eq!(open.path[0], point2(3.0, 4.0));
+    assert_eq!(open.length(), 7.0);
+}
+
+#[test]
+fn split_closed_segment_at_index() {
+    let (a, b) = square().split_at_index(2);
+    assert!(!a.closed && !b.closed);
+    assert_eq!(&a.path[..], &[point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)]);
+    assert_eq!(&b.path[..], &[point2(1.0, 1.0), point2(0.0, 1.0), point2(0.0, 0.0)]);
+    assert_eq!(a.length() + b.length(), 4.0);
+}
+
+#[test]
+fn split_at_length_adds_a_point() {
+    let (a, b) = square().split_at_length(1.5);
+    assert_eq!(&a.path[..], &[point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 0.5)]);
+    assert_eq!(b.path[0], point2(1.0, 0.5));
+    assert_eq!(a.length(), 1.5);
+    assert_eq!(b.length(), 2.5);
+
+    let (a, b) = square().split_at_length(1.0);
+    assert_eq!(a.path.len(), 2);
+    assert_eq!(&b.path[..2], &[point2(1.0, 0.0), point2(1.0, 1.0)]);
+}
+
+#[test]
+fn concat_merges_touching_ends() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 0.0)], EPSILON);
+    let joined = a.concat(&b, EPSILON);
+    assert!(joined.closed);
+    assert_eq!(joined.path.len(), 3);
+    assert_eq!(joined.length(), 2.0);
+}
+
+#[test]
+fn subsegment_and_point_at_length() {
+    let segment = square();
+    assert_eq!(segment.point_at_length(3.5), point2(0.0, 0.5));
+    assert_eq!(segment.point_at_length(10.0), point2(0.0, 0.0));
+
+    let piece = segment.subsegment(0.5, 2.5);
+    assert!(!piece.closed);
+    assert_eq!(
+        &piece.path[..],
+        &[point2(0.5, 0.0), point2(1.0, 0.0), point2(1.0, 1.0), point2(0.5, 1.0)]
+    );
+    assert_eq!(piece.length(), 2.0);
+}
+
+#[test]
+fn inline_storage_can_be_chosen() {
+    type Wide = ::PathSegment<UnknownUnit, [::euclid::TypedPoint2D<f32, UnknownUnit>; 8]>;
+
+    let points: Vec<_> = (0..8).map(|i| point2(i as f32, 0.0)).collect();
+    let wide = Wide::new_inline(points.clone(), EPSILON);
+    assert!(!wide.path.spilled());
+
+    let narrow: PathSegment = wide.clone().into_inline();
+    assert!(narrow.path.spilled());
+    assert_eq!(narrow, PathSegment::new(points, EPSILON));
+    assert_eq!(narrow.length(), wide.length());
+    assert_eq!(narrow.into_inline(), wide);
+}
+
+#[test]
+fn two_point_smallvecs_still_build_segments() {
+    let points: ::smallvec::SmallVec<[::euclid::TypedPoint2D<f32, UnknownUnit>; 2]> =
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)].into();
+    let segment = PathSegment::new(points, EPSILON);
+    assert_eq!(segment.length(), 1.0);
+}
@@ -0,0 +1,193 @@
+use ::{optimize_until_stable, Epsilon, run_pipeline, run_pipeline_with_progress, run_pipeline_with_stats, Pass,
+       PipelineConfig};
+use euclid::{UnknownUnit, point2};
+use std::ops::ControlFlow;
+
+type Point = ::Point<UnknownUnit>;
+
+#[test]
+fn stats_for_a_joined_line() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+        vec![point2(2.0, 0.0), point2(3.0, 0.0)],
+    ];
+
+    let (out, stats) = run_pipeline_with_stats(input, &PipelineConfig::default());
+    assert_eq!(out.len(), 1);
+    assert_eq!(stats.segments_in, 3);
+    assert_eq!(stats.segments_out, 1);
+    assert_eq!(stats.joins, 2);
+    assert_eq!(stats.points_dropped, 0);
+    assert_eq!(stats.length_before, 3.0);
+    assert_eq!(stats.length_after, 3.0);
+    assert_eq!(stats.ambiguities, 0);
+}
+
+#[test]
+fn stats_for_a_closed_loop() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+    ];
+
+    let (out, stats) = run_pipeline_with_stats(input, &PipelineConfig::default());
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+    assert_eq!(stats.joins, 2);
+    assert_eq!(stats.points_dropped, 0);
+    assert_eq!(stats.length_before, stats.length_after);
+}
+
+#[test]
+fn stats_count_what_was_dropped_and_travelled() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(100.0, 100.0), point2(101.0, 100.0)],
+        vec![point2(101.0, 100.0), point2(102.0, 100.0)],
+        // Too short to keep.
+        vec![point2(200.0, 200.0), point2(200.0, 200.0)],
+    ];
+
+    let (out, stats) = run_pipeline_with_stats(input, &PipelineConfig::default());
+    assert_eq!(out.len(), 1);
+    assert_eq!(stats.joins, 1);
+    assert_eq!(stats.points_dropped, 2);
+    // The pen starts where the only segment does.
+    assert_eq!(stats.pen_up_travel, 0.0);
+}
+
+#[test]
+fn progress_reaches_total() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(5.0, 0.0), point2(6.0, 0.0)],
+    ];
+
+    let mut reports = vec![];
+    let out = run_pipeline_with_progress(input, &PipelineConfig::default(), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    });
+    assert_eq!(out.len(), 2);
+
+    let last = reports.last().unwrap();
+    assert_eq!(last.pass, Pass::ConnectObvious);
+    assert_eq!(last.done, last.total);
+}
+
+#[test]
+fn cancelling_keeps_every_segment() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(5.0, 0.0), point2(6.0, 0.0)],
+        vec![point2(10.0, 0.0), point2(11.0, 0.0)],
+    ];
+
+    let mut calls = 0;
+    let out = run_pipeline_with_progress(input, &PipelineConfig::default(), |_| {
+        calls += 1;
+        ControlFlow::Break(())
+    });
+    assert_eq!(calls, 1);
+    assert_eq!(out.len(), 3);
+}
+
+#[test]
+fn prune_drops_dangling_segments() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(5.0, 5.0), point2(6.0, 5.0)],
+    ];
+    let config = PipelineConfig {
+        prune: true,
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(input, &config);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+}
+
+#[test]
+fn optimize_until_stable_stops_early() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+        vec![point2(2.0, 0.0), point2(3.0, 0.0)],
+    ];
+    let out = optimize_until_stable(input.clone(), &PipelineConfig::default(), 100);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.len(), 4);
+
+    let out = optimize_until_stable(input, &PipelineConfig::default(), 0);
+    assert_eq!(out.len(), 3);
+}
+
+#[test]
+fn optimize_until_stable_joins_after_pruning() {
+    // The spur at (1, 0) makes the join there ambiguous until it is pruned.
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, -1.0)],
+    ];
+    let config = PipelineConfig {
+        prune: true,
+        ..PipelineConfig::default()
+    };
+    let out = optimize_until_stable(input, &config, 10);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+}
+
+#[test]
+fn spurs_left_by_joins_are_cut_out() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 3.0)],
+        vec![point2(1.0, 3.0), point2(1.0, 0.0), point2(2.0, 0.0)],
+    ];
+
+    let joined = run_pipeline(input.clone(), &PipelineConfig::default());
+    assert_eq!(joined.len(), 1);
+    assert_eq!(joined[0].path.len(), 5);
+
+    let config = PipelineConfig {
+        remove_spurs: true,
+        debug_validate: true,
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(input, &config);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.len(), 3);
+    assert_eq!(out[0].length(), 2.0);
+}
+
+#[test]
+fn spurs_use_the_epsilon_resolved_against_the_input() {
+    let input: Vec<Vec<Point>> = vec![
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.0),
+            point2(1.0, 3.0),
+            point2(1.05, 0.0),
+            point2(2.0, 0.0),
+            point2(2.0, -1.0),
+            point2(0.0, -1.0),
+            point2(0.0, 0.0),
+        ],
+        // Pruned, which shrinks the bounds of the output.
+        vec![point2(100.0, 100.0), point2(101.0, 100.0)],
+    ];
+    let config = PipelineConfig {
+        epsilon: Epsilon::RelativeToBounds(0.001),
+        prune: true,
+        remove_spurs: true,
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(input, &config);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].path.iter().all(|p| p.y <= 0.0));
+}
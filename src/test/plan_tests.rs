@@ -0,0 +1,126 @@
+use ::{plan_travel, travel_distance, Move, Plan};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn empty_plan() {
+    let plan = Plan::from_ordered(Vec::<PathSegment>::new(), point2(0.0, 0.0));
+    assert!(plan.moves.is_empty());
+    assert_eq!(plan.pen_up_distance(), 0.0);
+    assert_eq!(plan.pen_down_distance(), 0.0);
+}
+
+#[test]
+fn pen_up_moves_between_segments() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(4.0, 4.0), point2(4.0, 6.0)], EPSILON);
+    let plan = Plan::from_ordered(vec![a.clone(), b.clone()], point2(0.0, 0.0));
+    assert_eq!(
+        plan.moves,
+        vec![
+            Move::PenDown(a),
+            Move::PenUp(vec![point2(1.0, 0.0), point2(4.0, 4.0)]),
+            Move::PenDown(b),
+        ]
+    );
+    assert_eq!(plan.pen_up_distance(), 5.0);
+    assert_eq!(plan.pen_down_distance(), 3.0);
+}
+
+#[test]
+fn closed_segments_return_to_their_start() {
+    let square = PathSegment::new(
+        vec![
+            point2(1.0, 0.0),
+            point2(2.0, 0.0),
+            point2(2.0, 1.0),
+            point2(1.0, 1.0),
+            point2(1.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let line = PathSegment::new(vec![point2(1.0, 3.0), point2(5.0, 3.0)], EPSILON);
+    let plan = Plan::from_ordered(vec![square, line], point2(0.0, 0.0));
+    assert_eq!(plan.moves.len(), 4);
+    assert_eq!(plan.moves[2], Move::PenUp(vec![point2(1.0, 0.0), point2(1.0, 3.0)]));
+    assert_eq!(plan.pen_up_distance(), 4.0);
+    assert_eq!(plan.pen_down_distance(), 8.0);
+}
+
+#[test]
+fn plan_travel_matches_order_travel() {
+    let segments = vec![
+        PathSegment::new(vec![point2(10.0, 0.0), point2(11.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(5.0, 0.0), point2(1.0, 0.0)], EPSILON),
+    ];
+    let plan = plan_travel(segments.clone(), point2(0.0, 0.0));
+    let ordered = ::order_travel(segments, point2(0.0, 0.0));
+    assert_eq!(plan.pen_up_distance(), travel_distance(&ordered, point2(0.0, 0.0)));
+    assert_eq!(plan.moves[1], Move::PenDown(ordered[0].clone()));
+}
+
+fn square(x0: f32, y0: f32, x1: f32, y1: f32) -> PathSegment {
+    PathSegment::new(
+        vec![
+            point2(x0, y0),
+            point2(x1, y0),
+            point2(x1, y1),
+            point2(x0, y1),
+            point2(x0, y0),
+        ],
+        EPSILON,
+    )
+}
+
+#[test]
+fn pen_up_moves_route_around_keep_out() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(0.0, 1.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(10.0, 1.0), point2(10.0, 2.0)], EPSILON);
+    let keep_out = vec![square(4.0, -1.0, 6.0, 4.0)];
+
+    let plan = Plan::from_ordered_avoiding(vec![a, b], point2(0.0, 0.0), &keep_out);
+    assert_eq!(
+        plan.moves[1],
+        Move::PenUp(vec![
+            point2(0.0, 1.0),
+            point2(4.0, -1.0),
+            point2(6.0, -1.0),
+            point2(10.0, 1.0),
+        ])
+    );
+    let direct = Plan::from_ordered_avoiding(plan_segments(&plan), point2(0.0, 0.0), &[]);
+    assert!(plan.pen_up_distance() > direct.pen_up_distance());
+}
+
+#[test]
+fn clear_moves_stay_straight() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(0.0, 1.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(10.0, 1.0), point2(10.0, 2.0)], EPSILON);
+    let keep_out = vec![square(4.0, 3.0, 6.0, 5.0)];
+
+    let plan = Plan::from_ordered_avoiding(vec![a, b], point2(0.0, 0.0), &keep_out);
+    assert_eq!(plan.moves[1], Move::PenUp(vec![point2(0.0, 1.0), point2(10.0, 1.0)]));
+}
+
+#[test]
+fn moves_may_run_along_keep_out_edges() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(6.0, 0.0), point2(9.0, 0.0)], EPSILON);
+    let keep_out = vec![square(4.0, 0.0, 6.0, 2.0)];
+
+    let plan = Plan::from_ordered_avoiding(vec![a, b], point2(0.0, 0.0), &keep_out);
+    assert_eq!(plan.moves[1], Move::PenUp(vec![point2(4.0, 0.0), point2(6.0, 0.0)]));
+}
+
+fn plan_segments(plan: &Plan<UnknownUnit>) -> Vec<PathSegment> {
+    plan.moves
+        .iter()
+        .filter_map(|m| match *m {
+            Move::PenDown(ref s) => Some(s.clone()),
+            Move::PenUp(_) => None,
+        })
+        .collect()
+}
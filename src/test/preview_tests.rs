@@ -0,0 +1,62 @@
+use ::{preview, preview_plan, Plan, PreviewOptions};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const RED: [u8; 4] = [255, 0, 0, 255];
+
+fn options() -> PreviewOptions {
+    PreviewOptions {
+        width: 11,
+        height: 11,
+        margin: 0,
+        ..PreviewOptions::default()
+    }
+}
+
+fn rungs() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(0.0, 10.0), point2(10.0, 10.0)], EPSILON),
+    ]
+}
+
+#[test]
+fn lines_are_scaled_to_fit() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(100.0, 0.0)], EPSILON);
+    let image = preview(&[line], &options());
+    assert_eq!(image.rgba.len(), 11 * 11 * 4);
+    // A flat line is centered vertically.
+    for x in 0..11 {
+        assert_eq!(image.pixel(x, 5), BLACK);
+    }
+    assert_eq!(image.pixel(0, 0), WHITE);
+}
+
+#[test]
+fn pen_up_moves_use_their_own_color() {
+    let plan = Plan::from_ordered(rungs(), point2(0.0, 0.0));
+    let image = preview_plan(&plan, &options());
+    assert_eq!(image.pixel(0, 0), BLACK);
+    assert_eq!(image.pixel(10, 10), BLACK);
+    assert_eq!(image.pixel(5, 5), RED);
+
+    let hidden = preview_plan(&plan, &PreviewOptions { pen_up: None, ..options() });
+    assert_eq!(hidden.pixel(5, 5), WHITE);
+}
+
+#[test]
+fn writes_a_png() {
+    let mut bytes = vec![];
+    preview(&rungs(), &options()).write_png(&mut bytes).unwrap();
+    assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+}
+
+#[test]
+fn nothing_to_draw_is_blank() {
+    let image = preview::<UnknownUnit>(&[], &options());
+    assert!(image.rgba.chunks(4).all(|p| p == WHITE));
+}
@@ -1,4 +1,4 @@
-use ::prune;
+use ::{prune, prune_with_removed};
 use super::util::*;
 use permutohedron::heap_recursive as permute;
 use euclid::{point2, UnknownUnit};
@@ -247,3 +247,64 @@ fn removes_a_dangling_line_off_the_back_of_a_cycle() {
         ..p
     });
 }
+
+#[test]
+fn removed_segments_are_returned() {
+    let input = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(1.0, 1.0), point2(2.0, 2.0)],
+        vec![point2(2.0, 2.0), point2(3.0, 2.0)],
+    ];
+    let (kept, removed) = prune_with_removed(input, EPSILON, true);
+    assert_same(
+        &kept,
+        &[
+            PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 1.0)], EPSILON),
+            PathSegment::new(vec![point2(1.0, 1.0), point2(0.0, 0.0)], EPSILON),
+        ],
+        false,
+    ).unwrap();
+    assert_same(
+        &removed,
+        &[
+            PathSegment::new(vec![point2(1.0, 1.0), point2(2.0, 2.0)], EPSILON),
+            PathSegment::new(vec![point2(2.0, 2.0), point2(3.0, 2.0)], EPSILON),
+        ],
+        false,
+    ).unwrap();
+}
+
+#[test]
+fn isolated_closed_segments_are_only_kept_by_prune_open_in_place() {
+    let square = vec![
+        point2(0.0, 0.0),
+        point2(1.0, 0.0),
+        point2(1.0, 1.0),
+        point2(0.0, 1.0),
+        point2(0.0, 0.0),
+    ];
+    for &only_starts in &[true, false] {
+        let output: Vec<PathSegment> = prune(vec![square.clone()], EPSILON, only_starts);
+        assert!(output.is_empty());
+
+        let mut dual_qt = ::DualQuadTree::from_segments(vec![square.clone()], EPSILON);
+        ::prune_open_in_place(&mut dual_qt, only_starts);
+        let output: Vec<PathSegment> = dual_qt.into_iter().collect();
+        assert_eq!(output.len(), 1);
+        assert!(output[0].closed);
+    }
+}
+
+#[test]
+fn too_short_segments_are_returned_as_removed() {
+    let input = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(1.0, 1.0), point2(0.0, 0.0)],
+        vec![point2(5.0, 5.0), point2(5.0, 5.0)],
+    ];
+    let (kept, removed) = prune_with_removed(input, EPSILON, true);
+    assert_eq!(kept.len(), 2);
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].path[0], point2(5.0, 5.0));
+}
@@ -0,0 +1,69 @@
+//! The Python bindings can't be loaded here, so these run the same steps as
+//! `python/src/lib.rs`: tuples in, `run_pipeline`, and flat `(n, 2)` rows
+//! out with closed paths repeating their first point.
+
+use ::{batch_from_tuples, flat_from_points, points_from_flat, run_pipeline, Epsilon, PipelineConfig};
+use euclid::UnknownUnit;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+type Points = ::Points<UnknownUnit>;
+
+fn stitch(polylines: Vec<Vec<(f32, f32)>>, epsilon: Epsilon, prune: bool) -> Vec<Vec<f32>> {
+    let input: Vec<Points> = batch_from_tuples(polylines);
+    let config = PipelineConfig {
+        epsilon: epsilon,
+        prune: prune,
+        ..PipelineConfig::default()
+    };
+    let output: Vec<PathSegment> = run_pipeline(input, &config);
+    output
+        .into_iter()
+        .map(|segment| {
+            let points: Points = segment.into();
+            flat_from_points(&points)
+        })
+        .collect()
+}
+
+#[test]
+fn closed_paths_repeat_their_first_point() {
+    let out = stitch(
+        vec![vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)], vec![(1.0, 1.0), (0.0, 0.0)]],
+        Epsilon::Absolute(0.001),
+        false,
+    );
+    assert_eq!(out.len(), 1);
+    let rows = &out[0];
+    assert_eq!(rows.len(), 8);
+    assert_eq!(&rows[..2], &rows[6..]);
+}
+
+#[test]
+fn open_paths_come_back_as_rows_of_pairs() {
+    let out = stitch(vec![vec![(0.0, 0.0), (1.0, 0.0)], vec![(1.0, 0.0), (2.0, 5.0)]], Epsilon::Absolute(0.001), false);
+    assert_eq!(out.len(), 1);
+    let points: Points = points_from_flat(&out[0]);
+    assert_eq!(points.len(), 3);
+    assert_eq!((points[2].x, points[2].y), (2.0, 5.0));
+}
+
+#[test]
+fn relative_epsilon_scales_with_the_drawing() {
+    let input = vec![vec![(0.0, 0.0), (100.0, 0.0)], vec![(100.5, 0.0), (200.0, 0.0)]];
+    assert_eq!(stitch(input.clone(), Epsilon::Absolute(0.001), false).len(), 2);
+    assert_eq!(stitch(input, Epsilon::RelativeToBounds(0.01), false).len(), 1);
+}
+
+#[test]
+fn prune_drops_dangling_lines() {
+    let out = stitch(
+        vec![
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)],
+            vec![(5.0, 5.0), (6.0, 5.0)],
+        ],
+        Epsilon::Absolute(0.001),
+        true,
+    );
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].len(), 8);
+}
@@ -0,0 +1,63 @@
+use ::resample;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn assert_spacing(segment: &PathSegment, spacing: f32) {
+    let mut points = segment.path.to_vec();
+    if segment.closed {
+        points.push(points[0]);
+    }
+    for w in points.windows(2) {
+        assert!(((w[1] - w[0]).length() - spacing).abs() < 1.0e-4);
+    }
+}
+
+#[test]
+fn open_endpoints_are_kept() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(3.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let out = resample(segment, 2.0);
+    assert!(!out.closed);
+    assert_eq!(out.path.len(), 6);
+    assert_eq!(out.path[0], point2(0.0, 0.0));
+    assert_eq!(out.path[5], point2(10.0, 0.0));
+    assert_spacing(&out, 2.0);
+}
+
+#[test]
+fn spacing_is_adjusted_to_fit() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let out = resample(segment, 3.0);
+    assert_eq!(out.path.len(), 4);
+    assert_spacing(&out, 10.0 / 3.0);
+}
+
+#[test]
+fn closed_segments_stay_closed() {
+    let segment = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(4.0, 0.0),
+            point2(4.0, 4.0),
+            point2(0.0, 4.0),
+            point2(0.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let out = resample(segment, 1.0);
+    assert!(out.closed);
+    assert_eq!(out.path.len(), 16);
+    assert_eq!(out.path[0], point2(0.0, 0.0));
+    assert_eq!(out.path[4], point2(4.0, 0.0));
+    assert_spacing(&out, 1.0);
+}
+
+#[test]
+fn lengths_are_recomputed() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let out = resample(segment, 1.0);
+    assert_eq!(out.path.len(), 11);
+    assert!((out.length() - 10.0).abs() < 1.0e-4);
+}
@@ -0,0 +1,78 @@
+use ::{connect_obvious_in_place, restitch, DqtId, DualQuadTree};
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn stitched(segments: Vec<Vec<Point>>) -> DualQuadTree<UnknownUnit> {
+    let mut dual_qt = DualQuadTree::from_segments(segments, EPSILON);
+    connect_obvious_in_place(&mut dual_qt, false, false);
+    dual_qt
+}
+
+fn id_starting_at(dual_qt: &DualQuadTree<UnknownUnit>, point: Point) -> DqtId {
+    dual_qt
+        .iter()
+        .find(|&(_, s)| s.path[0] == point || s.path[s.path.len() - 1] == point)
+        .map(|(id, _)| id)
+        .unwrap()
+}
+
+#[test]
+fn edited_paths_join_their_new_neighbors() {
+    let mut dual_qt = stitched(vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+        vec![point2(5.0, 0.0), point2(6.0, 0.0)],
+    ]);
+    assert_eq!(dual_qt.len(), 2);
+
+    // Drag the end of the first path onto the start of the second.
+    let edited = id_starting_at(&dual_qt, point2(0.0, 0.0));
+    dual_qt.remove_segment(edited).unwrap();
+    let ids = restitch(
+        &mut dual_qt,
+        vec![vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(5.0, 0.0)]],
+        false,
+        false,
+    );
+
+    assert_eq!(ids.len(), 1);
+    assert_eq!(dual_qt.len(), 1);
+    let (_, joined) = dual_qt.iter().next().unwrap();
+    assert_eq!(joined.path.len(), 4);
+}
+
+#[test]
+fn removing_a_path_clears_the_ambiguity_it_caused() {
+    let mut dual_qt = stitched(vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(2.0, 0.0)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+    ]);
+    assert_eq!(dual_qt.len(), 3);
+
+    let spur = id_starting_at(&dual_qt, point2(1.0, 1.0));
+    dual_qt.remove_segment(spur).unwrap();
+    restitch(&mut dual_qt, vec![vec![point2(3.0, 3.0), point2(4.0, 4.0)]], false, false);
+
+    assert_eq!(dual_qt.len(), 2);
+    assert!(dual_qt.iter().any(|(_, s)| s.path.len() == 3));
+}
+
+#[test]
+fn pieces_outside_the_original_bounds_are_indexed() {
+    let mut dual_qt = stitched(vec![vec![point2(0.0, 0.0), point2(1.0, 0.0)]]);
+    let ids = restitch(
+        &mut dual_qt,
+        vec![
+            vec![point2(100.0, 100.0), point2(101.0, 100.0)],
+            vec![point2(101.0, 100.0), point2(102.0, 100.0)],
+        ],
+        false,
+        false,
+    );
+    assert_eq!(ids.len(), 1);
+    assert_eq!(dual_qt.len(), 2);
+}
@@ -0,0 +1,141 @@
+use ::{connect_obvious, connect_obvious_curved, dash, graph_stitch, graph_stitch_with_cost, graph_stitch_with_epsilon,
+       merge_overstrokes, nesting, occlude, order_by_containment, order_travel, order_travel_between, orient,
+       plan_travel, plan_travel_avoiding, plan_travel_between, remove_jogs, remove_zero_area_spurs, resample,
+       sanitize, smooth_chaikin, snap_to_boundaries, snap_to_grid, split_max_length, split_self_intersections,
+       union, CurvedPathSegment, Direction, EndpointIndex, Error, FillRule, Move, Plan, SanitizeReport, Span};
+use euclid::{UnknownUnit, point2};
+use std::f32::{INFINITY, NAN};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn non_finite_points_split_polylines() {
+    let input = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(NAN, 0.0), point2(2.0, 0.0), point2(3.0, 0.0)],
+        vec![point2(0.0, 0.0), point2(0.0, INFINITY)],
+        vec![point2(5.0, 5.0), point2(6.0, 5.0)],
+    ];
+
+    let (out, report) = sanitize(input);
+    assert_eq!(out.len(), 3);
+    assert_eq!(&out[0][..], &[point2(0.0, 0.0), point2(1.0, 0.0)]);
+    assert_eq!(&out[1][..], &[point2(2.0, 0.0), point2(3.0, 0.0)]);
+    assert_eq!(
+        report,
+        SanitizeReport {
+            points_dropped: 2,
+            polylines_split: 1,
+            polylines_dropped: 1,
+        }
+    );
+}
+
+#[test]
+fn try_new_rejects_non_finite_points() {
+    let result = PathSegment::try_new(vec![point2(0.0, 0.0), point2(NAN, 1.0)], EPSILON);
+    assert_eq!(result.err(), Some(Error::NonFinite));
+    let result = PathSegment::try_new(vec![point2(0.0, 0.0), point2(1.0, INFINITY)], EPSILON);
+    assert_eq!(result.err(), Some(Error::NonFinite));
+}
+
+#[test]
+fn connect_obvious_ignores_non_finite_points() {
+    let input = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(NAN, NAN)],
+        vec![point2(1.0, 0.0), point2(1.0, 1.0)],
+        vec![point2(INFINITY, 0.0), point2(1.0, 1.0)],
+    ];
+
+    let out: Vec<PathSegment> = connect_obvious(input, EPSILON, true, false);
+    assert_eq!(out.len(), 1);
+    assert_eq!(&out[0].path[..], &[point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)]);
+}
+
+fn with_a_bad_point() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(
+            vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(NAN, 0.0), point2(2.0, 0.0), point2(3.0, 0.0)],
+            EPSILON,
+        ),
+        PathSegment::new(
+            vec![point2(0.0, 5.0), point2(1.0, 5.0), point2(1.0, INFINITY), point2(0.0, 6.0), point2(0.0, 5.0)],
+            EPSILON,
+        ),
+    ]
+}
+
+fn all_finite(segments: &[PathSegment]) -> bool {
+    segments.iter().all(|s| s.path.iter().all(|p| p.x.is_finite() && p.y.is_finite()))
+}
+
+fn plan_is_finite(plan: &Plan<UnknownUnit>) -> bool {
+    plan.moves.iter().all(|m| match *m {
+        Move::PenDown(ref segment) => all_finite(&[segment.clone()]),
+        Move::PenUp(ref points) => points.iter().all(|p| p.x.is_finite() && p.y.is_finite()),
+    })
+}
+
+#[test]
+fn passes_over_built_segments_drop_non_finite_points() {
+    let input = with_a_bad_point();
+    assert!(all_finite(&split_max_length(input.clone(), 0.5, EPSILON)));
+    assert!(all_finite(&smooth_chaikin(input.clone(), 2, true)));
+    assert!(all_finite(&merge_overstrokes(input.clone(), 0.1, EPSILON)));
+    assert!(all_finite(&occlude(input.clone(), &input, EPSILON)));
+    assert!(all_finite(&remove_zero_area_spurs(input.clone(), EPSILON)));
+    assert!(all_finite(&remove_jogs(input.clone(), 0.1)));
+    assert!(all_finite(&dash(input.clone(), &[0.5, 0.5], 0.0)));
+    assert!(all_finite(&snap_to_grid(input.clone(), 0.5)));
+    assert!(all_finite(&snap_to_boundaries(input.clone(), 0.1, EPSILON)));
+    assert!(all_finite(&orient(input.clone(), Direction::IncreasingX, true)));
+    assert!(all_finite(&union(&input, &input, FillRule::NonZero, EPSILON)));
+    assert!(all_finite(&graph_stitch(input.clone())));
+    assert!(all_finite(&graph_stitch_with_epsilon(input.clone(), EPSILON)));
+    assert!(all_finite(&graph_stitch_with_cost(input.clone(), EPSILON, |_, _, _, _| 0.0)));
+    assert!(all_finite(&order_by_containment(input.clone())));
+
+    let bad = point2(NAN, 0.0);
+    for &start in &[point2(0.0, 0.0), bad] {
+        assert!(all_finite(&order_travel(input.clone(), start)));
+        assert!(all_finite(&order_travel_between(input.clone(), start, Some(bad))));
+        assert!(plan_is_finite(&plan_travel(input.clone(), start)));
+        assert!(plan_is_finite(&plan_travel_between(input.clone(), start, Some(bad))));
+        assert!(plan_is_finite(&plan_travel_avoiding(input.clone(), start, &input)));
+    }
+
+    assert_eq!(nesting(&input), vec![None, None]);
+    let index = EndpointIndex::new(&input);
+    assert_eq!(index.nearest_endpoint(bad, 1), vec![]);
+    assert_eq!(index.endpoints_within(bad, 1.0), vec![]);
+    assert_eq!(index.nearest_endpoint(point2(0.0, 5.0), 1)[0].segment, 1);
+
+    let curved = vec![
+        CurvedPathSegment::new(
+            point2(0.0, 0.0),
+            vec![Span::Line { to: point2(1.0, 0.0) }, Span::Line { to: bad }, Span::Line { to: point2(2.0, 0.0) }],
+            EPSILON,
+        ),
+        CurvedPathSegment::new(point2(1.0, 0.0), vec![Span::Line { to: point2(1.0, 1.0) }], EPSILON),
+    ];
+    let out = connect_obvious_curved(curved, EPSILON);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].spans.len(), 2);
+
+    for segment in input {
+        assert!(all_finite(&[resample(segment.clone(), 0.5)]));
+        assert!(all_finite(&split_self_intersections(segment, false, EPSILON)));
+    }
+}
+
+#[test]
+fn a_closed_segment_is_cut_only_at_its_bad_point() {
+    let out = split_max_length(vec![with_a_bad_point().remove(1)], 100.0, EPSILON);
+    assert_eq!(out.len(), 1);
+    assert!(!out[0].closed);
+    assert_eq!(
+        &out[0].path[..],
+        &[point2(0.0, 6.0), point2(0.0, 5.0), point2(1.0, 5.0)][..]
+    );
+}
@@ -0,0 +1,59 @@
+use ::split_self_intersections;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+// Goes right, loops up and around, crosses itself at (2, 0) and carries on
+// to the right.
+fn looped() -> PathSegment {
+    PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(4.0, 0.0),
+            point2(4.0, 2.0),
+            point2(2.0, 2.0),
+            point2(2.0, -1.0),
+            point2(5.0, -1.0),
+        ],
+        EPSILON,
+    )
+}
+
+#[test]
+fn simple_paths_are_untouched() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)],
+        EPSILON,
+    );
+    assert_eq!(split_self_intersections(segment.clone(), true, EPSILON), vec![segment]);
+}
+
+#[test]
+fn extracting_a_loop() {
+    let out = split_self_intersections(looped(), true, EPSILON);
+    assert_eq!(out.len(), 2);
+
+    assert!(out[0].closed);
+    assert_eq!(out[0].path.len(), 4);
+    assert_eq!(out[0].path[0], point2(2.0, 0.0));
+
+    assert!(!out[1].closed);
+    assert_eq!(
+        out[1].path.to_vec(),
+        vec![point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, -1.0), point2(5.0, -1.0)]
+    );
+}
+
+#[test]
+fn splitting_at_the_crossing() {
+    let out = split_self_intersections(looped(), false, EPSILON);
+    assert_eq!(out.len(), 3);
+    assert!(out[0].closed);
+    assert_eq!(out[1].path.to_vec(), vec![point2(0.0, 0.0), point2(2.0, 0.0)]);
+    assert_eq!(
+        out[2].path.to_vec(),
+        vec![point2(2.0, 0.0), point2(2.0, -1.0), point2(5.0, -1.0)]
+    );
+}
@@ -0,0 +1,61 @@
+use ::smooth_chaikin;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn corner() -> PathSegment {
+    PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 4.0)], EPSILON)
+}
+
+#[test]
+fn pinned_endpoints_are_kept() {
+    let out = smooth_chaikin(vec![corner()], 1, true);
+    assert_eq!(
+        out[0].path.to_vec(),
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.0),
+            point2(3.0, 0.0),
+            point2(4.0, 1.0),
+            point2(4.0, 3.0),
+            point2(4.0, 4.0),
+        ]
+    );
+}
+
+#[test]
+fn unpinned_endpoints_pull_in() {
+    let out = smooth_chaikin(vec![corner()], 1, false);
+    assert_eq!(out[0].path.len(), 4);
+    assert_eq!(out[0].path[0], point2(1.0, 0.0));
+    assert_eq!(out[0].path[3], point2(4.0, 3.0));
+}
+
+#[test]
+fn straight_lines_are_untouched() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(4.0, 0.0)], EPSILON);
+    assert_eq!(smooth_chaikin(vec![line.clone()], 3, true), vec![line]);
+}
+
+#[test]
+fn closed_segments_stay_closed() {
+    let square = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(4.0, 0.0),
+            point2(4.0, 4.0),
+            point2(0.0, 4.0),
+            point2(0.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let out = smooth_chaikin(vec![square], 2, true);
+    assert!(out[0].closed);
+    assert_eq!(out[0].path.len(), 16);
+
+    let path = &out[0].path;
+    let closing = (path[0] - path[path.len() - 1]).length();
+    assert!(out[0].length() + closing < 16.0);
+}
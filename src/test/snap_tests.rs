@@ -0,0 +1,52 @@
+use ::{connect_obvious, snap_to_grid};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn points_are_rounded() {
+    let segment = PathSegment::new(vec![point2(0.1, 0.9), point2(2.2, 3.6)], EPSILON);
+    let out = snap_to_grid(vec![segment], 1.0);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 1.0), point2(2.0, 4.0)]);
+    assert_eq!(out[0].length_2(), 13.0);
+}
+
+#[test]
+fn duplicate_points_are_merged() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(0.1, 0.0), point2(0.2, 0.1), point2(3.0, 0.0)],
+        EPSILON,
+    );
+    let out = snap_to_grid(vec![segment], 1.0);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.0), point2(3.0, 0.0)]);
+}
+
+#[test]
+fn collapsed_segments_are_dropped() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(0.2, 0.1)], EPSILON);
+    assert!(snap_to_grid(vec![segment], 1.0).is_empty());
+}
+
+#[test]
+fn nearly_closed_segments_become_closed() {
+    let segment = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 4.0), point2(0.2, 0.1)],
+        EPSILON,
+    );
+    assert!(!segment.closed);
+    let out = snap_to_grid(vec![segment], 1.0);
+    assert!(out[0].closed);
+    assert_eq!(out[0].path.len(), 3);
+}
+
+#[test]
+fn snapping_helps_stitching() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(5.02, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(4.98, 0.01), point2(10.0, 0.0)], EPSILON);
+    let snapped = snap_to_grid(vec![a, b], 0.5);
+    let joined: Vec<PathSegment> = connect_obvious(snapped, EPSILON, false, false);
+    assert_eq!(joined.len(), 1);
+}
@@ -0,0 +1,92 @@
+use ::{connect_obvious, stitch_segment_soup, Epsilon};
+use super::util::*;
+use euclid::{TypedPoint2D, UnknownUnit, point2};
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn check(pairs: Vec<(Point, Point)>) {
+    let soup: Vec<PathSegment> = stitch_segment_soup(pairs.clone(), EPSILON);
+    let general: Vec<PathSegment> = connect_obvious(
+        pairs.into_iter().map(|(a, b)| vec![a, b]),
+        EPSILON,
+        false,
+        false,
+    );
+    assert_same(&soup, &general, true).unwrap();
+}
+
+#[test]
+fn loops_and_chains_match_connect_obvious() {
+    check(vec![
+        (point2(0.0, 0.0), point2(1.0, 0.0)),
+        (point2(1.0, 1.0), point2(1.0, 0.0)),
+        (point2(1.0, 1.0), point2(0.0, 1.0)),
+        (point2(0.0, 0.0), point2(0.0, 1.0)),
+        (point2(5.0, 0.0), point2(6.0, 0.0)),
+        (point2(7.0, 0.0), point2(6.0, 0.0)),
+    ]);
+}
+
+#[test]
+fn ends_within_epsilon_are_joined() {
+    let out: Vec<PathSegment> = stitch_segment_soup(
+        vec![
+            (point2(5.0, 0.0), point2(6.0, 0.0)),
+            (point2(6.0, 0.0005), point2(7.0, 0.0)),
+        ],
+        EPSILON,
+    );
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].path.len(), 3);
+}
+
+#[test]
+fn ambiguous_points_are_not_joined() {
+    check(vec![
+        (point2(0.0, 0.0), point2(1.0, 0.0)),
+        (point2(1.0, 0.0), point2(2.0, 0.0)),
+        (point2(1.0, 0.0), point2(1.0, 1.0)),
+        (point2(1.0, 1.0), point2(1.0, 2.0)),
+    ]);
+}
+
+#[test]
+fn degenerate_pairs_are_dropped() {
+    let out: Vec<PathSegment> = stitch_segment_soup(
+        vec![
+            (point2(0.0, 0.0), point2(0.0, 0.0)),
+            (point2(0.0, 0.0), point2(::std::f32::NAN, 0.0)),
+        ],
+        EPSILON,
+    );
+    assert!(out.is_empty());
+}
+
+#[test]
+fn relative_epsilon_is_resolved_against_the_pairs() {
+    let pairs = vec![
+        (point2(0.0, 0.0), point2(100.0, 0.0)),
+        (point2(100.5, 0.0), point2(200.0, 0.0)),
+    ];
+    let absolute: Vec<PathSegment> = stitch_segment_soup(pairs.clone(), EPSILON);
+    let relative: Vec<PathSegment> = stitch_segment_soup(pairs, Epsilon::RelativeToBounds(0.01));
+    assert_eq!(absolute.len(), 2);
+    assert_eq!(relative.len(), 1);
+}
+
+#[test]
+fn ends_are_grouped_transitively() {
+    // The first and last ends are 0.0016 apart, more than epsilon, but each
+    // is within epsilon of the middle pair, so all four meet at one point.
+    let pairs = vec![
+        (point2(-1.0, 0.0), point2(0.0, 0.0)),
+        (point2(0.0008, 0.0), point2(0.0008, -1.0)),
+        (point2(0.0016, 0.0), point2(1.0, 0.0)),
+    ];
+    let out: Vec<PathSegment> = stitch_segment_soup(pairs, EPSILON);
+    // Three ends at one point is ambiguous, so nothing is joined.
+    assert_eq!(out.len(), 3);
+}
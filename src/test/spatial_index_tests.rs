@@ -0,0 +1,100 @@
+use ::{run_pipeline, DualQuadTree, IndexBackend, LayerId, PipelineConfig};
+use euclid::{TypedPoint2D, TypedRect, UnknownUnit, point2, size2};
+use spatial_index::Index;
+use std::f32::INFINITY;
+
+type Point = TypedPoint2D<f32, UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+/// A staircase of unit steps, cut into two-point segments with every other
+/// one flipped.
+fn staircase(steps: usize) -> Vec<Vec<Point>> {
+    let mut points = vec![point2(0.0, 0.0)];
+    for i in 0..steps {
+        let (x, y) = (i as f32, i as f32);
+        points.push(point2(x + 1.0, y));
+        points.push(point2(x + 1.0, y + 1.0));
+    }
+    points
+        .windows(2)
+        .enumerate()
+        .map(|(i, w)| if i % 2 == 0 { vec![w[0], w[1]] } else { vec![w[1], w[0]] })
+        .collect()
+}
+
+fn sorted(mut paths: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    for path in &mut paths {
+        if (path[0].x, path[0].y) > (path[path.len() - 1].x, path[path.len() - 1].y) {
+            path.reverse();
+        }
+    }
+    paths.sort_by(|a, b| (a[0].x, a[0].y).partial_cmp(&(b[0].x, b[0].y)).unwrap());
+    paths
+}
+
+fn run(backend: IndexBackend) -> Vec<Vec<Point>> {
+    let config = PipelineConfig {
+        epsilon: EPSILON.into(),
+        allow_ambiguous: true,
+        index: backend,
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(staircase(50), &config);
+    sorted(out.into_iter().map(|s| s.path.to_vec()).collect())
+}
+
+#[test]
+fn grid_and_quadtree_agree() {
+    let tree = run(IndexBackend::default());
+    let grid = run(IndexBackend::Grid { cell_size: 4.0 });
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree, grid);
+}
+
+#[test]
+fn tiny_quadtree_nodes_still_work() {
+    let tiny = run(IndexBackend::QuadTree { max_depth: 2, max_children: 1 });
+    assert_eq!(tiny, run(IndexBackend::default()));
+}
+
+#[test]
+fn degenerate_cell_size_falls_back() {
+    for &cell_size in &[0.0, -1.0, ::std::f32::NAN] {
+        let out = run(IndexBackend::Grid { cell_size: cell_size });
+        assert_eq!(out.len(), 1);
+    }
+}
+
+#[test]
+fn grid_forgets_removed_segments() {
+    let segments = vec![
+        vec![point2(0.0, 0.0), point2(1.0, 0.0)],
+        vec![point2(5.0, 0.0), point2(6.0, 0.0)],
+    ];
+    let mut dual_qt: DualQuadTree<UnknownUnit> =
+        DualQuadTree::from_segments_with_index(segments, EPSILON, IndexBackend::Grid { cell_size: 4.0 });
+    assert!(dual_qt.query_forward(point2(0.0, 0.0), LayerId(0), EPSILON, false, false).is_some());
+    assert!(dual_qt.query_forward(point2(0.0, 0.0), LayerId(0), EPSILON, false, false).is_none());
+    assert_eq!(dual_qt.len(), 1);
+}
+
+#[test]
+fn grid_queries_only_walk_occupied_cells() {
+    let bounds = TypedRect::new(point2(0.0, 0.0), size2(1.0, 1.0));
+    let mut index: Index<u32, UnknownUnit> = Index::new(IndexBackend::Grid { cell_size: 1.0 }, bounds, 1.0e-6);
+    index.insert(0, TypedRect::new(point2(0.5, 0.5), size2(0.0, 0.0)));
+    index.insert(1, TypedRect::new(point2(0.5, 0.5), size2(0.000005, 0.000005)));
+    // Far too many cells to store it in each of them.
+    index.insert(2, TypedRect::new(point2(-1.0e30, 0.0), size2(2.0e30, 1.0)));
+
+    let hits = |rect| {
+        let mut hits: Vec<u32> = index.query(rect).into_iter().map(|(&item, _)| item).collect();
+        hits.sort();
+        hits
+    };
+    assert_eq!(hits(TypedRect::new(point2(-1.0e30, -1.0e30), size2(2.0e30, 2.0e30))), vec![0, 1, 2]);
+    assert_eq!(hits(TypedRect::new(point2(0.0, 0.0), size2(INFINITY, INFINITY))), vec![0, 1, 2]);
+    assert_eq!(hits(TypedRect::new(point2(0.5, 0.5), size2(0.0, 0.0))), vec![0, 1, 2]);
+    assert_eq!(hits(TypedRect::new(point2(0.6, 0.6), size2(0.0, 0.0))), vec![2]);
+}
@@ -0,0 +1,63 @@
+use ::{split_max_length, split_max_points};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn line(count: usize) -> PathSegment {
+    PathSegment::new(
+        (0..count).map(|i| point2(i as f32, 0.0)).collect::<Vec<_>>(),
+        EPSILON,
+    )
+}
+
+#[test]
+fn short_segments_are_untouched() {
+    let out = split_max_length(vec![line(3)], 10.0, EPSILON);
+    assert_eq!(out, vec![line(3)]);
+}
+
+#[test]
+fn pieces_share_their_endpoints() {
+    let out = split_max_length(vec![line(6)], 2.0, EPSILON);
+    assert_eq!(out.len(), 3);
+    for piece in &out {
+        assert!(piece.length() <= 2.0);
+    }
+    assert_eq!(out[0].path.last(), out[1].path.first());
+    assert_eq!(out[1].path.last(), out[2].path.first());
+}
+
+#[test]
+fn long_edges_get_their_own_piece() {
+    let segment = PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON);
+    let out = split_max_length(vec![segment.clone()], 1.0, EPSILON);
+    assert_eq!(out, vec![segment]);
+}
+
+#[test]
+fn split_by_point_count() {
+    let out = split_max_points(vec![line(10)], 4, EPSILON);
+    assert_eq!(out.len(), 3);
+    assert!(out.iter().all(|s| s.path.len() <= 4));
+    let total: usize = out.iter().map(|s| s.path.len()).sum();
+    assert_eq!(total, 10 + 2);
+}
+
+#[test]
+fn closed_segments_are_opened_when_split() {
+    let square = PathSegment::new(
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.0),
+            point2(1.0, 1.0),
+            point2(0.0, 1.0),
+            point2(0.0, 0.0),
+        ],
+        EPSILON,
+    );
+    let out = split_max_points(vec![square], 3, EPSILON);
+    assert_eq!(out.len(), 2);
+    assert!(out.iter().all(|s| !s.closed));
+}
@@ -0,0 +1,65 @@
+use ::snap_to_boundaries;
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn wall() -> PathSegment {
+    PathSegment::new(vec![point2(0.0, 0.0), point2(10.0, 0.0)], EPSILON)
+}
+
+#[test]
+fn near_miss_becomes_a_t_junction() {
+    let stub = PathSegment::new(vec![point2(5.0, 0.3), point2(5.0, 5.0)], EPSILON);
+    let out = snap_to_boundaries(vec![wall(), stub], 0.5, EPSILON);
+
+    let paths: Vec<_> = out.iter().map(|s| s.path.to_vec()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            vec![point2(0.0, 0.0), point2(5.0, 0.0)],
+            vec![point2(5.0, 0.0), point2(10.0, 0.0)],
+            vec![point2(5.0, 0.0), point2(5.0, 0.3), point2(5.0, 5.0)],
+        ]
+    );
+}
+
+#[test]
+fn gaps_wider_than_max_gap_are_left_alone() {
+    let stub = PathSegment::new(vec![point2(5.0, 1.0), point2(5.0, 5.0)], EPSILON);
+    let input = vec![wall(), stub];
+    assert_eq!(snap_to_boundaries(input.clone(), 0.5, EPSILON), input);
+}
+
+#[test]
+fn closed_targets_gain_a_corner() {
+    let square = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 4.0), point2(0.0, 4.0), point2(0.0, 0.0)],
+        EPSILON,
+    );
+    let stub = PathSegment::new(vec![point2(2.0, -0.2), point2(2.0, -3.0)], EPSILON);
+    let out = snap_to_boundaries(vec![square, stub], 0.5, EPSILON);
+
+    assert_eq!(out.len(), 2);
+    assert!(out[0].closed);
+    assert!(out[0].path.contains(&point2(2.0, 0.0)));
+    assert_eq!(out[0].path.len(), 5);
+    assert_eq!(out[1].path[0], point2(2.0, 0.0));
+}
+
+#[test]
+fn ends_near_ends_are_left_to_stitching() {
+    let stub = PathSegment::new(vec![point2(10.2, 0.0), point2(15.0, 0.0)], EPSILON);
+    let input = vec![wall(), stub];
+    assert_eq!(snap_to_boundaries(input.clone(), 0.5, EPSILON), input);
+}
+
+#[test]
+fn ends_already_on_the_body_split_it() {
+    let stub = PathSegment::new(vec![point2(3.0, 0.0), point2(3.0, 5.0)], EPSILON);
+    let out = snap_to_boundaries(vec![wall(), stub], 0.5, EPSILON);
+    assert_eq!(out.len(), 3);
+    assert_eq!(out[0].path.to_vec(), vec![point2(0.0, 0.0), point2(3.0, 0.0)]);
+    assert_eq!(out[2].path.to_vec(), vec![point2(3.0, 0.0), point2(3.0, 5.0)]);
+}
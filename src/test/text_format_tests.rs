@@ -0,0 +1,100 @@
+use ::{read_layers, read_segments, write_layers, write_segments, Error, TextLayer};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+type Layer = TextLayer<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn shapes() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(vec![point2(0.1, -2.5), point2(1.0e-7, 3.0e8), point2(1.0 / 3.0, 7.0)], EPSILON),
+        PathSegment::new(
+            vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 0.0)],
+            EPSILON,
+        ),
+    ]
+}
+
+fn layer(name: &str, metadata: Vec<(&str, &str)>, segments: Vec<PathSegment>) -> Layer {
+    TextLayer {
+        name: name.into(),
+        metadata: metadata.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+        segments: segments,
+    }
+}
+
+#[test]
+fn segments_round_trip_exactly() {
+    let segments = shapes();
+    assert!(segments[1].closed);
+    let text = write_segments(&segments);
+    assert_eq!(read_segments::<UnknownUnit>(&text).unwrap(), segments);
+}
+
+#[test]
+fn touching_open_paths_stay_open() {
+    let text = "open 0,0 1,0 1,1 0,0\n";
+    let segments: Vec<PathSegment> = read_segments(text).unwrap();
+    assert!(!segments[0].closed);
+    assert_eq!(segments[0].path.len(), 4);
+    assert_eq!(write_segments(&segments), text);
+}
+
+#[test]
+fn layers_round_trip() {
+    let layers = vec![
+        layer("walls", vec![("pen", "0.3 mm"), ("colour", "")], shapes()),
+        layer("road signs", vec![], vec![shapes()[0].clone()]),
+    ];
+    let text = write_layers(&layers).unwrap();
+    assert_eq!(read_layers::<UnknownUnit>(&text).unwrap(), layers);
+}
+
+#[test]
+fn comments_default_layer_and_repeated_layers() {
+    let text = "# a drawing\n\nopen 0,0 1,0\nlayer a\nopen 2,0 3,0\nlayer 0\nclosed 0,0 1,0 1,1\nlayer a\nopen 4,0 5,0\n";
+    let layers: Vec<Layer> = read_layers(text).unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].name, "0");
+    assert_eq!(layers[0].segments.len(), 2);
+    assert!(layers[0].segments[1].closed);
+    assert_eq!(layers[1].name, "a");
+    assert_eq!(layers[1].segments.len(), 2);
+}
+
+#[test]
+fn bad_input_is_rejected() {
+    let read = |text: &str| read_segments::<UnknownUnit>(text).err();
+    assert_eq!(read("line 0,0 1,1"), Some(Error::Malformed));
+    assert_eq!(read("open 0,0 1"), Some(Error::Malformed));
+    assert_eq!(read("open 0,0"), Some(Error::TooFewPoints));
+    assert_eq!(read("open 0,0 inf,1"), Some(Error::NonFinite));
+}
+
+#[test]
+fn empty_layers_round_trip() {
+    let layers = vec![layer("0", vec![], vec![]), layer("a", vec![], shapes()), layer("b", vec![], vec![])];
+    let text = write_layers(&layers).unwrap();
+    assert_eq!(read_layers::<UnknownUnit>(&text).unwrap(), layers);
+
+    // Without a `layer 0` line the empty default layer is left out.
+    assert_eq!(read_layers::<UnknownUnit>("layer a\n").unwrap(), vec![layer("a", vec![], vec![])]);
+}
+
+#[test]
+fn whitespace_in_names_is_kept() {
+    let layers = vec![layer(" a ", vec![], shapes()), layer("a", vec![], shapes()), layer("", vec![], shapes())];
+    let text = write_layers(&layers).unwrap();
+    assert_eq!(read_layers::<UnknownUnit>(&text).unwrap(), layers);
+}
+
+#[test]
+fn unwritable_names_and_metadata_are_rejected() {
+    let write = |layer: Layer| write_layers(&[layer]).err();
+    assert_eq!(write(layer("a\nopen 0,0 1,1", vec![], vec![])), Some(Error::Malformed));
+    assert_eq!(write(layer("a", vec![("pen width", "1")], vec![])), Some(Error::Malformed));
+    assert_eq!(write(layer("a", vec![("", "1")], vec![])), Some(Error::Malformed));
+    assert_eq!(write(layer("a", vec![("pen", "1\r")], vec![])), Some(Error::Malformed));
+    assert_eq!(read_layers::<UnknownUnit>("meta\n").err(), Some(Error::Malformed));
+}
@@ -0,0 +1,50 @@
+use ::{run_pipeline, PipelineConfig};
+use euclid::{UnknownUnit, point2};
+use log::{self, Log, Metadata, Record, LevelFilter};
+use std::sync::Mutex;
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+struct Recorder {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "line_stitch"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.lines.lock().unwrap().push(format!("{}", record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn passes_log_their_decisions() {
+    let recorder: &'static Recorder = Box::leak(Box::new(Recorder { lines: Mutex::new(vec![]) }));
+    // Only one logger can ever be installed, so this test owns it.
+    log::set_logger(recorder).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let square = vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], 0.001),
+        PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0)], 0.001),
+        PathSegment::new(vec![point2(1.0, 1.0), point2(0.0, 1.0)], 0.001),
+        PathSegment::new(vec![point2(0.0, 1.0), point2(0.0, 0.0)], 0.001),
+        PathSegment::new(vec![point2(5.0, 5.0), point2(6.0, 5.0)], 0.001),
+    ];
+    let config = PipelineConfig { prune: true, ..PipelineConfig::default() };
+    run_pipeline(square, &config);
+
+    let lines = recorder.lines.lock().unwrap();
+    let has = |prefix: &str| lines.iter().any(|l| l.starts_with(prefix));
+    assert!(has("connect_obvious: start"));
+    assert!(has("connect_obvious: done, 3 joins"));
+    assert!(has("join "));
+    assert!(has("prune DqtId"));
+    assert!(has("pipeline: done"));
+}
@@ -0,0 +1,36 @@
+use ::{transform, transform_units};
+use euclid::{TypedTransform2D, UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+struct Mm;
+
+#[test]
+fn lengths_follow_the_transform() {
+    let square = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 1.0), point2(0.0, 0.0)],
+        EPSILON,
+    );
+    let out = transform(vec![square], &TypedTransform2D::create_scale(2.0, 3.0), EPSILON);
+    assert!(out[0].closed);
+    assert_eq!(&out[0].path[..], &[point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 3.0), point2(0.0, 3.0)]);
+    assert_eq!(out[0].length(), 7.0);
+}
+
+#[test]
+fn squashing_can_close_segments() {
+    let u = PathSegment::new(vec![point2(0.0, 0.0), point2(0.0, 1.0), point2(1.0, 1.0), point2(1.0, 0.0)], EPSILON);
+    let out = transform(vec![u], &TypedTransform2D::create_scale(0.0001, 1.0), EPSILON);
+    assert!(out[0].closed);
+    assert_eq!(out[0].path.len(), 3);
+}
+
+#[test]
+fn units_can_change() {
+    let line = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON);
+    let inches_to_mm: TypedTransform2D<f32, UnknownUnit, Mm> = TypedTransform2D::create_scale(25.4, 25.4);
+    let out: Vec<::PathSegment<Mm>> = transform_units(vec![line], &inches_to_mm, EPSILON);
+    assert_eq!(out[0].length(), 25.4);
+}
@@ -0,0 +1,86 @@
+use ::{order_travel, order_travel_between, plan_travel_between, travel_distance, Move};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+#[test]
+fn no_segments() {
+    let out = order_travel(Vec::<PathSegment>::new(), point2(0.0, 0.0));
+    assert!(out.is_empty());
+}
+
+#[test]
+fn closest_segment_is_drawn_first() {
+    let far = PathSegment::new(vec![point2(10.0, 0.0), point2(11.0, 0.0)], EPSILON);
+    let near = PathSegment::new(vec![point2(1.0, 0.0), point2(2.0, 0.0)], EPSILON);
+
+    let out = order_travel(vec![far.clone(), near.clone()], point2(0.0, 0.0));
+    assert_eq!(out, vec![near, far]);
+}
+
+#[test]
+fn open_segments_are_reversed_when_closer() {
+    let a = PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON);
+    let b = PathSegment::new(vec![point2(5.0, 0.0), point2(1.0, 0.0)], EPSILON);
+
+    let out = order_travel(vec![b, a.clone()], point2(0.0, 0.0));
+    assert_eq!(out[0], a);
+    assert_eq!(out[1].path[0], point2(1.0, 0.0));
+    assert_eq!(travel_distance(&out, point2(0.0, 0.0)), 0.0);
+}
+
+fn ladder() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(vec![point2(0.0, 0.5), point2(0.0, 1.0)], EPSILON),
+        PathSegment::new(vec![point2(1.0, 1.0), point2(1.0, 9.0)], EPSILON),
+    ]
+}
+
+#[test]
+fn the_end_point_changes_the_last_direction() {
+    let greedy = order_travel(ladder(), point2(0.0, 0.0));
+    assert_eq!(greedy[1].path[0], point2(1.0, 1.0));
+
+    let out = order_travel_between(ladder(), point2(0.0, 0.0), Some(point2(1.0, 0.0)));
+    assert_eq!(out[0], ladder()[0]);
+    assert_eq!(out[1].path[0], point2(1.0, 9.0));
+}
+
+#[test]
+fn improving_the_order_never_makes_it_longer() {
+    // A scatter of short strokes from a simple linear congruential generator.
+    let mut seed = 12345u32;
+    let mut next = || {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) as f32 / 65536.0 * 100.0
+    };
+    let segments: Vec<PathSegment> = (0..200)
+        .map(|_| {
+            let (x, y) = (next(), next());
+            PathSegment::new(vec![point2(x, y), point2(x + 1.0, y + 0.5)], EPSILON)
+        })
+        .collect();
+    let (start, end) = (point2(0.0, 0.0), point2(100.0, 0.0));
+    let cost = |out: &[PathSegment]| travel_distance(out, start) + (out[out.len() - 1].last() - end).length();
+
+    let greedy = order_travel(segments.clone(), start);
+    let improved = order_travel_between(segments.clone(), start, Some(end));
+    assert_eq!(improved.len(), segments.len());
+    assert!(cost(&improved) <= cost(&greedy));
+    assert!(improved.iter().all(|s| segments.iter().any(|t| t.path == s.path || {
+        let mut r = t.path.clone();
+        r.reverse();
+        r == s.path
+    })));
+}
+
+#[test]
+fn plans_finish_at_the_end_point() {
+    let plan = plan_travel_between(ladder(), point2(0.0, 0.0), Some(point2(1.0, 0.0)));
+    match plan.moves.last() {
+        Some(&Move::PenUp(ref points)) => assert_eq!(points[points.len() - 1], point2(1.0, 0.0)),
+        other => panic!("expected a pen-up move, got {:?}", other),
+    }
+}
@@ -0,0 +1,79 @@
+use ::{connect_obvious, graph_stitch, run_pipeline, validate, PipelineConfig, Violation};
+use euclid::{UnknownUnit, point2};
+
+type PathSegment = ::PathSegment<UnknownUnit>;
+
+const EPSILON: f32 = 0.001;
+
+fn square() -> Vec<PathSegment> {
+    vec![
+        PathSegment::new(vec![point2(0.0, 0.0), point2(1.0, 0.0)], EPSILON),
+        PathSegment::new(vec![point2(1.0, 0.0), point2(1.0, 1.0)], EPSILON),
+        PathSegment::new(vec![point2(1.0, 1.0), point2(0.0, 1.0)], EPSILON),
+        PathSegment::new(vec![point2(0.0, 1.0), point2(0.0, 0.0)], EPSILON),
+    ]
+}
+
+#[test]
+fn connect_obvious_output_is_valid() {
+    let input = square();
+    let output = connect_obvious(input.clone(), EPSILON, false, false);
+    assert_eq!(validate(&input, &output, EPSILON), vec![]);
+}
+
+#[test]
+fn dropped_segments_are_reported() {
+    let mut input = square();
+    input.push(PathSegment::new(vec![point2(5.0, 5.0), point2(6.0, 5.0)], EPSILON));
+    let output = graph_stitch(input.clone());
+
+    let problems = validate(&input, &output, EPSILON);
+    assert_eq!(
+        problems,
+        vec![
+            Violation::Uncovered { segment: 4, point: point2(5.0, 5.0) },
+            Violation::Uncovered { segment: 4, point: point2(6.0, 5.0) },
+        ]
+    );
+}
+
+#[test]
+fn unjoined_ends_are_reported() {
+    let input = square();
+    let problems = validate(&input, &input, EPSILON);
+    assert_eq!(problems.len(), 4);
+    assert!(problems.iter().all(|v| match *v {
+        Violation::Unjoined { .. } => true,
+        _ => false,
+    }));
+}
+
+#[test]
+fn open_loops_and_nan_are_reported() {
+    let mut open = PathSegment::new(
+        vec![point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0)],
+        EPSILON,
+    );
+    open.path.push(point2(0.0, 0.0));
+    let nan = PathSegment::new(vec![point2(2.0, 2.0), point2(::std::f32::NAN, 2.0)], EPSILON);
+
+    let problems = validate(&[], &[open, nan], EPSILON);
+    assert_eq!(
+        problems,
+        vec![
+            Violation::InconsistentClosed { segment: 0 },
+            Violation::NonFinite { segment: 1, index: 1 },
+        ]
+    );
+}
+
+#[test]
+fn pipeline_validates_its_output() {
+    let config = PipelineConfig {
+        debug_validate: true,
+        allow_ambiguous: true,
+        ..PipelineConfig::default()
+    };
+    let out = run_pipeline(square().into_iter().map(|s| s.path), &config);
+    assert_eq!(out.len(), 1);
+}
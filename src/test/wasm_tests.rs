@@ -0,0 +1,32 @@
+use ::stitch;
+use js_sys::{Array, Float32Array};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+fn polyline(coords: &[f32]) -> Float32Array {
+    Float32Array::from(coords)
+}
+
+#[wasm_bindgen_test]
+fn joins_float32_arrays() {
+    let input = Array::new();
+    input.push(&polyline(&[0.0, 0.0, 1.0, 0.0]));
+    input.push(&polyline(&[1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0]));
+
+    let out = stitch(&input, 0.001, false, false);
+    assert_eq!(out.length(), 1);
+    let path = out.get(0).dyn_into::<Float32Array>().unwrap().to_vec();
+    // Closed paths repeat their first point.
+    assert_eq!(path.len(), 10);
+    assert_eq!(&path[..2], &path[8..]);
+}
+
+#[wasm_bindgen_test]
+fn ignores_entries_that_are_not_arrays() {
+    let input = Array::new();
+    input.push(&polyline(&[0.0, 0.0, 1.0, 0.0]));
+    input.push(&"not a polyline".into());
+
+    let out = stitch(&input, 0.001, false, false);
+    assert_eq!(out.length(), 1);
+}
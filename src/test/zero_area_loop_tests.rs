@@ -1,4 +1,4 @@
-use ::remove_zero_area_loops;
+use ::{remove_zero_area_loops, remove_zero_area_loops_with_removed, remove_zero_area_spurs};
 use super::util::*;
 use permutohedron::heap_recursive as permute;
 use euclid::{UnknownUnit};
@@ -93,3 +93,75 @@ fn inverted_lines_are_removed() {
         ..default_problem()
     });
 }
+
+#[test]
+fn removed_lines_are_returned() {
+    let input = vec![
+        (Point::new(0.0, 0.0), Point::new(1.0, 0.0)),
+        (Point::new(1.0, 0.0), Point::new(2.0, 0.0)),
+        (Point::new(1.0, 0.0), Point::new(0.0, 0.0)),
+        (Point::new(5.0, 5.0), Point::new(5.0, 5.0)),
+    ];
+    let (kept, removed) = remove_zero_area_loops_with_removed(input, EPSILON);
+    assert_eq!(kept, vec![(Point::new(1.0, 0.0), Point::new(2.0, 0.0))]);
+    assert_eq!(
+        removed,
+        vec![
+            (Point::new(0.0, 0.0), Point::new(1.0, 0.0)),
+            (Point::new(1.0, 0.0), Point::new(0.0, 0.0)),
+            (Point::new(5.0, 5.0), Point::new(5.0, 5.0)),
+        ]
+    );
+}
+
+fn open(points: &[(f32, f32)]) -> PathSegment {
+    PathSegment::new(points.iter().map(|&(x, y)| Point::new(x, y)).collect::<Vec<_>>(), EPSILON)
+}
+
+fn points(segment: &PathSegment) -> Vec<(f32, f32)> {
+    segment.path.iter().map(|p| (p.x, p.y)).collect()
+}
+
+#[test]
+fn spur_in_the_middle_of_a_path_is_cut_out() {
+    let out = remove_zero_area_spurs(vec![open(&[(0.0, 0.0), (1.0, 0.0), (1.0, 3.0), (1.0, 0.0), (2.0, 0.0)])], EPSILON);
+    assert_eq!(out.len(), 1);
+    assert_eq!(points(&out[0]), vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+    assert!(!out[0].closed);
+    assert_eq!(out[0].length(), 2.0);
+}
+
+#[test]
+fn spur_over_several_corners_is_cut_out() {
+    let path = &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (2.0, 2.0), (1.0, 1.0), (1.0, 0.0005), (2.0, 0.0)];
+    let out = remove_zero_area_spurs(vec![open(path)], EPSILON);
+    assert_eq!(points(&out[0]), vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+}
+
+#[test]
+fn paths_without_spurs_are_untouched() {
+    let zigzag = open(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 1.0)]);
+    let square = open(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)]);
+    let out = remove_zero_area_spurs(vec![zigzag.clone(), square.clone()], EPSILON);
+    assert_eq!(out, vec![zigzag, square]);
+}
+
+#[test]
+fn spur_across_the_start_of_a_loop_is_cut_out() {
+    // The loop starts at the tip of a spur sticking out of (0, 0).
+    let square = open(&[(-1.0, -1.0), (0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (-1.0, -1.0)]);
+    assert!(square.closed);
+    let out = remove_zero_area_spurs(vec![square], EPSILON);
+    assert_eq!(out.len(), 1);
+    assert!(out[0].closed);
+    assert_eq!(points(&out[0]), vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+}
+
+#[test]
+fn paths_that_are_nothing_but_a_spur_are_dropped() {
+    let there_and_back = open(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (1.0, 0.0), (0.0, 0.0)]);
+    let open_spur = open(&[(0.0, 5.0), (3.0, 5.0), (0.0, 5.0), (3.0, 5.0), (0.0, 5.0), (0.0, 5.0)]);
+    let kept = open(&[(0.0, 9.0), (3.0, 9.0)]);
+    let out = remove_zero_area_spurs(vec![there_and_back, open_spur, kept.clone()], EPSILON);
+    assert_eq!(out, vec![kept]);
+}
@@ -0,0 +1,187 @@
+use ::*;
+use std::fmt::Write;
+
+/// Writes the segments in the text format read by `read_segments`, with no
+/// layer lines.
+pub fn write_segments<S>(segments: &[PathSegment<S>]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        write_segment(&mut out, segment);
+    }
+    out
+}
+
+/// One layer of a drawing in the text format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayer<S> {
+    /// The name after the `layer` keyword, whitespace included.
+    pub name: String,
+    /// The `meta` lines of the layer as key, value pairs, in the order they
+    /// were written.
+    pub metadata: Vec<(String, String)>,
+    /// The paths drawn on the layer.
+    pub segments: Vec<PathSegment<S>>,
+}
+
+/// Writes each layer's segments in the text format, under a `layer` line
+/// followed by its `meta` lines.
+///
+/// Fails with `Error::Malformed` if a layer name or metadata value has a
+/// line break in it, or a metadata key is empty or has whitespace in it,
+/// since none of those would read back the same.
+pub fn write_layers<S>(layers: &[TextLayer<S>]) -> Result<String, Error> {
+    let breaks_line = |s: &str| s.contains(|c| c == '\n' || c == '\r');
+    let mut out = String::new();
+    for layer in layers {
+        if breaks_line(&layer.name) {
+            return Err(Error::Malformed);
+        }
+        writeln!(out, "layer {}", layer.name).unwrap();
+        for &(ref key, ref value) in &layer.metadata {
+            if key.is_empty() || key.contains(char::is_whitespace) || breaks_line(value) {
+                return Err(Error::Malformed);
+            }
+            writeln!(out, "meta {} {}", key, value).unwrap();
+        }
+        for segment in &layer.segments {
+            write_segment(&mut out, segment);
+        }
+    }
+    Ok(out)
+}
+
+fn write_segment<S>(out: &mut String, segment: &PathSegment<S>) {
+    out.push_str(if segment.closed { "closed" } else { "open" });
+    for p in &segment.path {
+        write!(out, " {},{}", p.x, p.y).unwrap();
+    }
+    out.push('\n');
+}
+
+/// Reads every path in the text format, ignoring layers and metadata.
+///
+/// The format has one path per line:
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// layer walls
+/// meta pen 0.3mm
+/// open 0,0 10,0 10,5
+/// closed 0,0 1,0 1,1
+/// layer roads
+/// open -2.5,3 4,3e2
+/// ```
+///
+/// Every path is `open` or `closed`, followed by its points as whitespace
+/// separated `x,y` pairs.  A closed path doesn't repeat its first point at
+/// the end.  `layer NAME` puts the paths after it on the layer `NAME`, which
+/// is everything after the single space following `layer`, leading and
+/// trailing whitespace included; paths before the first `layer` line are on
+/// the layer `0`.  `meta KEY VALUE` adds an optional key, value pair to the
+/// current layer, with the value running to the end of the line in the same
+/// way.  `write_segments` writes coordinates with as many digits as it takes
+/// to read back the exact same `f32`.
+///
+/// The segments are exactly as they were written: no epsilon is applied,
+/// so open paths whose ends touch stay open.
+pub fn read_segments<S>(text: &str) -> Result<Vec<PathSegment<S>>, Error> {
+    Ok(read_layers(text)?.into_iter().flat_map(|layer| layer.segments).collect())
+}
+
+/// Reads the paths in the text format, grouped by layer in the order that
+/// the layers first show up.
+///
+/// The default layer `0` is left out if nothing was drawn on it and it was
+/// never named by a `layer 0` line.
+pub fn read_layers<S>(text: &str) -> Result<Vec<TextLayer<S>>, Error> {
+    let mut layers: Vec<TextLayer<S>> = vec![TextLayer {
+        name: "0".into(),
+        metadata: vec![],
+        segments: vec![],
+    }];
+    let mut default_named = false;
+    let mut current = 0;
+
+    for line in text.lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, rest) = split_word(line);
+        match keyword {
+            "layer" => {
+                current = match layers.iter().position(|l| l.name == rest) {
+                    Some(i) => i,
+                    None => {
+                        layers.push(TextLayer {
+                            name: rest.into(),
+                            metadata: vec![],
+                            segments: vec![],
+                        });
+                        layers.len() - 1
+                    }
+                };
+                default_named |= current == 0;
+            }
+            "meta" => {
+                let (key, value) = split_word(rest);
+                if key.is_empty() {
+                    return Err(Error::Malformed);
+                }
+                layers[current].metadata.push((key.into(), value.into()));
+            }
+            "open" | "closed" => {
+                let segment = read_path(rest, keyword == "closed")?;
+                layers[current].segments.push(segment);
+            }
+            _ => return Err(Error::Malformed),
+        }
+    }
+
+    // The default layer only shows up if something was drawn on it, or it
+    // was asked for by name.
+    if !default_named && layers[0].segments.is_empty() && layers[0].metadata.is_empty() {
+        layers.remove(0);
+    }
+    Ok(layers)
+}
+
+/// Splits off the first word.  Only the one whitespace character after it is
+/// taken as the separator, so that names and values keep their own.
+fn split_word(line: &str) -> (&str, &str) {
+    match line.find(char::is_whitespace) {
+        Some(i) => {
+            let separator = line[i..].chars().next().unwrap().len_utf8();
+            (&line[..i], &line[i + separator..])
+        }
+        None => (line, ""),
+    }
+}
+
+fn read_path<S>(rest: &str, closed: bool) -> Result<PathSegment<S>, Error> {
+    let mut path: Points<S> = SmallVec::new();
+    for pair in rest.split_whitespace() {
+        let mut coords = pair.split(',').map(|c| c.parse::<f32>());
+        match (coords.next(), coords.next(), coords.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => path.push(Point::new(x, y)),
+            _ => return Err(Error::Malformed),
+        }
+    }
+    if path.len() < 2 {
+        return Err(Error::TooFewPoints);
+    }
+    if !path.iter().all(|&p| ::sanitize::is_finite(p)) {
+        return Err(Error::NonFinite);
+    }
+
+    let mut segment = PathSegment {
+        path: path,
+        closed: closed,
+        layer: LayerId::default(),
+        length_2: 0.0,
+        length: 0.0,
+    };
+    segment.update_lengths();
+    Ok(segment)
+}
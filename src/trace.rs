@@ -0,0 +1,34 @@
+//! Logging through the `log` crate when the `log` feature is on.
+//!
+//! Passes log a `debug` record when they start and finish, and a `trace`
+//! record for every decision they make.  All records use the
+//! `line_stitch` target.  With the feature off the macros expand to nothing
+//! that runs, but still type check their arguments.
+
+#[cfg(feature = "log")]
+macro_rules! debug_event {
+    ($($arg:tt)*) => { debug!(target: "line_stitch", $($arg)*) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => { trace!(target: "line_stitch", $($arg)*) };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug_event {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
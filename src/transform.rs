@@ -0,0 +1,44 @@
+use ::*;
+use euclid::TypedTransform2D;
+use sanitize::sanitize_segments;
+
+/// Maps every point through `transform`.
+///
+/// Lengths are recomputed, and since a transform that shrinks things can
+/// bring the ends of an open segment together, whether each segment is
+/// closed is decided again using `epsilon`.  Closed segments stay closed.  A
+/// relative `epsilon` is resolved against the bounds of the transformed
+/// points.
+pub fn transform<S, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<S>>,
+    transform: &TypedTransform2D<f32, S, S>,
+    epsilon: E,
+) -> Vec<PathSegment<S>> {
+    transform_units(segments, transform, epsilon)
+}
+
+/// Like `transform`, but the transform may also change the unit of the
+/// points.
+pub fn transform_units<Src, Dst, E: Into<Epsilon>>(
+    segments: Vec<PathSegment<Src>>,
+    transform: &TypedTransform2D<f32, Src, Dst>,
+    epsilon: E,
+) -> Vec<PathSegment<Dst>> {
+    let segments = sanitize_segments(segments);
+    let paths: Vec<(Points<Dst>, LayerId)> = segments
+        .into_iter()
+        .map(|segment| {
+            let layer = segment.layer;
+            let points: Points<Src> = segment.into();
+            (points.iter().map(|p| transform.transform_point(p)).collect(), layer)
+        })
+        .collect();
+
+    let epsilon = epsilon
+        .into()
+        .resolve_with(|| util::compute_bounding_box(paths.iter().flat_map(|p| p.0.iter().cloned())));
+    paths
+        .into_iter()
+        .map(|(p, layer)| PathSegment::new(p, epsilon).with_layer(layer))
+        .collect()
+}
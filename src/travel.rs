@@ -0,0 +1,217 @@
+use ::*;
+use util::{centered_with_radius, compute_bounding_box};
+use sanitize::{is_finite, sanitize_segments};
+use std::cmp::Ordering;
+
+/// Reorders the segments so that the pen travels as little as possible
+/// between them, starting at `start`.  Open segments may be reversed.
+///
+/// A `start` with a NaN or infinite coordinate is taken to be the start of
+/// the first segment.
+pub fn order_travel<S: 'static>(segments: Vec<PathSegment<S>>, start: Point<S>) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let order = travel_order(&segments, start);
+    apply_order(segments, &order)
+}
+
+/// Like `order_travel`, but also counts the move to `end` after the last
+/// segment, when there is one, and spends some extra time improving the
+/// order.
+///
+/// Plotters start at their home position and are often best left near the
+/// side that the paper comes out of; this picks the order with both of
+/// those legs in mind.
+pub fn order_travel_between<S: 'static>(
+    segments: Vec<PathSegment<S>>,
+    start: Point<S>,
+    end: Option<Point<S>>,
+) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let order = travel_order_between(&segments, start, end);
+    apply_order(segments, &order)
+}
+
+/// Returns the distance travelled with the pen up when drawing the
+/// segments in the given order, starting at `start`.
+pub fn travel_distance<S>(segments: &[PathSegment<S>], start: Point<S>) -> f32 {
+    let mut position = start;
+    let mut total = 0.0;
+    for segment in segments {
+        total += (segment.first() - position).length();
+        position = exit_point(segment);
+    }
+    total
+}
+
+/// The point where the pen is lifted after drawing `segment`.
+pub(crate) fn exit_point<S>(segment: &PathSegment<S>) -> Point<S> {
+    if segment.closed {
+        segment.first()
+    } else {
+        segment.last()
+    }
+}
+
+/// Greedily picks the closest unvisited endpoint, returning the index of
+/// each segment along with whether it should be drawn in reverse.
+pub(crate) fn travel_order<S: 'static>(segments: &[PathSegment<S>], start: Point<S>) -> Vec<(usize, bool)> {
+    if segments.is_empty() {
+        return vec![];
+    }
+    let start = finite_start(segments, start);
+
+    let bounds = compute_bounding_box(
+        segments
+            .iter()
+            .flat_map(|s| vec![s.first(), s.last()])
+            .chain(Some(start)),
+    );
+    let bounds = bounds.inflate(
+        1.0f32.max(bounds.size.width / 10.0),
+        1.0f32.max(bounds.size.height / 10.0),
+    );
+
+    let mut tree = QuadTree::default(bounds);
+    let mut ids = Vec::with_capacity(segments.len());
+    for (index, segment) in segments.iter().enumerate() {
+        let start_id = tree.insert_with_box((index, false), segment.first().aabb()).unwrap();
+        let end_id = if segment.closed {
+            None
+        } else {
+            tree.insert_with_box((index, true), segment.last().aabb())
+        };
+        ids.push((start_id, end_id));
+    }
+
+    let initial_radius =
+        bounds.size.width.max(bounds.size.height) / (segments.len() as f32).sqrt();
+
+    let mut position = start;
+    let mut order = Vec::with_capacity(segments.len());
+    while let Some((index, reversed)) = nearest_endpoint(&tree, position, initial_radius) {
+        let (start_id, end_id) = ids[index];
+        tree.remove(start_id);
+        if let Some(end_id) = end_id {
+            tree.remove(end_id);
+        }
+
+        let segment = &segments[index];
+        position = if reversed {
+            segment.first()
+        } else {
+            exit_point(segment)
+        };
+        order.push((index, reversed));
+    }
+
+    order
+}
+
+/// How far ahead `travel_order_between` looks for a run of segments to
+/// flip.  Keeps each round linear in the number of segments.
+const FLIP_WINDOW: usize = 64;
+
+/// How many rounds of flips `travel_order_between` makes at most.
+const FLIP_ROUNDS: usize = 8;
+
+/// The greedy order, improved by flipping runs of segments (2-opt) for as
+/// long as that shortens the travel from `start`, through the segments and
+/// on to `end`.
+pub(crate) fn travel_order_between<S: 'static>(
+    segments: &[PathSegment<S>],
+    start: Point<S>,
+    end: Option<Point<S>>,
+) -> Vec<(usize, bool)> {
+    let start = finite_start(segments, start);
+    let end = end.filter(|&end| is_finite(end));
+    let mut order = travel_order(segments, start);
+    let entry = |&(index, reversed): &(usize, bool)| {
+        let segment = &segments[index];
+        if reversed { segment.last() } else { segment.first() }
+    };
+    let exit = |&(index, reversed): &(usize, bool)| {
+        let segment = &segments[index];
+        if reversed { segment.first() } else { exit_point(segment) }
+    };
+
+    let n = order.len();
+    for _ in 0..FLIP_ROUNDS {
+        let mut improved = false;
+        for i in 0..n {
+            let before = if i == 0 { start } else { exit(&order[i - 1]) };
+            for j in i..n.min(i + FLIP_WINDOW) {
+                let after = if j + 1 < n { Some(entry(&order[j + 1])) } else { end };
+                let leg = |a: Point<S>, b: Option<Point<S>>| b.map_or(0.0, |b| (b - a).length());
+
+                // Flipping the run only changes the moves into and out of it.
+                let old = (entry(&order[i]) - before).length() + leg(exit(&order[j]), after);
+                let new = (exit(&order[j]) - before).length() + leg(entry(&order[i]), after);
+                if new < old - 1.0e-6 * old.max(1.0) {
+                    order[i..j + 1].reverse();
+                    for step in &mut order[i..j + 1] {
+                        // Closed segments start and end at the same point.
+                        if !segments[step.0].closed {
+                            step.1 = !step.1;
+                        }
+                    }
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    order
+}
+
+/// `start`, or the start of the first segment if `start` isn't finite.
+pub(crate) fn finite_start<S>(segments: &[PathSegment<S>], start: Point<S>) -> Point<S> {
+    match segments.first() {
+        Some(first) if !is_finite(start) => first.first(),
+        _ => start,
+    }
+}
+
+pub(crate) fn apply_order<S>(segments: Vec<PathSegment<S>>, order: &[(usize, bool)]) -> Vec<PathSegment<S>> {
+    let mut slots: Vec<Option<PathSegment<S>>> = segments.into_iter().map(Some).collect();
+    order
+        .iter()
+        .map(|&(index, reversed)| {
+            let mut segment = slots[index].take().unwrap();
+            if reversed {
+                segment.path.reverse();
+            }
+            segment
+        })
+        .collect()
+}
+
+/// The item of `tree` nearest to `point`, searching outwards from
+/// `initial_radius`.  There is none for a point that isn't finite.
+pub(crate) fn nearest_endpoint<T: Copy, S>(
+    tree: &QuadTree<T, S>,
+    point: Point<S>,
+    initial_radius: f32,
+) -> Option<T> {
+    if tree.is_empty() || !is_finite(point) {
+        return None;
+    }
+
+    // Anything outside of the query box is further away than `radius`, so
+    // the closest hit inside of the circle is the closest overall.
+    let mut radius = initial_radius;
+    loop {
+        let found = tree.query(centered_with_radius(point, radius))
+            .into_iter()
+            .map(|(&item, rect, _)| ((rect.origin - point).square_length(), item))
+            .filter(|&(d2, _)| d2 <= radius * radius)
+            .min_by(|&(a, _), &(b, _)| a.partial_cmp(&b).unwrap_or(Ordering::Equal));
+
+        if let Some((_, item)) = found {
+            return Some(item);
+        }
+        radius *= 2.0;
+    }
+}
@@ -1,17 +1,64 @@
 use ::*;
 use euclid::*;
 
-pub fn populate<I, P, S: 'static>(segments: I, epsilon: f32) -> DualQuadTree<S>
+/// Builds a `DualQuadTree` out of the segments, returning it along with the
+/// resolved epsilon.
+pub fn populate<I, P, S: 'static>(segments: I, epsilon: Epsilon) -> (DualQuadTree<S>, f32)
 where
     I: IntoIterator<Item = P>,
-    P: Into<smallvec::SmallVec<[Point<S>; 2]>>,
+    P: Into<Points<S>>,
+{
+    populate_with_index(segments, epsilon, IndexBackend::default())
+}
+
+/// Like `populate`, but keeps the endpoints in `index`.
+pub fn populate_with_index<I, P, S: 'static>(segments: I, epsilon: Epsilon, index: IndexBackend) -> (DualQuadTree<S>, f32)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let (all_segments, scene_aabb, epsilon) = prepare(segments, epsilon);
+    let mut dual_qt = DualQuadTree::new(scene_aabb, epsilon, index);
+    for segment in all_segments {
+        dual_qt.insert(segment);
+    }
+
+    (dual_qt, epsilon)
+}
+
+/// Cleans up the segments and drops the ones too short to matter, returning
+/// them along with the bounds a `DualQuadTree` over them should cover and
+/// the resolved epsilon.
+pub(crate) fn prepare<I, P, S>(segments: I, epsilon: Epsilon) -> (Vec<PathSegment<S>>, TypedRect<f32, S>, f32)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
+{
+    let (all_segments, _, scene_aabb, epsilon) = prepare_keeping_short(segments, epsilon);
+    (all_segments, scene_aabb, epsilon)
+}
+
+/// Like `prepare`, but also returns the segments that were too short to
+/// matter, as `(kept, too_short, bounds, epsilon)`.
+pub(crate) fn prepare_keeping_short<I, P, S>(
+    segments: I,
+    epsilon: Epsilon,
+) -> (Vec<PathSegment<S>>, Vec<PathSegment<S>>, TypedRect<f32, S>, f32)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Points<S>>,
 {
     let mut all_segments = vec![];
+    let mut too_short = vec![];
     let mut starts_and_ends = vec![];
 
-    for segment in segments.into_iter().map(Into::into).filter(|a| a.len() > 1) {
+    let (segments, _) = sanitize(segments);
+    let epsilon = epsilon.resolve_with(|| compute_bounding_box(segments.iter().flat_map(|s| s.iter().cloned())));
+
+    for segment in segments {
         let segment = PathSegment::new(segment, epsilon);
         if segment.length_2() < epsilon {
+            too_short.push(segment);
             continue;
         }
 
@@ -28,12 +75,7 @@ where
         epsilon.max(rect.size.height / 10.0),
     );
 
-    let mut dual_qt = DualQuadTree::new(scene_aabb);
-    for segment in all_segments {
-        dual_qt.insert(segment);
-    }
-
-    dual_qt
+    (all_segments, too_short, scene_aabb, epsilon)
 }
 
 pub(crate) fn centered_with_radius<S>(pt: Point<S>, radius: f32) -> euclid::TypedRect<f32, S> {
@@ -41,6 +83,24 @@ pub(crate) fn centered_with_radius<S>(pt: Point<S>, radius: f32) -> euclid::Type
     euclid::TypedRect::new(pt - half, (half * 2.0).to_size())
 }
 
+/// A segment for a sub-path read from a format that marks closed sub-paths
+/// for itself.  A closed sub-path that already ends where it started drops
+/// the repeated point; an open one stays open wherever it ends.
+pub(crate) fn subpath<S>(mut path: Vec<Point<S>>, closed: bool, epsilon: f32) -> PathSegment<S> {
+    if closed && path.len() > 2 && centered_with_radius(path[0], epsilon).contains(&path[path.len() - 1]) {
+        path.pop();
+    }
+    let mut segment = PathSegment {
+        path: path.into(),
+        closed: closed,
+        layer: LayerId::default(),
+        length_2: 0.0,
+        length: 0.0,
+    };
+    segment.update_lengths();
+    segment
+}
+
 // TODO: rename
 pub fn compute_bounding_box<S, I: IntoIterator<Item = Point<S>>>(i: I) -> TypedRect<f32, S> {
     use euclid::{point2, vec2};
@@ -51,7 +111,7 @@ pub fn compute_bounding_box<S, I: IntoIterator<Item = Point<S>>>(i: I) -> TypedR
     let mut max_x = f32::NEG_INFINITY;
     let mut max_y = f32::NEG_INFINITY;
 
-    for pt in i {
+    for pt in i.into_iter().filter(|&p| ::sanitize::is_finite(p)) {
         min_x = min_x.min(pt.x);
         min_y = min_y.min(pt.y);
 
@@ -64,3 +124,81 @@ pub fn compute_bounding_box<S, I: IntoIterator<Item = Point<S>>>(i: I) -> TypedR
         vec2(max_x - min_x, max_y - min_y).to_size(),
     )
 }
+
+/// Even-odd point in polygon test.
+pub(crate) fn contains_point<S>(polygon: &[Point<S>], point: Point<S>) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Twice the signed area of a ring; positive for counter-clockwise rings.
+pub(crate) fn area<S>(ring: &[Point<S>]) -> f32 {
+    let mut total = 0.0;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        total += a.x * b.y - b.x * a.y;
+    }
+    total
+}
+
+/// Where `a0 -> a1` crosses `b0 -> b1`, as fractions along both edges.
+pub(crate) fn crossing<S>(a0: Point<S>, a1: Point<S>, b0: Point<S>, b1: Point<S>) -> Option<(f32, f32)> {
+    let r = a1 - a0;
+    let s = b1 - b0;
+    let denom = r.cross(s);
+    if denom == 0.0 {
+        return None;
+    }
+
+    let t = (b0 - a0).cross(s) / denom;
+    let u = (b0 - a0).cross(r) / denom;
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        Some((t, u))
+    } else {
+        None
+    }
+}
+
+/// Disjoint sets over indices, with path halving and union by size.
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    pub(crate) fn find(&mut self, mut id: usize) -> usize {
+        while self.parent[id] != id {
+            let grandparent = self.parent[self.parent[id]];
+            self.parent[id] = grandparent;
+            id = grandparent;
+        }
+        id
+    }
+
+    pub(crate) fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.size[a] >= self.size[b] { (a, b) } else { (b, a) };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+    }
+}
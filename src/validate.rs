@@ -0,0 +1,184 @@
+use ::*;
+use sanitize::is_finite;
+use util::{centered_with_radius, compute_bounding_box};
+
+type Rect<S> = euclid::TypedRect<f32, S>;
+
+/// A broken invariant found by `validate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Violation<S> {
+    /// A point of the input segment at index `segment` isn't within epsilon
+    /// of any output segment.
+    Uncovered {
+        /// The index of the input segment.
+        segment: usize,
+        /// The point that went missing.
+        point: Point<S>,
+    },
+    /// Open ends of the output segments `a` and `b` are within epsilon of
+    /// each other, so they could have been joined.
+    Unjoined {
+        /// The index of one output segment.
+        a: usize,
+        /// The index of the other output segment.
+        b: usize,
+        /// Where the ends of `a` and `b` meet.
+        point: Point<S>,
+    },
+    /// The output segment at index `segment` is open although its ends
+    /// touch, or is closed with fewer than two points.
+    InconsistentClosed {
+        /// The index of the output segment.
+        segment: usize,
+    },
+    /// The point at `index` of the output segment at index `segment` has a
+    /// coordinate that is NaN or infinite.
+    NonFinite {
+        /// The index of the output segment.
+        segment: usize,
+        /// The index of the point in the segment.
+        index: usize,
+    },
+}
+
+/// Checks that `output` is a plausible result of running passes over
+/// `input`, returning every problem found.
+///
+/// * every input point lies within epsilon of some output segment,
+/// * no two open output ends are within epsilon of each other,
+/// * open output segments don't end where they start,
+/// * every output point is finite.
+///
+/// Passes that drop segments on purpose, like `prune` and `graph_stitch`,
+/// will be reported as leaving input points uncovered.  A relative
+/// `epsilon` is resolved against the bounds of `input`.
+pub fn validate<S: 'static, E: Into<Epsilon>>(
+    input: &[PathSegment<S>],
+    output: &[PathSegment<S>],
+    epsilon: E,
+) -> Vec<Violation<S>> {
+    let epsilon = epsilon.into().resolve_segments(input);
+    let input = input.iter().map(|s| &s.path[..]);
+    violations(input, output, epsilon)
+}
+
+pub(crate) fn violations<'a, S: 'static, I>(
+    input: I,
+    output: &[PathSegment<S>],
+    epsilon: f32,
+) -> Vec<Violation<S>>
+where
+    I: IntoIterator<Item = &'a [Point<S>]>,
+{
+    let mut out = vec![];
+
+    for (i, segment) in output.iter().enumerate() {
+        for (j, p) in segment.path.iter().enumerate() {
+            if !is_finite(*p) {
+                out.push(Violation::NonFinite { segment: i, index: j });
+            }
+        }
+
+        let inconsistent = if segment.closed {
+            segment.path.len() < 2
+        } else {
+            (segment.first() - segment.last()).length() <= epsilon
+        };
+        if inconsistent {
+            out.push(Violation::InconsistentClosed { segment: i });
+        }
+    }
+
+    let input: Vec<&[Point<S>]> = input.into_iter().collect();
+    let finite_points = input
+        .iter()
+        .flat_map(|s| s.iter().cloned())
+        .chain(output.iter().flat_map(|s| s.path.iter().cloned()))
+        .filter(|&p| is_finite(p));
+    let bounds = compute_bounding_box(finite_points);
+    if bounds.size.width.is_finite() {
+        let bounds = bounds.inflate(
+            1.0f32.max(bounds.size.width / 10.0) + epsilon,
+            1.0f32.max(bounds.size.height / 10.0) + epsilon,
+        );
+        uncovered(&input, output, epsilon, bounds, &mut out);
+        unjoined(output, epsilon, bounds, &mut out);
+    }
+
+    out
+}
+
+fn uncovered<S: 'static>(
+    input: &[&[Point<S>]],
+    output: &[PathSegment<S>],
+    epsilon: f32,
+    bounds: Rect<S>,
+    out: &mut Vec<Violation<S>>,
+) {
+    let mut edges = QuadTree::default(bounds);
+    for segment in output {
+        let path = &segment.path;
+        let edge_count = if segment.closed { path.len() } else { path.len() - 1 };
+        for j in 0..edge_count {
+            let (a, b) = (path[j], path[(j + 1) % path.len()]);
+            if is_finite(a) && is_finite(b) {
+                edges.insert_with_box((a, b), Rect::from_points(&[a, b]));
+            }
+        }
+    }
+
+    for (i, points) in input.iter().enumerate() {
+        // Points that aren't finite can't be drawn, so they aren't expected
+        // to be covered.
+        for &point in points.iter().filter(|&&p| is_finite(p)) {
+            let covered = edges
+                .query(centered_with_radius(point, epsilon))
+                .into_iter()
+                .any(|(&(a, b), _, _)| distance_to_edge(point, a, b) <= epsilon);
+            if !covered {
+                out.push(Violation::Uncovered {
+                    segment: i,
+                    point: point,
+                });
+            }
+        }
+    }
+}
+
+fn unjoined<S: 'static>(
+    output: &[PathSegment<S>],
+    epsilon: f32,
+    bounds: Rect<S>,
+    out: &mut Vec<Violation<S>>,
+) {
+    let mut ends = QuadTree::default(bounds);
+    for (i, segment) in output.iter().enumerate() {
+        if segment.closed {
+            continue;
+        }
+        for &p in &[segment.first(), segment.last()] {
+            if is_finite(p) {
+                ends.insert_with_box((i, p), p.aabb());
+            }
+        }
+    }
+
+    for (_, &((a, p), _)) in ends.iter() {
+        for (&(b, q), _, _) in ends.query(centered_with_radius(p, epsilon)) {
+            if a < b && (q - p).length() <= epsilon {
+                out.push(Violation::Unjoined { a: a, b: b, point: p });
+            }
+        }
+    }
+}
+
+fn distance_to_edge<S>(p: Point<S>, a: Point<S>, b: Point<S>) -> f32 {
+    let ab = b - a;
+    let length_2 = ab.square_length();
+    let t = if length_2 == 0.0 {
+        0.0
+    } else {
+        ((p - a).dot(ab) / length_2).max(0.0).min(1.0)
+    };
+    (p - (a + ab * t)).length()
+}
@@ -0,0 +1,47 @@
+//! A JS-facing API built with `wasm-bindgen`.
+//!
+//! Polylines cross the boundary as `Float32Array`s of interleaved `x, y`
+//! pairs.
+
+use ::*;
+use euclid::UnknownUnit;
+use js_sys::{Array, Float32Array};
+use wasm_bindgen::prelude::*;
+// The generated glue refers to the crate by a relative path.
+#[allow(unused_imports)]
+use wasm_bindgen;
+
+fn to_points(array: &Float32Array) -> Points<UnknownUnit> {
+    let coords = array.to_vec();
+    coords.chunks(2).filter(|c| c.len() == 2).map(|c| Point::new(c[0], c[1])).collect()
+}
+
+fn to_array(segment: PathSegment<UnknownUnit>) -> Float32Array {
+    let points: Points<UnknownUnit> = segment.into();
+    let coords: Vec<f32> = points.iter().flat_map(|p| vec![p.x, p.y]).collect();
+    Float32Array::from(&coords[..])
+}
+
+/// Runs `connect_obvious` (and optionally `prune` and `graph_stitch`) over
+/// an array of `Float32Array` polylines.
+///
+/// Returns an array of `Float32Array`s.  Closed paths repeat their first
+/// point at the end.  Entries that aren't `Float32Array`s are ignored.
+#[wasm_bindgen]
+pub fn stitch(polylines: &Array, epsilon: f32, prune: bool, graph_stitch: bool) -> Array {
+    let input: Vec<Points<UnknownUnit>> = polylines
+        .iter()
+        .filter_map(|p| p.dyn_into::<Float32Array>().ok())
+        .map(|p| to_points(&p))
+        .collect();
+
+    let config = PipelineConfig {
+        epsilon: Epsilon::Absolute(epsilon),
+        prune: prune,
+        graph_stitch: graph_stitch,
+        ..PipelineConfig::default()
+    };
+
+    let output: Vec<PathSegment<UnknownUnit>> = run_pipeline(input, &config);
+    output.into_iter().map(to_array).map(JsValue::from).collect()
+}
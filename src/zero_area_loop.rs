@@ -1,24 +1,41 @@
 use ::*;
 use util::*;
 use euclid::approxeq::ApproxEq;
+use sanitize::sanitize_segments;
 
 type Rect<S> = euclid::TypedRect<f32, S>;
 
 ///
 /// TODO: Document
 ///
-pub fn remove_zero_area_loops<I, S: 'static>(segments: I, epsilon: f32) -> Vec<(Point<S>, Point<S>)>
+pub fn remove_zero_area_loops<I, S: 'static, E: Into<Epsilon>>(segments: I, epsilon: E) -> Vec<(Point<S>, Point<S>)>
+where
+    I: Into<Vec<(Point<S>, Point<S>)>>,
+{
+    remove_zero_area_loops_with_removed(segments, epsilon).0
+}
+
+/// Like `remove_zero_area_loops`, but also returns the lines that were
+/// removed, as `(kept, removed)`.  Both lines of every pair that cancelled
+/// out are removed, along with lines that are shorter than `epsilon`.
+pub fn remove_zero_area_loops_with_removed<I, S: 'static, E: Into<Epsilon>>(
+    segments: I,
+    epsilon: E,
+) -> (Vec<(Point<S>, Point<S>)>, Vec<(Point<S>, Point<S>)>)
 where
     I: Into<Vec<(Point<S>, Point<S>)>>,
 {
     let collected = segments.into();
     let aabb = compute_bounding_box(collected.iter().flat_map(|&(p1, p2)| vec![p1, p2]));
+    let epsilon = epsilon.into().resolve(&aabb);
     let aabb = aabb.inflate(1.0f32.max(aabb.size.width / 10.0), 1.0f32.max(aabb.size.height / 10.0));
     let mut quad_tree = QuadTree::default(aabb);
     let eps = Point::new(epsilon, epsilon);
+    let mut removed = vec![];
 
     'outer: for (p1a, p2a) in collected {
         if p1a.approx_eq_eps(&p2a, &eps) {
+            removed.push((p1a, p2a));
             continue;
         }
 
@@ -40,6 +57,8 @@ where
             // Our inverse is already in the tree
             if p1a.approx_eq_eps(&p2b, &eps) && p2a.approx_eq_eps(&p1b, &eps) {
                 quad_tree.remove(id);
+                removed.push((p1b, p2b));
+                removed.push((p1a, p2a));
                 continue 'outer;
             }
         }
@@ -47,5 +66,77 @@ where
         quad_tree.insert_with_box((p1a, p2a), query);
     }
 
-    quad_tree.iter().map(|(_, &(l, _))| l).collect()
+    (quad_tree.iter().map(|(_, &(l, _))| l).collect(), removed)
+}
+
+/// Cuts the spurs out of the paths: places where a path runs out to a point
+/// and straight back along the same line, as in `a, x, y, x, b`, which
+/// becomes `a, x, b`.  Spurs that retrace several corners, like
+/// `x, y, z, y, x`, are cut out all at once.
+///
+/// These show up when `connect_obvious` joins a fragment onto a segment that
+/// retraces it, and are drawn as a blob of ink at the turnaround.  Closed
+/// segments are looked at all the way around, and any segment that turns
+/// out to be nothing but a spur is dropped, just like the lines that cancel
+/// out in `remove_zero_area_loops`.
+pub fn remove_zero_area_spurs<S, E: Into<Epsilon>>(segments: Vec<PathSegment<S>>, epsilon: E) -> Vec<PathSegment<S>> {
+    let segments = sanitize_segments(segments);
+    let epsilon = epsilon.into().resolve_segments(&segments);
+    let eps = Point::new(epsilon, epsilon);
+
+    segments
+        .into_iter()
+        .filter_map(|segment| {
+            let closed = segment.closed;
+            let mut kept: Vec<Point<S>> = Vec::with_capacity(segment.path.len());
+            for &p in &segment.path {
+                if kept.last().map_or(false, |last| last.approx_eq_eps(&p, &eps)) {
+                    continue;
+                }
+                // Coming back to the point before the last one makes the
+                // last one the tip of a spur.
+                if kept.len() >= 2 && kept[kept.len() - 2].approx_eq_eps(&p, &eps) {
+                    kept.pop();
+                    continue;
+                }
+                kept.push(p);
+            }
+
+            if closed {
+                // The start of a loop is as good a place for a spur as any.
+                loop {
+                    let n = kept.len();
+                    if n >= 2 && kept[n - 1].approx_eq_eps(&kept[0], &eps) {
+                        kept.pop();
+                    } else if n >= 3 && kept[n - 2].approx_eq_eps(&kept[0], &eps) {
+                        kept.pop();
+                    } else if n >= 3 && kept[n - 1].approx_eq_eps(&kept[1], &eps) {
+                        kept.remove(0);
+                    } else {
+                        break;
+                    }
+                }
+                if kept.len() < 3 {
+                    return None;
+                }
+            } else if kept.len() < 2 {
+                return None;
+            }
+
+            if kept.len() == segment.path.len() {
+                return Some(segment);
+            }
+            // Not `PathSegment::new`, which would close an open path that
+            // happens to end where it started.
+            let mut trimmed = PathSegment {
+                path: SmallVec::from_vec(kept),
+                closed: closed,
+                layer: segment.layer,
+                length_2: 0.0,
+                length: 0.0,
+            };
+            trimmed.update_lengths();
+            Some(trimmed)
+        })
+        .collect()
 }
@@ -0,0 +1,58 @@
+//! Runs the `line-stitch` binary on the files in `tests/fixtures`.
+
+use std::path::Path;
+use std::process::Command;
+
+fn line_stitch(args: &[&str]) -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_line-stitch"))
+        .current_dir(fixtures)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "line-stitch failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn joins_a_text_drawing_into_a_closed_loop() {
+    let svg = line_stitch(&["lines.txt"]);
+    assert_eq!(svg.matches("<polygon").count(), 1);
+    assert_eq!(svg.matches("<polyline").count(), 1);
+}
+
+#[test]
+fn short_paths_are_dropped() {
+    let svg = line_stitch(&["--prune-len", "0.5", "lines.txt"]);
+    assert_eq!(svg.matches("<polygon").count(), 1);
+    assert_eq!(svg.matches("<polyline").count(), 0);
+}
+
+#[test]
+fn svg_transforms_are_applied() {
+    // The pieces only meet up once the group transforms are applied, and
+    // the nested `scale(2)` puts the path on the corner at (11, 21).
+    let gcode = line_stitch(&["--format", "gcode", "square.svg"]);
+    let pen_downs = gcode.matches("G1 Z0").count();
+    assert_eq!(pen_downs, 2);
+    for corner in &["X10 Y20", "X11 Y20", "X11 Y21", "X10 Y21"] {
+        assert!(gcode.contains(corner), "{} missing from\n{}", corner, gcode);
+    }
+    assert!(gcode.contains("X51 Y50"));
+}
+
+#[test]
+fn unsupported_transforms_are_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_line-stitch"))
+        .arg("--format")
+        .arg("gcode")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bad_transform.svg"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unsupported transform"));
+}